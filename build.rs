@@ -1,4 +1,85 @@
+use std::path::{Path, PathBuf};
+
 fn main() {
     #[cfg(not(debug_assertions))]
     embed_resource::compile("jonitor.rc", embed_resource::NONE);
+
+    if std::env::var_os("CARGO_FEATURE_EMBED_WEB").is_some() {
+        embed_web();
+    }
+}
+
+/// Writes `web_assets.rs` to OUT_DIR, the files of the built web application in `web/dist` with
+/// their precompressed `.gz` and `.br` variants, included by `src/web.rs`
+fn embed_web() {
+    let dist_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("web")
+        .join("dist");
+
+    println!("cargo:rerun-if-changed={}", dist_dir.to_string_lossy());
+
+    if !dist_dir.join("index.html").is_file() {
+        panic!(
+            "{} does not exist. Build the web application with `npm run build` in the web folder first",
+            dist_dir.join("index.html").to_string_lossy()
+        );
+    }
+
+    let mut files = Vec::new();
+    list_files(&dist_dir, &mut files);
+    files.sort();
+
+    let mut out = String::from("&[\n");
+
+    for path in files {
+        let ext = path.extension().map(|x| x.to_string_lossy().into_owned());
+        // variants are included with the original file
+        if matches!(ext.as_deref(), Some("gz" | "br")) {
+            continue;
+        }
+
+        let url_path: Vec<String> = path
+            .strip_prefix(&dist_dir)
+            .unwrap()
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        let variant = |ext: &str| {
+            let mut variant = path.as_os_str().to_owned();
+            variant.push(ext);
+            let variant = PathBuf::from(variant);
+
+            if variant.is_file() {
+                format!("Some(include_bytes!({:?}))", variant)
+            } else {
+                String::from("None")
+            }
+        };
+
+        out.push_str(&format!(
+            "    EmbeddedFile {{ path: {:?}, content: include_bytes!({:?}), gzip: {}, brotli: {} }},\n",
+            format!("/{}", url_path.join("/")),
+            path,
+            variant(".gz"),
+            variant(".br")
+        ));
+    }
+
+    out.push(']');
+
+    let out_path = Path::new(&std::env::var("OUT_DIR").unwrap()).join("web_assets.rs");
+    std::fs::write(out_path, out).expect("Failed to write web_assets.rs");
+}
+
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).expect("Failed to read web/dist") {
+        let path = entry.expect("Failed to read web/dist").path();
+
+        if path.is_dir() {
+            list_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
 }
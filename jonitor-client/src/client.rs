@@ -0,0 +1,461 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use reqwest::header::{IF_MATCH, IF_NONE_MATCH};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::frame::{self, DeltaDecoder, Encoding, Frame, Precision};
+use crate::{DashboardInfo, Error, RevisionInfo, SensorInfo};
+
+/// HTTP client of a Jonitor instance
+#[derive(Clone)]
+pub struct Client {
+    // e.g. http://127.0.0.1:10110, without the trailing slash
+    base_url: String,
+    // API token sent as a bearer token
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// Creates a client for the Jonitor instance at `base_url`, e.g. `http://127.0.0.1:10110`
+    pub fn new<S: Into<String>>(base_url: S) -> Client {
+        let mut base_url = base_url.into();
+
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+
+        Client {
+            base_url,
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Authenticates every request with an API token, for a Jonitor instance with
+    /// authentication enabled
+    pub fn with_token<S: Into<String>>(mut self, token: S) -> Client {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Requests `GET /sensors`
+    pub async fn sensors(&self) -> Result<SensorInfo, Error> {
+        self.get_json(&format!("{}/sensors", self.base_url)).await
+    }
+
+    /// Requests `GET /configs/:name`, the charts config of a dashboard as shown by the web
+    /// application, with the template instantiated and the selectors resolved
+    pub async fn dashboard(&self, name: &str) -> Result<serde_json::Value, Error> {
+        self.get_json(&format!("{}/configs/{name}", self.base_url))
+            .await
+    }
+
+    /// Requests `GET /api/dashboards`, the saved dashboards
+    pub async fn dashboards(&self) -> Result<Vec<DashboardInfo>, Error> {
+        self.get_json(&format!("{}/api/dashboards", self.base_url))
+            .await
+    }
+
+    /// Requests `GET /api/dashboards/:name`, a dashboard as it is saved, for editing
+    pub async fn saved_dashboard(&self, name: &str) -> Result<SavedDashboard, Error> {
+        let url = format!("{}/api/dashboards/{name}", self.base_url);
+
+        self.get_content(&url).await
+    }
+
+    /// Creates or replaces a dashboard with `PUT /api/dashboards/:name`. The dashboard is only
+    /// created if `etag` is `None`, and only replaced if it has not been changed since `etag`,
+    /// which is `*` to replace it whatever its content is.
+    pub async fn save_dashboard(
+        &self,
+        name: &str,
+        content: &serde_json::Value,
+        etag: Option<&str>,
+    ) -> Result<SaveResult, Error> {
+        let url = format!("{}/api/dashboards/{name}", self.base_url);
+
+        let request = match etag {
+            Some(etag) => self.http.put(url).header(IF_MATCH, etag),
+            None => self.http.put(url).header(IF_NONE_MATCH, "*"),
+        };
+
+        let body = serde_json::to_vec_pretty(content).map_err(|e| Error::Json(e.to_string()))?;
+
+        let response = self.send(request.body(body)).await?;
+
+        Ok(SaveResult {
+            created: response.status() == reqwest::StatusCode::CREATED,
+            etag: response_etag(&response)?,
+        })
+    }
+
+    /// Deletes a dashboard with `DELETE /api/dashboards/:name` if it has not been changed since
+    /// `etag`. Its revisions are kept.
+    pub async fn delete_dashboard(&self, name: &str, etag: &str) -> Result<(), Error> {
+        let url = format!("{}/api/dashboards/{name}", self.base_url);
+
+        self.send(self.http.delete(url).header(IF_MATCH, etag))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Renames a dashboard with `POST /api/dashboards/:name/rename` if it has not been changed
+    /// since `etag`
+    pub async fn rename_dashboard(
+        &self,
+        name: &str,
+        new_name: &str,
+        etag: &str,
+    ) -> Result<(), Error> {
+        let url = format!("{}/api/dashboards/{name}/rename", self.base_url);
+
+        let request = self
+            .http
+            .post(url)
+            .header(IF_MATCH, etag)
+            .json(&serde_json::json!({ "name": new_name }));
+
+        self.send(request).await?;
+
+        Ok(())
+    }
+
+    /// Requests `GET /api/dashboards/:name/revisions`, newest first
+    pub async fn revisions(&self, name: &str) -> Result<Vec<RevisionInfo>, Error> {
+        self.get_json(&format!(
+            "{}/api/dashboards/{name}/revisions",
+            self.base_url
+        ))
+        .await
+    }
+
+    /// Requests `GET /api/dashboards/:name/revisions/:id`
+    pub async fn revision(&self, name: &str, id: u64) -> Result<SavedDashboard, Error> {
+        let url = format!("{}/api/dashboards/{name}/revisions/{id}", self.base_url);
+
+        self.get_content(&url).await
+    }
+
+    /// Requests `GET /api/dashboards/:name/diff`, the differences between two revisions in the
+    /// unified diff format. The current content is compared when `to` is `None`.
+    pub async fn diff(&self, name: &str, from: u64, to: Option<u64>) -> Result<String, Error> {
+        let mut url = format!("{}/api/dashboards/{name}/diff?from={from}", self.base_url);
+
+        if let Some(to) = to {
+            url.push_str(&format!("&to={to}"));
+        }
+
+        let response = self.send(self.http.get(url)).await?;
+
+        response
+            .text()
+            .await
+            .map_err(|e| Error::Http(e.to_string()))
+    }
+
+    /// Saves a revision as the current content with
+    /// `POST /api/dashboards/:name/revisions/:id/restore`. Replacing an existing dashboard
+    /// requires `etag`.
+    pub async fn restore_revision(
+        &self,
+        name: &str,
+        id: u64,
+        etag: Option<&str>,
+    ) -> Result<SaveResult, Error> {
+        let url = format!(
+            "{}/api/dashboards/{name}/revisions/{id}/restore",
+            self.base_url
+        );
+
+        let mut request = self.http.post(url);
+
+        if let Some(etag) = etag {
+            request = request.header(IF_MATCH, etag);
+        }
+
+        let response = self.send(request).await?;
+
+        Ok(SaveResult {
+            created: response.status() == reqwest::StatusCode::CREATED,
+            etag: response_etag(&response)?,
+        })
+    }
+
+    /// Creates a stream of sensor information and data frames from `/data`. The stream connects
+    /// when `DataStream::next()` is first called.
+    pub fn data_stream(&self, options: StreamOptions) -> DataStream {
+        DataStream {
+            client: self.clone(),
+            reconnect_delay: options.min_reconnect_delay,
+            options,
+            socket: None,
+            decoder: None,
+            sensor_info_updated_count: None,
+            pending_frame: None,
+            has_disconnected: false,
+        }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let response = self.send(self.http.get(url)).await?;
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Http(e.to_string()))?;
+
+        serde_json::from_slice(&bytes).map_err(|e| Error::Json(e.to_string()))
+    }
+
+    async fn get_content(&self, url: &str) -> Result<SavedDashboard, Error> {
+        let response = self.send(self.http.get(url)).await?;
+        let etag = response_etag(&response)?;
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Http(e.to_string()))?;
+
+        Ok(SavedDashboard {
+            content: serde_json::from_slice(&bytes).map_err(|e| Error::Json(e.to_string()))?,
+            etag,
+        })
+    }
+
+    /// Sends a request with the API token, and returns an error for a non-success status code
+    async fn send(&self, mut request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Http(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Status(response.status().as_u16()));
+        }
+
+        Ok(response)
+    }
+
+    fn websocket_url(&self, options: &StreamOptions) -> String {
+        let base_url = if let Some(rest) = self.base_url.strip_prefix("https://") {
+            format!("wss://{rest}")
+        } else if let Some(rest) = self.base_url.strip_prefix("http://") {
+            format!("ws://{rest}")
+        } else {
+            self.base_url.clone()
+        };
+
+        match options.encoding {
+            Encoding::Full => format!("{base_url}/data"),
+            Encoding::Delta => format!(
+                "{base_url}/data?encoding={}&precision={}",
+                options.encoding.as_str(),
+                options.precision.as_str()
+            ),
+        }
+    }
+}
+
+/// Content of a saved dashboard or revision
+#[derive(Clone, Debug)]
+pub struct SavedDashboard {
+    pub content: serde_json::Value,
+    /// Sent back to change the dashboard only if it has not been changed by someone else
+    pub etag: String,
+}
+
+/// Result of saving a dashboard
+#[derive(Clone, Debug)]
+pub struct SaveResult {
+    /// The new ETag of the dashboard
+    pub etag: String,
+    /// `false` if an existing dashboard was replaced
+    pub created: bool,
+}
+
+/// Returns the ETag header of a response
+fn response_etag(response: &reqwest::Response) -> Result<String, Error> {
+    response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|x| x.to_str().ok())
+        .map(str::to_owned)
+        .ok_or_else(|| Error::Http(String::from("the response has no ETag")))
+}
+
+#[derive(Clone, Debug)]
+pub struct StreamOptions {
+    pub encoding: Encoding,
+    /// Only used with `Encoding::Delta`
+    pub precision: Precision,
+    /// Reconnect after the connection is lost, instead of ending the stream
+    pub reconnect: bool,
+    /// Delay before the first reconnection attempt, doubled after each failed attempt
+    pub min_reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+}
+
+impl Default for StreamOptions {
+    fn default() -> StreamOptions {
+        StreamOptions {
+            encoding: Encoding::Full,
+            precision: Precision::F64,
+            reconnect: true,
+            min_reconnect_delay: Duration::from_secs(1),
+            max_reconnect_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// The list of sensors/readings, sent after connecting and whenever it changes. The data
+    /// frames that follow use the layout described by it.
+    Sensors(SensorInfo),
+    Data(Frame),
+    /// The connection is lost or could not be established
+    Disconnected(Error),
+}
+
+/// Stream of events from `/data`, see `Client::data_stream()`
+pub struct DataStream {
+    client: Client,
+    options: StreamOptions,
+    socket: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    // `None` when the full encoding is used
+    decoder: Option<DeltaDecoder>,
+    // updated_count of the last sensor information returned
+    sensor_info_updated_count: Option<u64>,
+    // data frame to be returned after the updated sensor information
+    pending_frame: Option<Frame>,
+    has_disconnected: bool,
+    reconnect_delay: Duration,
+}
+
+impl DataStream {
+    /// Waits for the next event. Returns `None` when the connection is lost and reconnecting is
+    /// disabled.
+    pub async fn next(&mut self) -> Option<Event> {
+        if let Some(frame) = self.pending_frame.take() {
+            return Some(Event::Data(frame));
+        }
+
+        loop {
+            let socket = match self.socket.as_mut() {
+                Some(x) => x,
+                None => {
+                    if self.has_disconnected {
+                        if !self.options.reconnect {
+                            return None;
+                        }
+
+                        tokio::time::sleep(self.reconnect_delay).await;
+
+                        self.reconnect_delay =
+                            (self.reconnect_delay * 2).min(self.options.max_reconnect_delay);
+                    }
+
+                    return match self.connect().await {
+                        Ok(sensor_info) => {
+                            self.reconnect_delay = self.options.min_reconnect_delay;
+
+                            Some(Event::Sensors(sensor_info))
+                        }
+                        Err(e) => Some(self.disconnect(e)),
+                    };
+                }
+            };
+
+            let bytes = match socket.next().await {
+                Some(Ok(Message::Binary(x))) => x,
+                Some(Ok(Message::Close(close_frame))) => {
+                    let reason = close_frame.map(|x| x.reason.into_owned());
+
+                    return Some(self.disconnect(Error::Closed(reason)));
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Some(self.disconnect(Error::WebSocket(e.to_string()))),
+                None => return Some(self.disconnect(Error::Closed(None))),
+            };
+
+            let frame = match self.decoder.as_mut() {
+                Some(decoder) => decoder.decode(&bytes).cloned(),
+                None => frame::decode_full(&bytes),
+            };
+
+            let frame = match frame {
+                Ok(x) => x,
+                Err(e) => return Some(self.disconnect(Error::Decode(e))),
+            };
+
+            if self.sensor_info_updated_count != Some(frame.sensor_info_updated_count) {
+                // the list of sensors/readings has changed
+                return match self.client.sensors().await {
+                    Ok(sensor_info) => {
+                        self.sensor_info_updated_count = Some(sensor_info.updated_count);
+                        self.pending_frame = Some(frame);
+
+                        Some(Event::Sensors(sensor_info))
+                    }
+                    Err(e) => Some(self.disconnect(e)),
+                };
+            }
+
+            return Some(Event::Data(frame));
+        }
+    }
+
+    async fn connect(&mut self) -> Result<SensorInfo, Error> {
+        let sensor_info = self.client.sensors().await?;
+
+        let mut request = self
+            .client
+            .websocket_url(&self.options)
+            .into_client_request()
+            .map_err(|e| Error::WebSocket(e.to_string()))?;
+
+        if let Some(token) = &self.client.token {
+            let value = HeaderValue::from_str(&format!("Bearer {token}"))
+                .map_err(|e| Error::WebSocket(e.to_string()))?;
+
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| Error::WebSocket(e.to_string()))?;
+
+        self.socket = Some(socket);
+        self.decoder = match self.options.encoding {
+            Encoding::Full => None,
+            Encoding::Delta => Some(DeltaDecoder::new(self.options.precision)),
+        };
+        self.sensor_info_updated_count = Some(sensor_info.updated_count);
+
+        Ok(sensor_info)
+    }
+
+    fn disconnect(&mut self, error: Error) -> Event {
+        self.socket = None;
+        self.decoder = None;
+        self.pending_frame = None;
+        self.has_disconnected = true;
+
+        Event::Disconnected(error)
+    }
+}
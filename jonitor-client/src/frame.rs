@@ -0,0 +1,381 @@
+//! Decoding of binary frames sent by `/data`.
+//!
+//! A full frame begins with an `i64` integer (last_poll_time) and an `u64` integer
+//! (sensor_info_updated_count), followed by 2 `f64` numbers on repeat, representing the current
+//! value and the highest value of each reading. The numbers are at offset 2 onwards when the
+//! frame is seen as an array of 8-byte values.
+//!
+//! A delta-encoded frame has 2 more `u32` integers in the header, the frame kind and the entry
+//! count. A keyframe is followed by every value starting from offset 2, and a delta frame is
+//! followed by pairs of an `u32` offset and a value, for the values that have changed since the
+//! previous frame. Values are `f64` or `f32` depending on the negotiated precision.
+
+use std::fmt;
+
+use crate::SensorData;
+
+/// Size of the header of a full frame
+pub const FULL_HEADER_SIZE: usize = 16;
+/// Size of the header of a delta-encoded frame
+pub const DELTA_HEADER_SIZE: usize = 24;
+
+pub const FRAME_KIND_KEYFRAME: u32 = 0;
+pub const FRAME_KIND_DELTA: u32 = 1;
+
+/// Encoding of the frames sent by `/data`, negotiated with the `encoding` query parameter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// Every value is sent in every frame
+    #[default]
+    Full,
+    /// Keyframes are sent periodically, and only changed values are sent in between
+    Delta,
+}
+
+impl Encoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Full => "full",
+            Encoding::Delta => "delta",
+        }
+    }
+}
+
+/// Precision of the values in delta-encoded frames, negotiated with the `precision` query
+/// parameter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    F32,
+    #[default]
+    F64,
+}
+
+impl Precision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Precision::F32 => "f32",
+            Precision::F64 => "f64",
+        }
+    }
+
+    /// Size of a value in bytes
+    pub fn value_size(&self) -> usize {
+        match self {
+            Precision::F32 => 4,
+            Precision::F64 => 8,
+        }
+    }
+}
+
+/// Values of all readings at a point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Last poll time from HWiNFO
+    pub last_poll_time: i64,
+    /// `updated_count` of the `SensorInfo` describing the layout of `values`
+    pub sensor_info_updated_count: u64,
+    /// Current value and highest value of all readings in sequence, indexed by `offset - 2`
+    pub values: Vec<f64>,
+}
+
+/// Current value and highest value of a reading
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReadingValue {
+    pub current: f64,
+    pub maximum: f64,
+}
+
+impl Frame {
+    /// Returns the value at `offset`, as used by `SensorData::offset`
+    pub fn value(&self, offset: u32) -> Option<f64> {
+        (offset as usize)
+            .checked_sub(2)
+            .and_then(|i| self.values.get(i).copied())
+    }
+
+    /// Returns the values of the reading at `reading_index` of `sensor`
+    pub fn reading(&self, sensor: &SensorData, reading_index: usize) -> Option<ReadingValue> {
+        let offset = sensor
+            .offset
+            .checked_add(u32::try_from(reading_index * 2).ok()?)?;
+
+        Some(ReadingValue {
+            current: self.value(offset)?,
+            maximum: self.value(offset + 1)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    /// The frame is shorter than its header
+    TooShort,
+    /// The size of the frame does not match its content
+    InvalidLength,
+    UnknownKind(u32),
+    /// A delta frame was received before a keyframe with the same sensor_info_updated_count
+    MissingKeyframe,
+    InvalidOffset(u32),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "the frame is shorter than its header"),
+            DecodeError::InvalidLength => {
+                write!(f, "the size of the frame does not match its content")
+            }
+            DecodeError::UnknownKind(kind) => write!(f, "unknown frame kind `{kind}`"),
+            DecodeError::MissingKeyframe => {
+                write!(f, "a delta frame was received before a keyframe")
+            }
+            DecodeError::InvalidOffset(offset) => write!(f, "invalid value offset `{offset}`"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes a full frame
+pub fn decode_full(bytes: &[u8]) -> Result<Frame, DecodeError> {
+    if bytes.len() < FULL_HEADER_SIZE {
+        return Err(DecodeError::TooShort);
+    }
+
+    if !(bytes.len() - FULL_HEADER_SIZE).is_multiple_of(8) {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    Ok(Frame {
+        last_poll_time: read_i64(&bytes[0..8]),
+        sensor_info_updated_count: read_u64(&bytes[8..16]),
+        values: bytes[FULL_HEADER_SIZE..]
+            .chunks_exact(8)
+            .map(read_f64)
+            .collect(),
+    })
+}
+
+/// Decodes delta-encoded frames, keeping the values of the last keyframe and the changes
+/// applied since then
+pub struct DeltaDecoder {
+    precision: Precision,
+    frame: Option<Frame>,
+}
+
+impl DeltaDecoder {
+    pub fn new(precision: Precision) -> DeltaDecoder {
+        DeltaDecoder {
+            precision,
+            frame: None,
+        }
+    }
+
+    /// Applies a delta-encoded frame and returns the resulting values of all readings
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<&Frame, DecodeError> {
+        if bytes.len() < DELTA_HEADER_SIZE {
+            return Err(DecodeError::TooShort);
+        }
+
+        let last_poll_time = read_i64(&bytes[0..8]);
+        let sensor_info_updated_count = read_u64(&bytes[8..16]);
+        let kind = read_u32(&bytes[16..20]);
+        let count = read_u32(&bytes[20..24]) as usize;
+        let body = &bytes[DELTA_HEADER_SIZE..];
+        let value_size = self.precision.value_size();
+
+        match kind {
+            FRAME_KIND_KEYFRAME => {
+                if body.len() != count * value_size {
+                    return Err(DecodeError::InvalidLength);
+                }
+
+                let values = body
+                    .chunks_exact(value_size)
+                    .map(|chunk| self.read_value(chunk))
+                    .collect();
+
+                Ok(self.frame.insert(Frame {
+                    last_poll_time,
+                    sensor_info_updated_count,
+                    values,
+                }))
+            }
+            FRAME_KIND_DELTA => {
+                if body.len() != count * (4 + value_size) {
+                    return Err(DecodeError::InvalidLength);
+                }
+
+                let precision = self.precision;
+
+                let frame = match self.frame.as_mut() {
+                    Some(x) if x.sensor_info_updated_count == sensor_info_updated_count => x,
+                    _ => return Err(DecodeError::MissingKeyframe),
+                };
+
+                for entry in body.chunks_exact(4 + value_size) {
+                    let offset = read_u32(&entry[0..4]);
+
+                    let value = match (offset as usize)
+                        .checked_sub(2)
+                        .and_then(|i| frame.values.get_mut(i))
+                    {
+                        Some(x) => x,
+                        None => return Err(DecodeError::InvalidOffset(offset)),
+                    };
+
+                    *value = match precision {
+                        Precision::F32 => f64::from(f32::from_bits(read_u32(&entry[4..8]))),
+                        Precision::F64 => read_f64(&entry[4..12]),
+                    };
+                }
+
+                frame.last_poll_time = last_poll_time;
+
+                Ok(frame)
+            }
+            _ => Err(DecodeError::UnknownKind(kind)),
+        }
+    }
+
+    /// Returns the values decoded so far
+    pub fn frame(&self) -> Option<&Frame> {
+        self.frame.as_ref()
+    }
+
+    fn read_value(&self, bytes: &[u8]) -> f64 {
+        match self.precision {
+            Precision::F32 => f64::from(f32::from_bits(read_u32(bytes))),
+            Precision::F64 => read_f64(bytes),
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[0..4].try_into().unwrap())
+}
+
+fn read_i64(bytes: &[u8]) -> i64 {
+    i64::from_le_bytes(bytes[0..8].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[0..8].try_into().unwrap())
+}
+
+fn read_f64(bytes: &[u8]) -> f64 {
+    f64::from_le_bytes(bytes[0..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(kind: u32, count: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&10i64.to_le_bytes());
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        bytes.extend_from_slice(&kind.to_le_bytes());
+        bytes.extend_from_slice(&count.to_le_bytes());
+
+        bytes
+    }
+
+    #[test]
+    fn decode_full_frame() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&5i64.to_le_bytes());
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        bytes.extend_from_slice(&1.5f64.to_le_bytes());
+        bytes.extend_from_slice(&2.5f64.to_le_bytes());
+
+        let frame = decode_full(&bytes).unwrap();
+
+        assert_eq!(frame.last_poll_time, 5);
+        assert_eq!(frame.sensor_info_updated_count, 3);
+        assert_eq!(frame.values, vec![1.5, 2.5]);
+        assert_eq!(frame.value(2), Some(1.5));
+        assert_eq!(frame.value(1), None);
+        assert_eq!(frame.value(4), None);
+    }
+
+    #[test]
+    fn decode_full_invalid_length() {
+        assert_eq!(decode_full(&[0; 8]), Err(DecodeError::TooShort));
+        assert_eq!(decode_full(&[0; 20]), Err(DecodeError::InvalidLength));
+    }
+
+    #[test]
+    fn reading_values() {
+        let frame = Frame {
+            last_poll_time: 0,
+            sensor_info_updated_count: 0,
+            values: vec![1.0, 2.0, 3.0, 4.0],
+        };
+
+        let sensor = SensorData {
+            id: 1,
+            instance: 0,
+            name: String::from("CPU"),
+            readings: Vec::new(),
+            offset: 2,
+        };
+
+        assert_eq!(
+            frame.reading(&sensor, 1),
+            Some(ReadingValue {
+                current: 3.0,
+                maximum: 4.0
+            })
+        );
+        assert_eq!(frame.reading(&sensor, 2), None);
+    }
+
+    #[test]
+    fn delta_frame_applies_changes() {
+        let mut decoder = DeltaDecoder::new(Precision::F64);
+
+        let mut keyframe = header(FRAME_KIND_KEYFRAME, 2);
+        keyframe.extend_from_slice(&1.0f64.to_le_bytes());
+        keyframe.extend_from_slice(&2.0f64.to_le_bytes());
+        decoder.decode(&keyframe).unwrap();
+
+        let mut delta = header(FRAME_KIND_DELTA, 1);
+        delta.extend_from_slice(&3u32.to_le_bytes());
+        delta.extend_from_slice(&5.0f64.to_le_bytes());
+
+        assert_eq!(decoder.decode(&delta).unwrap().values, vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn delta_frame_errors() {
+        let mut decoder = DeltaDecoder::new(Precision::F32);
+
+        assert_eq!(decoder.decode(&[0; 10]), Err(DecodeError::TooShort));
+        assert_eq!(
+            decoder.decode(&header(7, 0)),
+            Err(DecodeError::UnknownKind(7))
+        );
+        assert_eq!(
+            decoder.decode(&header(FRAME_KIND_KEYFRAME, 1)),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decoder.decode(&header(FRAME_KIND_DELTA, 0)),
+            Err(DecodeError::MissingKeyframe)
+        );
+
+        let mut keyframe = header(FRAME_KIND_KEYFRAME, 1);
+        keyframe.extend_from_slice(&1.0f32.to_le_bytes());
+        decoder.decode(&keyframe).unwrap();
+
+        // offsets 0 and 1 are the header
+        let mut delta = header(FRAME_KIND_DELTA, 1);
+        delta.extend_from_slice(&1u32.to_le_bytes());
+        delta.extend_from_slice(&1.0f32.to_le_bytes());
+
+        assert_eq!(decoder.decode(&delta), Err(DecodeError::InvalidOffset(1)));
+    }
+}
@@ -0,0 +1,67 @@
+//! Models and clients for the HTTP and WebSocket API of Jonitor.
+//!
+//! The models and the frame decoder are always available. The HTTP and WebSocket clients require
+//! the `client` feature, which is enabled by default.
+//!
+//! # Example
+//! ```no_run
+//! use jonitor_client::{Client, Event, StreamOptions};
+//!
+//! # async fn run() {
+//! let client = Client::new("http://127.0.0.1:10110");
+//! let mut stream = client.data_stream(StreamOptions::default());
+//!
+//! let mut sensor_info = None;
+//!
+//! while let Some(event) = stream.next().await {
+//!     match event {
+//!         Event::Sensors(x) => sensor_info = Some(x),
+//!         Event::Data(frame) => {
+//!             let sensor = &sensor_info.as_ref().unwrap().sensors[0];
+//!             println!("{:?}", frame.reading(sensor, 0));
+//!         }
+//!         Event::Disconnected(e) => println!("Disconnected: {e}"),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::fmt;
+
+#[cfg(feature = "client")]
+mod client;
+pub mod frame;
+mod models;
+
+#[cfg(feature = "client")]
+pub use client::{Client, DataStream, Event, SaveResult, SavedDashboard, StreamOptions};
+pub use models::{DashboardInfo, ReadingData, ReadingType, RevisionInfo, SensorData, SensorInfo};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be received
+    Http(String),
+    /// The server responded with a non-success status code
+    Status(u16),
+    Json(String),
+    WebSocket(String),
+    /// The WebSocket connection is closed, with the reason sent by the server if any
+    Closed(Option<String>),
+    Decode(frame::DecodeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "HTTP error: {e}"),
+            Error::Status(status) => write!(f, "unexpected status code {status}"),
+            Error::Json(e) => write!(f, "failed to parse the response: {e}"),
+            Error::WebSocket(e) => write!(f, "WebSocket error: {e}"),
+            Error::Closed(Some(reason)) => write!(f, "connection closed: {reason}"),
+            Error::Closed(None) => write!(f, "connection closed"),
+            Error::Decode(e) => write!(f, "failed to decode a data frame: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,122 @@
+/// Sensor information returned by `GET /sensors`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SensorInfo {
+    /// Incremented when the list of sensors/readings changes
+    #[serde(rename = "updatedCount")]
+    pub updated_count: u64,
+    pub sensors: Vec<SensorData>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SensorData {
+    pub id: u32,
+    pub instance: u32,
+    pub name: String,
+    pub readings: Vec<ReadingData>,
+    /// Offset of the current value of the first reading in a data frame. The current value and
+    /// the highest value of the reading at index `i` are at `offset + i * 2` and
+    /// `offset + i * 2 + 1`.
+    pub offset: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReadingData {
+    pub id: u32,
+    pub name: String,
+    pub unit: String,
+    // missing in the sensor information sent by Jonitor 1.1.0 and older
+    #[serde(rename = "type", default)]
+    pub reading_type: ReadingType,
+}
+
+/// Type of a reading as reported by HWiNFO
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingType {
+    #[default]
+    None,
+    Temperature,
+    Voltage,
+    Fan,
+    Current,
+    Power,
+    Clock,
+    Usage,
+    Other,
+}
+
+impl ReadingType {
+    pub const ALL: [ReadingType; 9] = [
+        ReadingType::None,
+        ReadingType::Temperature,
+        ReadingType::Voltage,
+        ReadingType::Fan,
+        ReadingType::Current,
+        ReadingType::Power,
+        ReadingType::Clock,
+        ReadingType::Usage,
+        ReadingType::Other,
+    ];
+
+    /// Converts `SENSOR_READING_TYPE` of the HWiNFO shared memory
+    pub fn from_hwinfo(value: i32) -> ReadingType {
+        match value {
+            0 => ReadingType::None,
+            1 => ReadingType::Temperature,
+            2 => ReadingType::Voltage,
+            3 => ReadingType::Fan,
+            4 => ReadingType::Current,
+            5 => ReadingType::Power,
+            6 => ReadingType::Clock,
+            7 => ReadingType::Usage,
+            _ => ReadingType::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingType::None => "none",
+            ReadingType::Temperature => "temperature",
+            ReadingType::Voltage => "voltage",
+            ReadingType::Fan => "fan",
+            ReadingType::Current => "current",
+            ReadingType::Power => "power",
+            ReadingType::Clock => "clock",
+            ReadingType::Usage => "usage",
+            ReadingType::Other => "other",
+        }
+    }
+
+    /// Parses the name returned by `as_str()`
+    pub fn from_name(name: &str) -> Option<ReadingType> {
+        ReadingType::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+}
+
+/// A saved dashboard returned by `GET /api/dashboards`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardInfo {
+    pub name: String,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified: Option<u64>,
+    pub etag: String,
+    /// `None` if the file is not a valid dashboard
+    pub group_count: Option<usize>,
+    pub chart_count: Option<usize>,
+}
+
+/// A saved version of a dashboard returned by `GET /api/dashboards/:name/revisions`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RevisionInfo {
+    pub id: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// The user who saved the revision, `None` when authentication is disabled
+    pub author: Option<String>,
+    pub size: u64,
+    pub etag: String,
+}
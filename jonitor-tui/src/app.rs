@@ -0,0 +1,292 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use jonitor_client::frame::Frame;
+use jonitor_client::{Event, SensorInfo};
+use ratatui::widgets::TableState;
+
+use crate::dashboard::{Dataset, Display};
+
+/// Number of values kept for the sparkline of each reading
+pub(crate) const HISTORY_LENGTH: usize = 30;
+
+pub(crate) struct App {
+    pub(crate) base_url: String,
+    // name of the dashboard whose datasets are shown instead of every reading
+    pub(crate) dashboard: Option<String>,
+    pub(crate) status: String,
+    pub(crate) rows: Vec<Row>,
+    pub(crate) search: String,
+    pub(crate) is_searching: bool,
+    pub(crate) sort_column: SortColumn,
+    pub(crate) sort_descending: bool,
+    pub(crate) table_state: TableState,
+}
+
+pub(crate) struct Row {
+    // sensor id, sensor instance and reading id
+    key: (u32, u32, u32),
+    pub(crate) sensor_name: String,
+    pub(crate) reading_name: String,
+    pub(crate) unit: String,
+    // offset of the current value in data frames, `None` if the reading of a dataset is not found
+    offset: Option<u32>,
+    pub(crate) display: Display,
+    pub(crate) current: Option<f64>,
+    // lowest value seen since the reading was first received
+    pub(crate) minimum: Option<f64>,
+    // highest value reported by HWiNFO
+    pub(crate) maximum: Option<f64>,
+    pub(crate) history: VecDeque<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortColumn {
+    // the order of sensors/readings in HWiNFO
+    None,
+    Sensor,
+    Reading,
+    Value,
+    Minimum,
+    Maximum,
+}
+
+impl SortColumn {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            SortColumn::None => "HWiNFO order",
+            SortColumn::Sensor => "sensor",
+            SortColumn::Reading => "reading",
+            SortColumn::Value => "value",
+            SortColumn::Minimum => "min",
+            SortColumn::Maximum => "max",
+        }
+    }
+
+    fn next(&self) -> SortColumn {
+        match self {
+            SortColumn::None => SortColumn::Sensor,
+            SortColumn::Sensor => SortColumn::Reading,
+            SortColumn::Reading => SortColumn::Value,
+            SortColumn::Value => SortColumn::Minimum,
+            SortColumn::Minimum => SortColumn::Maximum,
+            SortColumn::Maximum => SortColumn::None,
+        }
+    }
+}
+
+impl App {
+    /// Returns the application showing every reading, or the datasets of a dashboard in the
+    /// order of its charts
+    pub(crate) fn new(base_url: &str, dashboard: Option<(String, Vec<Dataset>)>) -> App {
+        let (dashboard, rows) = match dashboard {
+            Some((name, datasets)) => (
+                Some(name),
+                datasets
+                    .into_iter()
+                    .map(|x| Row {
+                        key: x.key,
+                        sensor_name: x.chart_title,
+                        reading_name: x.label,
+                        unit: x.unit,
+                        offset: None,
+                        display: x.display,
+                        current: None,
+                        minimum: None,
+                        maximum: None,
+                        history: VecDeque::with_capacity(HISTORY_LENGTH),
+                    })
+                    .collect(),
+            ),
+            None => (None, Vec::with_capacity(0)),
+        };
+
+        App {
+            base_url: base_url.to_owned(),
+            dashboard,
+            status: String::from("Connecting..."),
+            rows,
+            search: String::with_capacity(0),
+            is_searching: false,
+            sort_column: SortColumn::None,
+            sort_descending: false,
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub(crate) fn handle_stream_event(&mut self, event: Event) {
+        match event {
+            Event::Sensors(sensor_info) => {
+                self.status = String::from("Connected");
+                self.update_sensors(sensor_info);
+            }
+            Event::Data(frame) => self.update_values(&frame),
+            Event::Disconnected(e) => self.status = format!("Disconnected: {e}. Reconnecting..."),
+        }
+    }
+
+    /// Handles a key press. Returns `false` when the application should be closed.
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+
+        if self.is_searching {
+            match key.code {
+                KeyCode::Enter => self.is_searching = false,
+                KeyCode::Esc => {
+                    self.is_searching = false;
+                    self.search.clear();
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Char(c) => self.search.push(c),
+                _ => {}
+            }
+
+            self.table_state.select(Some(0));
+
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => self.is_searching = true,
+            KeyCode::Char('s') => self.sort_column = self.sort_column.next(),
+            KeyCode::Char('r') => self.sort_descending = !self.sort_descending,
+            KeyCode::Down | KeyCode::Char('j') => self.table_state.scroll_down_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.table_state.scroll_up_by(1),
+            KeyCode::PageDown => self.table_state.scroll_down_by(20),
+            KeyCode::PageUp => self.table_state.scroll_up_by(20),
+            KeyCode::Home => self.table_state.select_first(),
+            KeyCode::End => self.table_state.select_last(),
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Returns the rows matching the search text, in the selected order
+    pub(crate) fn visible_rows(&self) -> Vec<&Row> {
+        let search = self.search.to_lowercase();
+
+        let mut rows = self
+            .rows
+            .iter()
+            .filter(|row| {
+                search.is_empty()
+                    || row.sensor_name.to_lowercase().contains(&search)
+                    || row.reading_name.to_lowercase().contains(&search)
+                    || row.unit.to_lowercase().contains(&search)
+            })
+            .collect::<Vec<_>>();
+
+        match self.sort_column {
+            SortColumn::None => {}
+            SortColumn::Sensor => rows.sort_by(|a, b| a.sensor_name.cmp(&b.sensor_name)),
+            SortColumn::Reading => rows.sort_by(|a, b| a.reading_name.cmp(&b.reading_name)),
+            SortColumn::Value => rows.sort_by(|a, b| compare_values(a.current, b.current)),
+            SortColumn::Minimum => rows.sort_by(|a, b| compare_values(a.minimum, b.minimum)),
+            SortColumn::Maximum => rows.sort_by(|a, b| compare_values(a.maximum, b.maximum)),
+        }
+
+        if self.sort_descending {
+            rows.reverse();
+        }
+
+        rows
+    }
+
+    fn update_sensors(&mut self, sensor_info: SensorInfo) {
+        if self.dashboard.is_some() {
+            // the rows of a dashboard stay the same, only the readings are found again
+            for row in self.rows.iter_mut() {
+                row.offset = sensor_info.sensors.iter().find_map(|sensor| {
+                    let i = sensor
+                        .readings
+                        .iter()
+                        .position(|reading| (sensor.id, sensor.instance, reading.id) == row.key)?;
+
+                    Some(sensor.offset + i as u32 * 2)
+                });
+            }
+
+            return;
+        }
+
+        // keep the history of readings that still exist
+        let mut old_rows = self
+            .rows
+            .drain(..)
+            .map(|row| (row.key, row))
+            .collect::<HashMap<_, _>>();
+
+        for sensor in sensor_info.sensors.iter() {
+            for (i, reading) in sensor.readings.iter().enumerate() {
+                let key = (sensor.id, sensor.instance, reading.id);
+                let offset = sensor.offset + i as u32 * 2;
+
+                let row = match old_rows.remove(&key) {
+                    Some(mut row) => {
+                        row.sensor_name.clone_from(&sensor.name);
+                        row.reading_name.clone_from(&reading.name);
+                        row.unit.clone_from(&reading.unit);
+                        row.offset = Some(offset);
+
+                        row
+                    }
+                    None => Row {
+                        key,
+                        sensor_name: sensor.name.clone(),
+                        reading_name: reading.name.clone(),
+                        unit: reading.unit.clone(),
+                        offset: Some(offset),
+                        display: Display::default(),
+                        current: None,
+                        minimum: None,
+                        maximum: None,
+                        history: VecDeque::with_capacity(HISTORY_LENGTH),
+                    },
+                };
+
+                self.rows.push(row);
+            }
+        }
+    }
+
+    fn update_values(&mut self, frame: &Frame) {
+        for row in self.rows.iter_mut() {
+            let Some(offset) = row.offset else {
+                continue;
+            };
+
+            let (current, maximum) = match (frame.value(offset), frame.value(offset + 1)) {
+                (Some(current), Some(maximum)) => {
+                    (row.display.convert(current), row.display.convert(maximum))
+                }
+                _ => continue,
+            };
+
+            row.current = Some(current);
+            row.maximum = Some(maximum);
+            row.minimum = Some(row.minimum.map_or(current, |x| x.min(current)));
+
+            if row.history.len() == HISTORY_LENGTH {
+                row.history.pop_front();
+            }
+
+            row.history.push_back(current);
+        }
+    }
+}
+
+fn compare_values(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,283 @@
+use ratatui::style::Color;
+use serde_json::Value;
+
+/// Number of decimal places of the values, unless set by the dashboard
+const DEFAULT_DECIMALS: usize = 2;
+
+/// A dataset of a chart in the dashboard given on the command line
+pub(crate) struct Dataset {
+    pub(crate) chart_title: String,
+    pub(crate) label: String,
+    // sensor id, sensor instance and reading id
+    pub(crate) key: (u32, u32, u32),
+    pub(crate) unit: String,
+    pub(crate) display: Display,
+}
+
+/// How the values of a reading are converted and shown
+pub(crate) struct Display {
+    // the value is shown as `value * scale + offset`
+    scale: f64,
+    offset: f64,
+    pub(crate) decimals: usize,
+    // range of the history, the lowest and highest value in it if not set
+    pub(crate) minimum_value: Option<f64>,
+    pub(crate) maximum_value: Option<f64>,
+    // sorted by value
+    thresholds: Vec<Threshold>,
+}
+
+#[derive(Clone)]
+struct Threshold {
+    value: f64,
+    // `None` if the color cannot be shown in a terminal
+    color: Option<Color>,
+    label: Option<String>,
+}
+
+impl Default for Display {
+    fn default() -> Display {
+        Display {
+            scale: 1.0,
+            offset: 0.0,
+            decimals: DEFAULT_DECIMALS,
+            minimum_value: None,
+            maximum_value: None,
+            thresholds: Vec::with_capacity(0),
+        }
+    }
+}
+
+impl Display {
+    /// Returns the value in the unit of the dataset
+    pub(crate) fn convert(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    /// Returns the color and the label of the threshold which applies to a converted value, i.e.
+    /// the last threshold with a value not above it
+    pub(crate) fn threshold(&self, value: f64) -> Option<(Option<Color>, Option<&str>)> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|x| x.value <= value)
+            .map(|x| (x.color, x.label.as_deref()))
+    }
+}
+
+/// Returns the datasets of every chart of a dashboard returned by `GET /configs/:name`, in order
+pub(crate) fn datasets(config: &Value) -> Result<Vec<Dataset>, String> {
+    let groups = config
+        .as_array()
+        .ok_or_else(|| String::from("the dashboard is not an array of groups"))?;
+
+    let mut datasets = Vec::new();
+
+    for chart in groups
+        .iter()
+        .filter_map(|x| x.get("chartConfigs")?.as_array())
+        .flatten()
+    {
+        let chart_title = chart.get("title").and_then(Value::as_str).unwrap_or("");
+        let chart_type = chart.get("chartType").and_then(Value::as_str);
+        let chart_decimals = chart.get("decimals").and_then(Value::as_u64);
+        let minimum_value = chart.get("minimumValue").and_then(Value::as_f64);
+        // 0 if the chart type does not use it
+        let maximum_value = chart
+            .get("maximumValue")
+            .and_then(Value::as_f64)
+            .filter(|x| *x > 0.0);
+        let thresholds = chart
+            .get("thresholds")
+            .and_then(Value::as_array)
+            .map(|x| x.iter().filter_map(threshold).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let Some(chart_datasets) = chart.get("datasets").and_then(Value::as_array) else {
+            continue;
+        };
+
+        for (i, dataset) in chart_datasets.iter().enumerate() {
+            let id = |name: &str| {
+                dataset
+                    .get(name)
+                    .and_then(Value::as_u64)
+                    .and_then(|x| u32::try_from(x).ok())
+            };
+
+            // selectors which match no reading are left without ids
+            let (Some(sensor_id), Some(sensor_instance), Some(reading_id)) =
+                (id("sensorId"), id("sensorInstance"), id("readingId"))
+            else {
+                continue;
+            };
+
+            let number = |name: &str| dataset.get(name).and_then(Value::as_f64);
+
+            // the first dataset of a scatter chart is shown on the x axis
+            let maximum_value = if chart_type == Some("scatter") && i == 0 {
+                chart
+                    .get("xMaximumValue")
+                    .and_then(Value::as_f64)
+                    .filter(|x| *x > 0.0)
+            } else {
+                maximum_value
+            };
+
+            datasets.push(Dataset {
+                chart_title: chart_title.to_owned(),
+                label: dataset
+                    .get("label")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_owned(),
+                key: (sensor_id, sensor_instance, reading_id),
+                unit: dataset
+                    .get("unit")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_owned(),
+                display: Display {
+                    scale: number("scale").unwrap_or(1.0),
+                    offset: number("offset").unwrap_or(0.0),
+                    decimals: dataset
+                        .get("decimals")
+                        .and_then(Value::as_u64)
+                        .or(chart_decimals)
+                        .map_or(DEFAULT_DECIMALS, |x| x as usize),
+                    minimum_value,
+                    maximum_value,
+                    thresholds: thresholds.clone(),
+                },
+            });
+        }
+    }
+
+    if datasets.is_empty() {
+        return Err(String::from("the dashboard has no dataset"));
+    }
+
+    Ok(datasets)
+}
+
+fn threshold(value: &Value) -> Option<Threshold> {
+    Some(Threshold {
+        value: value.get("value")?.as_f64()?,
+        color: value.get("color")?.as_str()?.parse().ok(),
+        label: value
+            .get("label")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn dashboard(charts: Value) -> Value {
+        json!([{ "gridBreakpoints": {}, "chartConfigs": charts }])
+    }
+
+    fn dataset(label: &str, reading_id: u32) -> Value {
+        json!({
+            "sensorId": 1,
+            "sensorInstance": 0,
+            "readingId": reading_id,
+            "label": label,
+            "unit": "°C",
+            "color": "#0066cc"
+        })
+    }
+
+    #[test]
+    fn datasets_are_converted() {
+        let mut fahrenheit = dataset("CPU", 1);
+        fahrenheit["scale"] = json!(1.8);
+        fahrenheit["offset"] = json!(32);
+        fahrenheit["unit"] = json!("°F");
+        fahrenheit["decimals"] = json!(0);
+
+        let config = dashboard(json!([{
+            "title": "Temperatures",
+            "chartType": "table",
+            "decimals": 1,
+            "datasets": [fahrenheit, dataset("GPU", 2)]
+        }]));
+
+        let datasets = datasets(&config).unwrap();
+
+        assert_eq!(datasets.len(), 2);
+        assert_eq!(datasets[0].chart_title, "Temperatures");
+        assert_eq!(datasets[0].key, (1, 0, 1));
+        assert_eq!(datasets[0].unit, "°F");
+        assert_eq!(datasets[0].display.convert(100.0), 212.0);
+        assert_eq!(datasets[0].display.decimals, 0);
+
+        // the decimals of the chart are used if the dataset has none
+        assert_eq!(datasets[1].display.convert(100.0), 100.0);
+        assert_eq!(datasets[1].display.decimals, 1);
+    }
+
+    #[test]
+    fn thresholds_apply_up_to_the_next_one() {
+        let config = dashboard(json!([{
+            "title": "CPU",
+            "chartType": "gauge",
+            "maximumValue": 100,
+            "minimumValue": 20,
+            "thresholds": [
+                { "value": 0, "color": "#2ecc71" },
+                { "value": 80, "color": "red", "label": "Hot" }
+            ],
+            "datasets": [dataset("CPU", 1)]
+        }]));
+
+        let datasets = datasets(&config).unwrap();
+        let display = &datasets[0].display;
+
+        assert_eq!(display.minimum_value, Some(20.0));
+        assert_eq!(display.maximum_value, Some(100.0));
+
+        assert!(display.threshold(-1.0).is_none());
+        assert_eq!(
+            display.threshold(50.0),
+            Some((Some(Color::Rgb(0x2e, 0xcc, 0x71)), None))
+        );
+        assert_eq!(
+            display.threshold(80.0),
+            Some((Some(Color::Red), Some("Hot")))
+        );
+    }
+
+    #[test]
+    fn scatter_charts_have_the_x_axis_range_first() {
+        let config = dashboard(json!([{
+            "title": "Fan",
+            "chartType": "scatter",
+            "maximumValue": 2000,
+            "xMaximumValue": 100,
+            "datasets": [dataset("CPU", 1), dataset("Fan", 2)]
+        }]));
+
+        let datasets = datasets(&config).unwrap();
+
+        assert_eq!(datasets[0].display.maximum_value, Some(100.0));
+        assert_eq!(datasets[1].display.maximum_value, Some(2000.0));
+    }
+
+    #[test]
+    fn datasets_without_ids_are_skipped() {
+        let config = dashboard(json!([{
+            "title": "CPU",
+            "chartType": "bar",
+            "maximumValue": 0,
+            "datasets": [{ "selector": { "sensor": "CPU*" }, "label": "", "unit": "%", "color": "#000000" }]
+        }]));
+
+        assert!(datasets(&config).is_err());
+        assert!(datasets(&json!({})).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crossterm::event::{Event, EventStream, KeyEventKind};
+use futures_util::StreamExt;
+use jonitor_client::frame::{Encoding, Precision};
+use jonitor_client::{Client, StreamOptions};
+use ratatui::DefaultTerminal;
+use tokio::runtime::Builder;
+
+mod app;
+mod dashboard;
+mod ui;
+
+const DEFAULT_URL: &str = "http://127.0.0.1:10110";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let url = args.next().unwrap_or_else(|| String::from(DEFAULT_URL));
+    let dashboard = args.next();
+
+    if url == "-h" || url == "--help" {
+        println!("Usage: jonitor-tui [URL] [DASHBOARD]\n\nURL defaults to {DEFAULT_URL}\nDASHBOARD shows the datasets of a dashboard instead of every reading\nSet JONITOR_TOKEN to the API token if authentication is enabled");
+
+        return;
+    }
+
+    let runtime = match Builder::new_current_thread().enable_all().build() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Failed to create a Tokio runtime: {e}");
+
+            return;
+        }
+    };
+
+    let mut terminal = ratatui::init();
+    let result = runtime.block_on(run(&mut terminal, url, dashboard));
+    ratatui::restore();
+
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+    }
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    url: String,
+    dashboard: Option<String>,
+) -> std::io::Result<()> {
+    let mut client = Client::new(url);
+    if let Ok(token) = std::env::var("JONITOR_TOKEN") {
+        client = client.with_token(token);
+    }
+
+    // the dashboard is loaded once, the readings of its datasets are found again when the
+    // sensors change
+    let dashboard = match dashboard {
+        Some(name) => {
+            let datasets = client
+                .dashboard(&name)
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|x| dashboard::datasets(&x))
+                .map_err(|e| {
+                    std::io::Error::other(format!("Failed to load the dashboard {name}: {e}"))
+                })?;
+
+            Some((name, datasets))
+        }
+        None => None,
+    };
+
+    let mut app = app::App::new(client.base_url(), dashboard);
+
+    // values rarely change between frames, so delta encoding saves bandwidth on slow links
+    let mut stream = client.data_stream(StreamOptions {
+        encoding: Encoding::Delta,
+        precision: Precision::F32,
+        ..StreamOptions::default()
+    });
+
+    // the stream runs in its own task as `DataStream::next()` is not cancellation safe
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(16);
+
+    tokio::spawn(async move {
+        while let Some(event) = stream.next().await {
+            if event_tx.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut terminal_events = EventStream::new();
+
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+
+        tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(x) => app.handle_stream_event(x),
+                None => return Ok(()),
+            },
+            event = terminal_events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    if !app.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            },
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::app::{App, HISTORY_LENGTH};
+use crate::dashboard::Display;
+
+const SPARKLINE_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub(crate) fn draw(frame: &mut Frame, app: &mut App) {
+    let [header_area, table_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let dashboard = match &app.dashboard {
+        Some(x) => format!("  dashboard {x}"),
+        None => String::with_capacity(0),
+    };
+
+    let header = Line::from(vec![
+        concat!("Jonitor TUI v", env!("CARGO_PKG_VERSION")).bold(),
+        format!("  {}{dashboard}  {}", app.base_url, app.status).into(),
+    ]);
+    frame.render_widget(header, header_area);
+
+    let rows = app.visible_rows();
+
+    let table_rows = rows
+        .iter()
+        .map(|row| {
+            Row::new(vec![
+                Cell::from(row.sensor_name.clone()),
+                Cell::from(row.reading_name.clone()),
+                value_cell(row.current, &row.unit, &row.display),
+                value_cell(row.minimum, &row.unit, &row.display),
+                value_cell(row.maximum, &row.unit, &row.display),
+                Cell::from(sparkline(row.history.iter().copied(), &row.display)),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let (sensor_header, reading_header, row_name) = match app.dashboard {
+        Some(_) => ("Chart", "Dataset", "datasets"),
+        None => ("Sensor", "Reading", "readings"),
+    };
+
+    let title = format!(
+        " {} {row_name}, sorted by {}{} ",
+        rows.len(),
+        app.sort_column.name(),
+        if app.sort_descending {
+            " (descending)"
+        } else {
+            ""
+        }
+    );
+
+    let table = Table::new(
+        table_rows,
+        [
+            Constraint::Fill(3),
+            Constraint::Fill(3),
+            // wider for the labels of thresholds
+            Constraint::Length(20),
+            Constraint::Length(20),
+            Constraint::Length(20),
+            Constraint::Length(HISTORY_LENGTH as u16),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            sensor_header,
+            reading_header,
+            "Value",
+            "Min",
+            "Max",
+            "History",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::bordered().title(title))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, table_area, &mut app.table_state);
+
+    let footer = if app.is_searching {
+        Paragraph::new(format!("Search: {}_", app.search))
+    } else if !app.search.is_empty() {
+        Paragraph::new(format!(
+            "Search: {}  (/ edit, s sort, r reverse, q quit)",
+            app.search
+        ))
+    } else {
+        Paragraph::new("/ search, s sort, r reverse, ↑↓ scroll, q quit")
+    };
+    frame.render_widget(footer, footer_area);
+}
+
+/// Returns the value with the decimals of the dataset, in the color and with the label of its
+/// threshold
+fn value_cell(value: Option<f64>, unit: &str, display: &Display) -> Cell<'static> {
+    let Some(value) = value else {
+        return Cell::from("-");
+    };
+
+    let text = format!("{value:.*} {unit}", display.decimals);
+
+    match display.threshold(value) {
+        Some((color, label)) => {
+            let text = match label {
+                Some(x) => format!("{text} {x}"),
+                None => text,
+            };
+
+            let style = match color {
+                Some(x) => Style::new().fg(x),
+                None => Style::new(),
+            };
+
+            Cell::from(text).style(style)
+        }
+        None => Cell::from(text),
+    }
+}
+
+/// Draws the values as a line of block characters, scaled between the minimum and maximum value
+/// of the dataset, or the lowest and the highest value if they are not set
+fn sparkline<I: Iterator<Item = f64> + Clone>(values: I, display: &Display) -> String {
+    let min = display
+        .minimum_value
+        .unwrap_or_else(|| values.clone().fold(f64::INFINITY, f64::min));
+    let max = display
+        .maximum_value
+        .unwrap_or_else(|| values.clone().fold(f64::NEG_INFINITY, f64::max));
+    let range = max - min;
+
+    values
+        .map(|value| {
+            if range <= 0.0 || !range.is_finite() {
+                return SPARKLINE_CHARS[0];
+            }
+
+            let level = ((value - min) / range * (SPARKLINE_CHARS.len() - 1) as f64).round();
+
+            SPARKLINE_CHARS[(level as usize).min(SPARKLINE_CHARS.len() - 1)]
+        })
+        .collect()
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::config::AccessConfig;
+use crate::listener::PeerAddr;
+use crate::log;
+
+/// A range of IP addresses in CIDR notation, e.g. `192.168.1.0/24`. A single address without a
+/// prefix length is also accepted.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct IpNet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNet {
+    pub(crate) fn parse(value: &str) -> Result<IpNet, String> {
+        let (addr, prefix_len) = match value.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (value, None),
+        };
+
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|e| format!("Invalid IP range `{value}`: {e}"))?;
+
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix_len {
+            Some(x) => match x.parse::<u8>() {
+                Ok(x) if x <= max_prefix_len => x,
+                _ => {
+                    return Err(format!(
+                        "Invalid IP range `{value}`: the prefix length should be 0 to {}",
+                        max_prefix_len
+                    ))
+                }
+            },
+            None => max_prefix_len,
+        };
+
+        Ok(IpNet { addr, prefix_len })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parsed `AccessConfig`
+#[derive(Default)]
+struct Rules {
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+    max_websocket_connections: Option<u32>,
+    max_connections_per_ip: Option<u32>,
+}
+
+impl Rules {
+    fn new(config: Option<&AccessConfig>) -> Rules {
+        let config = match config {
+            Some(x) => x,
+            None => return Rules::default(),
+        };
+
+        // validated by Config::validate()
+        let parse =
+            |values: &[String]| values.iter().filter_map(|x| IpNet::parse(x).ok()).collect();
+
+        Rules {
+            allow: parse(&config.allow),
+            deny: parse(&config.deny),
+            trusted_proxies: parse(&config.trusted_proxies),
+            max_websocket_connections: config.max_websocket_connections,
+            max_connections_per_ip: config.max_connections_per_ip,
+        }
+    }
+}
+
+/// IP allowlist and denylist, and limits on the number of connections
+pub(crate) struct Access {
+    rules: RwLock<Rules>,
+    // open connections of each client
+    connections: Mutex<HashMap<IpAddr, u32>>,
+    websocket_connections: Mutex<u32>,
+}
+
+impl Access {
+    pub(crate) fn new(config: Option<&AccessConfig>) -> Arc<Access> {
+        Arc::new(Access {
+            rules: RwLock::new(Rules::new(config)),
+            connections: Mutex::new(HashMap::new()),
+            websocket_connections: Mutex::new(0),
+        })
+    }
+
+    /// Replaces the rules. Open connections are not affected.
+    pub(crate) fn update(&self, config: Option<&AccessConfig>) {
+        *self.rules.write().unwrap() = Rules::new(config);
+    }
+
+    /// Returns whether a client is allowed by the allowlist and denylist. The denylist takes
+    /// precedence, and every address is allowed when the allowlist is empty.
+    fn is_allowed(&self, ip: IpAddr) -> bool {
+        let rules = self.rules.read().unwrap();
+
+        if rules.deny.iter().any(|x| x.contains(ip)) {
+            return false;
+        }
+
+        rules.allow.is_empty() || rules.allow.iter().any(|x| x.contains(ip))
+    }
+
+    /// Returns the address of a client for logging. Behind a trusted proxy, the client is the
+    /// rightmost address of `X-Forwarded-For` which is not a trusted proxy. Clients connected
+    /// through a Unix domain socket are trusted, as only a local reverse proxy can connect.
+    pub(crate) fn client_addr(&self, peer: PeerAddr, headers: &HeaderMap) -> ClientAddr {
+        let rules = self.rules.read().unwrap();
+        let is_trusted = |ip: IpAddr| rules.trusted_proxies.iter().any(|x| x.contains(ip));
+
+        if peer.ip().is_some_and(|x| !is_trusted(x)) {
+            return ClientAddr {
+                peer,
+                forwarded: None,
+            };
+        }
+
+        // every header is a comma separated list, in the order of the proxies
+        let values: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|x| x.to_str().ok())
+            .flat_map(|x| x.split(','))
+            .map(str::trim)
+            .collect();
+
+        let mut forwarded = None;
+
+        for value in values.into_iter().rev() {
+            // e.g. `unknown`, the addresses on its left are not known to be correct
+            let ip = match parse_forwarded_ip(value) {
+                Some(x) => x,
+                None => break,
+            };
+
+            forwarded = Some(ip);
+
+            if !is_trusted(ip) {
+                break;
+            }
+        }
+
+        ClientAddr { peer, forwarded }
+    }
+
+    /// Counts a new connection of a client, or returns `None` if the client has reached
+    /// `max_connections_per_ip`. The connection is counted until the returned value is dropped.
+    pub(crate) fn open_connection(self: &Arc<Self>, ip: IpAddr) -> Option<ConnectionSlot> {
+        let max = self.rules.read().unwrap().max_connections_per_ip;
+
+        let mut connections = self.connections.lock().unwrap();
+        let count = connections.entry(ip).or_insert(0);
+
+        if max.is_some_and(|x| *count >= x) {
+            return None;
+        }
+
+        *count += 1;
+
+        Some(ConnectionSlot {
+            access: self.clone(),
+            ip,
+        })
+    }
+
+    /// Counts a new WebSocket connection, or returns `None` if `max_websocket_connections` is
+    /// reached. The connection is counted until the returned value is dropped.
+    pub(crate) fn open_websocket(self: &Arc<Self>) -> Option<WebSocketSlot> {
+        let max = self.rules.read().unwrap().max_websocket_connections;
+
+        let mut count = self.websocket_connections.lock().unwrap();
+
+        if max.is_some_and(|x| *count >= x) {
+            return None;
+        }
+
+        *count += 1;
+
+        Some(WebSocketSlot {
+            access: self.clone(),
+        })
+    }
+}
+
+/// Address of a client and the proxy it is connected through
+#[derive(Clone, Copy)]
+pub(crate) struct ClientAddr {
+    peer: PeerAddr,
+    // taken from `X-Forwarded-For` when `peer` is a trusted proxy
+    forwarded: Option<IpAddr>,
+}
+
+impl fmt::Debug for ClientAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.forwarded {
+            Some(ip) => write!(f, "{ip} (via {:?})", self.peer),
+            None => write!(f, "{:?}", self.peer),
+        }
+    }
+}
+
+/// Parses an address of `X-Forwarded-For`, which may have a port, e.g. `[::1]:1234`
+fn parse_forwarded_ip(value: &str) -> Option<IpAddr> {
+    let ip = value
+        .parse::<IpAddr>()
+        .or_else(|_| value.parse::<SocketAddr>().map(|x| x.ip()))
+        .ok()?;
+
+    Some(ip.to_canonical())
+}
+
+/// An open connection counted towards `max_connections_per_ip`
+pub(crate) struct ConnectionSlot {
+    access: Arc<Access>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        let mut connections = self.access.connections.lock().unwrap();
+
+        if let Some(count) = connections.get_mut(&self.ip) {
+            *count -= 1;
+
+            if *count == 0 {
+                connections.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// An open WebSocket connection counted towards `max_websocket_connections`
+pub(crate) struct WebSocketSlot {
+    access: Arc<Access>,
+}
+
+impl Drop for WebSocketSlot {
+    fn drop(&mut self) {
+        *self.access.websocket_connections.lock().unwrap() -= 1;
+    }
+}
+
+/// Rejects clients which are not allowed by the allowlist and denylist with 403 Forbidden.
+/// Clients connected through a Unix domain socket are always allowed.
+pub(crate) async fn middleware(
+    State(access): State<Arc<Access>>,
+    ConnectInfo(addr): ConnectInfo<PeerAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(ip) = addr.ip() {
+        if !access.is_allowed(ip) {
+            log(format!("{:?} is rejected by the IP access rules", addr));
+
+            return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn contains(net: &str, addr: &str) -> bool {
+        IpNet::parse(net).unwrap().contains(ip(addr))
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert!(IpNet::parse("192.168.1.0/24").is_ok());
+        assert!(IpNet::parse("10.0.0.1").is_ok());
+        assert!(IpNet::parse("::1").is_ok());
+        assert!(IpNet::parse("fd00::/8").is_ok());
+        assert!(IpNet::parse("0.0.0.0/0").is_ok());
+
+        assert!(IpNet::parse("192.168.1.0/33").is_err());
+        assert!(IpNet::parse("::/129").is_err());
+        assert!(IpNet::parse("192.168.1.0/").is_err());
+        assert!(IpNet::parse("192.168.1.0/-1").is_err());
+        assert!(IpNet::parse("192.168.1/24").is_err());
+        assert!(IpNet::parse("localhost").is_err());
+    }
+
+    #[test]
+    fn contains_ipv4() {
+        assert!(contains("192.168.1.0/24", "192.168.1.0"));
+        assert!(contains("192.168.1.0/24", "192.168.1.255"));
+        assert!(!contains("192.168.1.0/24", "192.168.2.1"));
+
+        // the host bits of the range are ignored
+        assert!(contains("192.168.1.77/24", "192.168.1.1"));
+
+        // a single address without a prefix length
+        assert!(contains("10.0.0.1", "10.0.0.1"));
+        assert!(!contains("10.0.0.1", "10.0.0.2"));
+
+        assert!(contains("0.0.0.0/0", "203.0.113.5"));
+        assert!(contains("172.16.0.0/12", "172.31.255.255"));
+        assert!(!contains("172.16.0.0/12", "172.32.0.0"));
+    }
+
+    #[test]
+    fn contains_ipv6() {
+        assert!(contains("::1", "::1"));
+        assert!(!contains("::1", "::2"));
+        assert!(contains("fd00::/8", "fdab:1234::1"));
+        assert!(!contains("fd00::/8", "fe80::1"));
+        assert!(contains("::/0", "2001:db8::1"));
+        assert!(contains("2001:db8::/127", "2001:db8::1"));
+        assert!(!contains("2001:db8::/128", "2001:db8::1"));
+    }
+
+    #[test]
+    fn ipv4_and_ipv6_do_not_match() {
+        assert!(!contains("0.0.0.0/0", "::1"));
+        assert!(!contains("::/0", "127.0.0.1"));
+
+        // clients of a dual stack listener are compared by their IPv4 address
+        assert!(!contains("127.0.0.1", "::ffff:127.0.0.1"));
+        let peer = PeerAddr::Tcp("[::ffff:127.0.0.1]:50000".parse().unwrap());
+        assert!(IpNet::parse("127.0.0.1")
+            .unwrap()
+            .contains(peer.ip().unwrap()));
+    }
+
+    #[test]
+    fn deny_takes_precedence() {
+        let access = Access::new(Some(&AccessConfig {
+            allow: vec![String::from("192.168.1.0/24")],
+            deny: vec![String::from("192.168.1.13")],
+            ..Default::default()
+        }));
+
+        assert!(access.is_allowed(ip("192.168.1.12")));
+        assert!(!access.is_allowed(ip("192.168.1.13")));
+        assert!(!access.is_allowed(ip("10.0.0.1")));
+
+        assert!(Access::new(None).is_allowed(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn forwarded_client_behind_trusted_proxy() {
+        let access = Access::new(Some(&AccessConfig {
+            trusted_proxies: vec![String::from("127.0.0.1"), String::from("10.0.0.0/8")],
+            ..Default::default()
+        }));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "198.51.100.1, 203.0.113.5, 10.0.0.2".parse().unwrap(),
+        );
+
+        let proxy = PeerAddr::Tcp("127.0.0.1:50000".parse().unwrap());
+        let client = access.client_addr(proxy, &headers);
+        assert_eq!(client.forwarded, Some(ip("203.0.113.5")));
+
+        // the header of an untrusted client is ignored
+        let other = PeerAddr::Tcp("192.0.2.1:50000".parse().unwrap());
+        assert_eq!(access.client_addr(other, &headers).forwarded, None);
+
+        // only a local reverse proxy can connect through a Unix domain socket
+        let client = access.client_addr(PeerAddr::Unix, &headers);
+        assert_eq!(client.forwarded, Some(ip("203.0.113.5")));
+    }
+}
@@ -0,0 +1,599 @@
+use std::sync::Arc;
+
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json};
+use jonitor_client::SensorInfo;
+
+use crate::auth::{self, Identity, Role};
+use crate::bundle::{self, Bundle, MAX_BUNDLE_SIZE};
+use crate::config::{Settings, SettingsError};
+use crate::dashboard::{self, Validation};
+use crate::generate;
+use crate::log;
+use crate::selector::{self, ResolvedDashboard};
+use crate::store::{self, Dashboard, DashboardStore, StoreError, MAX_DASHBOARD_SIZE};
+use crate::template;
+use crate::AppState;
+
+/// `GET /configs/auto` returns a dashboard generated from the current sensors. The name is
+/// reserved, so a dashboard with this name cannot be saved.
+const GENERATED_DASHBOARD_NAME: &str = "auto";
+
+/// Returns the routes of the dashboard API and the settings API. Changing a dashboard requires
+/// the editor role, importing a bundle and the server settings require the admin role.
+pub(crate) fn router() -> axum::Router<AppState> {
+    // GET /configs/:name is not handled by ServeDir as the file content needs to be validated
+    let read_routes = axum::Router::new()
+        .route("/configs/:name", get(configs_route))
+        .route("/api/dashboards", get(list_dashboards))
+        .route(
+            "/api/dashboards/export",
+            get(export_dashboards).fallback(reserved_name),
+        )
+        .route(
+            "/api/dashboards/generate",
+            get(generate_dashboard).fallback(reserved_name),
+        )
+        .route(
+            "/api/dashboards/instantiate",
+            post(instantiate_template)
+                .layer(DefaultBodyLimit::max(MAX_DASHBOARD_SIZE))
+                .fallback(reserved_name),
+        )
+        .route(
+            "/api/dashboards/validate",
+            post(validate_dashboard)
+                .layer(DefaultBodyLimit::max(MAX_DASHBOARD_SIZE))
+                .fallback(reserved_name),
+        )
+        .route("/api/dashboards/:name", get(get_dashboard))
+        .route("/api/dashboards/:name/revisions", get(list_revisions))
+        .route("/api/dashboards/:name/revisions/:id", get(get_revision))
+        .route("/api/dashboards/:name/diff", get(diff_revisions))
+        .route("/api/dashboards/:name/export", get(export_dashboard))
+        .route("/api/dashboards/:name/resolve", get(resolve_selectors));
+
+    let write_routes = axum::Router::new()
+        .route(
+            "/api/dashboards/:name",
+            axum::routing::put(put_dashboard).delete(delete_dashboard),
+        )
+        .route("/api/dashboards/:name/rename", post(rename_dashboard))
+        .route(
+            "/api/dashboards/:name/revisions/:id/restore",
+            post(restore_revision),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            Role::Editor,
+            auth::require_role,
+        ))
+        .layer(DefaultBodyLimit::max(MAX_DASHBOARD_SIZE));
+
+    // a bundle replaces any number of dashboards at once
+    let admin_routes = axum::Router::new()
+        .route(
+            "/api/dashboards/import",
+            post(import_bundle)
+                .layer(DefaultBodyLimit::max(MAX_BUNDLE_SIZE))
+                .fallback(reserved_name),
+        )
+        .route("/api/settings", get(get_settings).put(put_settings))
+        .route_layer(axum::middleware::from_fn_with_state(
+            Role::Admin,
+            auth::require_role,
+        ));
+
+    read_routes.merge(write_routes).merge(admin_routes)
+}
+
+/// Runs a blocking store operation
+async fn run<T, F>(store: Arc<DashboardStore>, f: F) -> Result<T, StoreError>
+where
+    T: Send + 'static,
+    F: FnOnce(&DashboardStore) -> Result<T, StoreError> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(move || f(&store)).await {
+        Ok(x) => x,
+        Err(e) => Err(StoreError::Io(format!(
+            "Failed to complete the dashboard task: {e}"
+        ))),
+    }
+}
+
+/// Handles the other methods of the routes next to `/api/dashboards/:name`, e.g.
+/// `PUT /api/dashboards/import`, as a dashboard with a reserved name
+async fn reserved_name() -> Response {
+    StoreError::ReservedName.into_response()
+}
+
+/// Returns the name saved as the author of revisions, `None` when authentication is disabled
+fn author(identity: Option<Extension<Identity>>) -> Option<String> {
+    identity.map(|Extension(x)| x.name)
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|x| x.to_str().ok())
+}
+
+async fn list_dashboards(State(store): State<Arc<DashboardStore>>) -> Response {
+    match run(store, |store| store.list()).await {
+        Ok(x) => Json(x).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Returns the current sensors, `None` until HWiNFO has been read
+async fn sensor_info(state: &AppState) -> Option<SensorInfo> {
+    serde_json::from_str(&state.latest_sensors.read().await).ok()
+}
+
+/// Validates a dashboard without saving it
+async fn validate_dashboard(State(state): State<AppState>, content: String) -> Response {
+    let sensor_info = sensor_info(&state).await;
+
+    let validation = dashboard::validate(&content, sensor_info.as_ref());
+
+    Json(validation).into_response()
+}
+
+async fn read_dashboard(store: Arc<DashboardStore>, name: &str) -> Result<Dashboard, StoreError> {
+    let name = name.to_owned();
+
+    run(store, move |store| store.read(&name)).await
+}
+
+/// Returns the response of an error reading a dashboard. Invalid files are logged.
+fn read_error(name: &str, error: StoreError) -> Response {
+    match error {
+        StoreError::TooLarge => {
+            log(format!(
+                "The size of the charts config file `{name}.json` exceeds 5 megabytes"
+            ));
+
+            (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+        }
+        StoreError::Invalid(e) => {
+            log(format!(
+                "Failed to parse the charts config file `{name}.json`: {e}"
+            ));
+
+            // the file on disk is invalid, the errors help to fix it
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(Validation::from(e))).into_response()
+        }
+        e => e.into_response(),
+    }
+}
+
+/// Returns the content of a dashboard, or 304 Not Modified if the client has the same content
+fn dashboard_response(dashboard: Dashboard, headers: &HeaderMap) -> Response {
+    let is_unchanged = header_str(headers, header::IF_NONE_MATCH)
+        .is_some_and(|x| x.split(',').any(|x| x.trim() == dashboard.etag));
+
+    if is_unchanged {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, dashboard.etag)]).into_response();
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, String::from("application/json")),
+            (header::ETAG, dashboard.etag),
+        ],
+        dashboard.content,
+    )
+        .into_response()
+}
+
+/// Returns a dashboard generated from the current sensors
+async fn generate_dashboard(State(state): State<AppState>) -> Response {
+    match sensor_info(&state).await {
+        Some(x) => (
+            [(header::CONTENT_TYPE, "application/json")],
+            generate::generate(&x),
+        )
+            .into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The sensors have not been read from HWiNFO yet",
+        )
+            .into_response(),
+    }
+}
+
+/// Returns a dashboard as it is saved, for editing
+async fn get_dashboard(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let result = read_dashboard(store, &name).await;
+
+    match result {
+        Ok(x) => dashboard_response(x, &headers),
+        Err(e) => read_error(&name, e),
+    }
+}
+
+/// Returns a dashboard for the web application, with the selectors of datasets replaced by the
+/// ids of the readings they match
+async fn configs_route(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    // `auto` is a reserved name, so the generated dashboard cannot be replaced by a saved one
+    if name == GENERATED_DASHBOARD_NAME {
+        return match sensor_info(&state).await {
+            Some(x) => {
+                let content = generate::generate(&x);
+
+                dashboard_response(
+                    Dashboard {
+                        etag: store::etag(&content),
+                        content,
+                    },
+                    &headers,
+                )
+            }
+            None => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The sensors have not been read from HWiNFO yet",
+            )
+                .into_response(),
+        };
+    }
+
+    let result = read_dashboard(state.dashboards.clone(), &name).await;
+
+    let sensor_info = sensor_info(&state).await;
+
+    let dashboard = match result {
+        Ok(x) => x,
+        Err(e) => return read_error(&name, e),
+    };
+
+    let content = concrete_dashboard(&dashboard.content, sensor_info.as_ref()).content;
+
+    // the ETag changes with the sensors matched by the variables and selectors
+    let dashboard = if content == dashboard.content {
+        dashboard
+    } else {
+        Dashboard {
+            etag: store::etag(&content),
+            content,
+        }
+    };
+
+    dashboard_response(dashboard, &headers)
+}
+
+/// Returns a valid dashboard with the template instantiated and the selectors resolved, with the
+/// warnings of both
+fn concrete_dashboard(content: &str, sensor_info: Option<&SensorInfo>) -> ResolvedDashboard {
+    let instance = template::instantiate(content, sensor_info);
+
+    let mut resolved =
+        selector::resolve_dashboard(&instance.content, &instance.group_paths, sensor_info);
+    resolved.warnings.splice(0..0, instance.warnings);
+
+    resolved
+}
+
+/// Instantiates a template for the current sensors without saving it, and returns it with the
+/// variables and charts which match nothing
+async fn instantiate_template(State(state): State<AppState>, content: String) -> Response {
+    if let Err(e) = dashboard::parse(&content) {
+        return StoreError::Invalid(e).into_response();
+    }
+
+    let sensor_info = match sensor_info(&state).await {
+        Some(x) => x,
+        None => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The sensors have not been read from HWiNFO yet",
+            )
+                .into_response()
+        }
+    };
+
+    let resolved = concrete_dashboard(&content, Some(&sensor_info));
+
+    let dashboard = match serde_json::from_str::<serde_json::Value>(&resolved.content) {
+        Ok(x) => x,
+        Err(_) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+        }
+    };
+
+    Json(serde_json::json!({
+        "dashboard": dashboard,
+        "warnings": resolved.warnings,
+    }))
+    .into_response()
+}
+
+/// Returns the readings matched by the selector of each dataset, and the variables, groups and
+/// charts which match nothing
+async fn resolve_selectors(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let result = read_dashboard(state.dashboards.clone(), &name).await;
+
+    let dashboard = match result {
+        Ok(x) => x,
+        Err(e) => return read_error(&name, e),
+    };
+
+    let sensor_info = sensor_info(&state).await;
+
+    Json(concrete_dashboard(&dashboard.content, sensor_info.as_ref())).into_response()
+}
+
+async fn put_dashboard(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+    identity: Option<Extension<Identity>>,
+    headers: HeaderMap,
+    content: String,
+) -> Response {
+    let if_match = header_str(&headers, header::IF_MATCH).map(str::to_owned);
+    // `If-None-Match: *` only creates a new dashboard
+    let create_only = header_str(&headers, header::IF_NONE_MATCH) == Some("*");
+    let author = author(identity);
+
+    let result = run(store, move |store| {
+        store.write(
+            &name,
+            &content,
+            if_match.as_deref(),
+            create_only,
+            author.as_deref(),
+        )
+    })
+    .await;
+
+    saved_response(result)
+}
+
+fn saved_response(result: Result<(String, bool), StoreError>) -> Response {
+    match result {
+        Ok((etag, true)) => (StatusCode::CREATED, [(header::ETAG, etag)]).into_response(),
+        Ok((etag, false)) => (StatusCode::NO_CONTENT, [(header::ETAG, etag)]).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn delete_dashboard(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let if_match = header_str(&headers, header::IF_MATCH).map(str::to_owned);
+
+    match run(store, move |store| store.delete(&name, if_match.as_deref())).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RenameRequest {
+    // the new name, without the `.json` extension
+    name: String,
+}
+
+async fn rename_dashboard(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<RenameRequest>,
+) -> Response {
+    let if_match = header_str(&headers, header::IF_MATCH).map(str::to_owned);
+
+    let result = run(store, move |store| {
+        store.rename(&name, &request.name, if_match.as_deref())
+    })
+    .await;
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn list_revisions(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+) -> Response {
+    match run(store, move |store| store.revisions(&name)).await {
+        Ok(x) => Json(x).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_revision(
+    State(store): State<Arc<DashboardStore>>,
+    Path((name, id)): Path<(String, u64)>,
+) -> Response {
+    match run(store, move |store| store.revision(&name, id)).await {
+        Ok(x) => (
+            [
+                (header::CONTENT_TYPE, String::from("application/json")),
+                (header::ETAG, x.etag),
+            ],
+            x.content,
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct DiffQuery {
+    from: u64,
+    // the current content is compared if not set
+    to: Option<u64>,
+}
+
+async fn diff_revisions(
+    State(store): State<Arc<DashboardStore>>,
+    Path(name): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Response {
+    match run(store, move |store| store.diff(&name, query.from, query.to)).await {
+        Ok(x) => x.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn restore_revision(
+    State(store): State<Arc<DashboardStore>>,
+    Path((name, id)): Path<(String, u64)>,
+    identity: Option<Extension<Identity>>,
+    headers: HeaderMap,
+) -> Response {
+    let if_match = header_str(&headers, header::IF_MATCH).map(str::to_owned);
+    let author = author(identity);
+
+    let result = run(store, move |store| {
+        store.restore(&name, id, if_match.as_deref(), author.as_deref())
+    })
+    .await;
+
+    saved_response(result)
+}
+
+/// Exports every valid dashboard as a bundle
+async fn export_dashboards(State(state): State<AppState>) -> Response {
+    let result = run(state.dashboards.clone(), |store| {
+        let mut dashboards = Vec::new();
+
+        for info in store.list()? {
+            match store.read(&info.name) {
+                Ok(x) => dashboards.push((info.name, x.content)),
+                Err(StoreError::Io(e)) => return Err(StoreError::Io(e)),
+                // invalid files cannot be imported
+                Err(_) => log(format!(
+                    "The charts config file `{}.json` is invalid and not exported",
+                    info.name
+                )),
+            }
+        }
+
+        Ok(dashboards)
+    })
+    .await;
+
+    match result {
+        Ok(x) => bundle_response(&state, x, "dashboards").await,
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Exports a dashboard as a bundle
+async fn export_dashboard(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    match read_dashboard(state.dashboards.clone(), &name).await {
+        Ok(x) => bundle_response(&state, vec![(name.clone(), x.content)], &name).await,
+        Err(e) => read_error(&name, e),
+    }
+}
+
+/// Returns a bundle as a file download named `<file_stem>.bundle.json`
+async fn bundle_response(
+    state: &AppState,
+    dashboards: Vec<(String, String)>,
+    file_stem: &str,
+) -> Response {
+    let sensor_info = sensor_info(state).await;
+
+    let bundle = bundle::export(dashboards, sensor_info.as_ref());
+
+    (
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{file_stem}.bundle.json\""),
+        )],
+        Json(bundle),
+    )
+        .into_response()
+}
+
+#[derive(serde::Deserialize)]
+struct ImportQuery {
+    // existing dashboards are replaced if set, otherwise they are reported as failed
+    #[serde(default)]
+    overwrite: bool,
+}
+
+/// Saves the dashboards of a bundle with the datasets remapped to the current sensors
+async fn import_bundle(
+    State(state): State<AppState>,
+    Query(query): Query<ImportQuery>,
+    identity: Option<Extension<Identity>>,
+    Json(bundle): Json<Bundle>,
+) -> Response {
+    if let Err(e) = bundle.check_version() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, e).into_response();
+    }
+
+    let sensor_info = sensor_info(&state).await;
+    let author = author(identity);
+
+    let result = run(state.dashboards.clone(), move |store| {
+        Ok(bundle::import(
+            store,
+            bundle,
+            sensor_info.as_ref(),
+            query.overwrite,
+            author.as_deref(),
+        ))
+    })
+    .await;
+
+    match result {
+        Ok(x) => Json(x).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_settings(State(state): State<AppState>) -> Response {
+    Json(state.settings.get()).into_response()
+}
+
+/// Saves the server settings to the configuration file
+async fn put_settings(
+    State(state): State<AppState>,
+    identity: Option<Extension<Identity>>,
+    Json(settings): Json<Settings>,
+) -> Response {
+    let settings_file = state.settings.clone();
+
+    let result =
+        tokio::task::spawn_blocking(move || settings_file.save(&settings).map(|_| settings)).await;
+
+    match result {
+        Ok(Ok(settings)) => {
+            if let Some(Extension(identity)) = identity {
+                log(format!(
+                    "The server settings are changed by {}",
+                    identity.name
+                ));
+            }
+
+            Json(settings).into_response()
+        }
+        Ok(Err(SettingsError::Overridden(option))) => (
+            StatusCode::CONFLICT,
+            format!("The setting is given by {option}, which overrides the configuration file"),
+        )
+            .into_response(),
+        Ok(Err(SettingsError::Invalid(e))) => (StatusCode::BAD_REQUEST, e).into_response(),
+        Ok(Err(SettingsError::Io(e))) => {
+            log(e);
+
+            (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response(),
+    }
+}
@@ -0,0 +1,555 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Form};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::AuthConfig;
+use crate::listener::{PeerAddr, TlsConnection};
+use crate::log;
+
+const SESSION_COOKIE: &str = "jonitor_session";
+
+/// What a user or an API token is allowed to do. Each role includes the permissions of the
+/// roles before it.
+#[derive(
+    Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Role {
+    // read sensors, data and dashboards
+    #[default]
+    Viewer,
+    // create and modify dashboards
+    Editor,
+    // change server settings
+    Admin,
+}
+
+impl Role {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+/// Who made a request, available to handlers as an extension when authentication is enabled
+#[derive(Clone)]
+pub(crate) struct Identity {
+    // username, or the name of the API token
+    pub(crate) name: String,
+    pub(crate) role: Role,
+}
+
+struct Session {
+    username: String,
+    expires: Instant,
+}
+
+/// Users, API tokens and browser sessions
+pub(crate) struct Auth {
+    // `None` when authentication is disabled
+    config: RwLock<Option<AuthConfig>>,
+    // keyed by session id
+    sessions: Mutex<HashMap<String, Session>>,
+    // prepended to the redirects and the cookie path, e.g. `/monitor`
+    base_path: String,
+}
+
+impl Auth {
+    pub(crate) fn new(config: Option<AuthConfig>, base_path: &str) -> Auth {
+        Auth {
+            config: RwLock::new(config),
+            sessions: Mutex::new(HashMap::new()),
+            base_path: base_path.to_owned(),
+        }
+    }
+
+    /// Returns the URL path of a route under the base path
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_path)
+    }
+
+    /// Replaces the users and tokens. Sessions of removed users, or users with a changed
+    /// password, are ended.
+    pub(crate) fn update(&self, config: Option<AuthConfig>) {
+        let mut current = self.config.write().unwrap();
+        let mut sessions = self.sessions.lock().unwrap();
+
+        match (current.as_ref(), config.as_ref()) {
+            (Some(old), Some(new)) => sessions.retain(|_, session| {
+                let password_hash = |config: &AuthConfig| {
+                    config
+                        .users
+                        .iter()
+                        .find(|x| x.username == session.username)
+                        .map(|x| x.password_hash.clone())
+                };
+
+                password_hash(new).is_some() && password_hash(new) == password_hash(old)
+            }),
+            _ => sessions.clear(),
+        }
+
+        *current = config;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.read().unwrap().is_some()
+    }
+
+    /// Checks the bearer token, the session cookie, and for WebSocket connections the `token`
+    /// query parameter, in this order
+    fn authenticate(&self, request: &Request) -> Option<Identity> {
+        let config = self.config.read().unwrap();
+        let config = config.as_ref()?;
+
+        let headers = request.headers();
+
+        if let Some(token) = bearer_token(headers) {
+            return find_token(config, token);
+        }
+
+        if let Some(session_id) = cookie(headers, SESSION_COOKIE) {
+            let sessions = self.sessions.lock().unwrap();
+
+            if let Some(session) = sessions.get(session_id) {
+                if session.expires > Instant::now() {
+                    // the role is looked up on every request so changes apply immediately
+                    return config
+                        .users
+                        .iter()
+                        .find(|x| x.username == session.username)
+                        .map(|x| Identity {
+                            name: x.username.clone(),
+                            role: x.role,
+                        });
+                }
+            }
+        }
+
+        if is_websocket_upgrade(headers) {
+            let token = request.uri().query().and_then(|query| {
+                query
+                    .split('&')
+                    .find_map(|x| x.strip_prefix("token="))
+                    .filter(|x| !x.is_empty())
+            });
+
+            if let Some(token) = token {
+                return find_token(config, token);
+            }
+        }
+
+        None
+    }
+
+    fn password_hash(&self, username: &str) -> Option<String> {
+        let config = self.config.read().unwrap();
+
+        config
+            .as_ref()?
+            .users
+            .iter()
+            .find(|x| x.username == username)
+            .map(|x| x.password_hash.clone())
+    }
+
+    /// Creates a session and returns its id and lifetime
+    fn create_session(&self, username: String) -> (String, Duration) {
+        let session_hours = match self.config.read().unwrap().as_ref() {
+            Some(x) => x.session_hours,
+            None => 0,
+        };
+        let lifetime = Duration::from_secs(u64::from(session_hours) * 3600);
+
+        let id = random_hex(32);
+        let now = Instant::now();
+
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| session.expires > now);
+        sessions.insert(
+            id.clone(),
+            Session {
+                username,
+                expires: now + lifetime,
+            },
+        );
+
+        (id, lifetime)
+    }
+
+    fn end_session(&self, id: &str) {
+        self.sessions.lock().unwrap().remove(id);
+    }
+}
+
+fn find_token(config: &AuthConfig, token: &str) -> Option<Identity> {
+    let hash = hash_token(token);
+
+    config
+        .tokens
+        .iter()
+        .find(|x| x.hash.eq_ignore_ascii_case(&hash))
+        .map(|x| Identity {
+            name: x.name.clone(),
+            role: x.role,
+        })
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|x| x.trim())
+}
+
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|x| x.to_str().ok())
+        .flat_map(|x| x.split(';'))
+        .find_map(|x| {
+            let (key, value) = x.trim().split_once('=')?;
+
+            if key == name {
+                Some(value)
+            } else {
+                None
+            }
+        })
+}
+
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::UPGRADE)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.eq_ignore_ascii_case("websocket"))
+}
+
+/// Rejects requests which are not authenticated when authentication is enabled. Browsers are
+/// redirected to the login page, other clients receive 401 Unauthorized. The `Auth` of the
+/// listener is inserted as an extension by `listener::run()`.
+pub(crate) async fn middleware(
+    Extension(auth): Extension<Arc<Auth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !auth.is_enabled() {
+        return next.run(request).await;
+    }
+
+    match auth.authenticate(&request) {
+        Some(identity) => {
+            request.extensions_mut().insert(identity);
+
+            next.run(request).await
+        }
+        None => {
+            let accepts_html = request.method() == Method::GET
+                && request
+                    .headers()
+                    .get(header::ACCEPT)
+                    .and_then(|x| x.to_str().ok())
+                    .is_some_and(|x| x.contains("text/html"));
+
+            if accepts_html {
+                Redirect::to(&auth.url("/login")).into_response()
+            } else {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Unauthorized",
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// Rejects requests with 403 Forbidden unless the identity has at least the role given as the
+/// state, e.g. `route_layer(from_fn_with_state(Role::Editor, require_role))`. Every request is
+/// allowed when authentication is disabled.
+pub(crate) async fn require_role(
+    State(role): State<Role>,
+    identity: Option<Extension<Identity>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match identity {
+        Some(Extension(identity)) if identity.role < role => (
+            StatusCode::FORBIDDEN,
+            format!(
+                "Forbidden: the {} role is required, {} has the {} role",
+                role.as_str(),
+                identity.name,
+                identity.role.as_str()
+            ),
+        )
+            .into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+/// Returns the name and role of the current user or API token, so the web application can hide
+/// what it cannot do
+pub(crate) async fn session_route(identity: Option<Extension<Identity>>) -> Response {
+    let body = match identity {
+        Some(Extension(identity)) => serde_json::json!({
+            "enabled": true,
+            "name": identity.name,
+            "role": identity.role,
+        }),
+        None => serde_json::json!({
+            "enabled": false,
+            "role": Role::Admin,
+        }),
+    };
+
+    axum::Json(body).into_response()
+}
+
+/// Returns the router of the login page, which is not protected by `middleware()`
+pub(crate) fn router() -> axum::Router {
+    axum::Router::new()
+        .route("/login", get(login_page_route).post(login_route))
+        .route("/logout", post(logout_route))
+}
+
+#[derive(Deserialize)]
+struct LoginForm {
+    username: String,
+    password: String,
+}
+
+async fn login_page_route(Extension(auth): Extension<Arc<Auth>>) -> Response {
+    if !auth.is_enabled() {
+        return Redirect::to(&auth.url("/")).into_response();
+    }
+
+    Html(login_page(&auth, None)).into_response()
+}
+
+async fn login_route(
+    Extension(auth): Extension<Arc<Auth>>,
+    ConnectInfo(addr): ConnectInfo<PeerAddr>,
+    tls: Option<Extension<TlsConnection>>,
+    Form(form): Form<LoginForm>,
+) -> Response {
+    if !auth.is_enabled() {
+        return Redirect::to(&auth.url("/")).into_response();
+    }
+
+    let password_hash = auth.password_hash(&form.username);
+    let password = form.password;
+
+    // hashing takes a while, run it outside of the runtime thread
+    let is_valid =
+        tokio::task::spawn_blocking(move || verify_password(&password, password_hash.as_deref()))
+            .await
+            .unwrap_or(false);
+
+    if !is_valid {
+        log(format!(
+            "Failed login attempt for `{}` from {:?}",
+            form.username, addr
+        ));
+
+        return (
+            StatusCode::UNAUTHORIZED,
+            Html(login_page(&auth, Some("Invalid username or password"))),
+        )
+            .into_response();
+    }
+
+    log(format!("{} logged in from {:?}", form.username, addr));
+
+    let (session_id, lifetime) = auth.create_session(form.username);
+
+    // the cookie is only sent over HTTPS if it was set over HTTPS
+    let secure = if tls.is_some() { "; Secure" } else { "" };
+
+    (
+        StatusCode::SEE_OTHER,
+        [
+            (
+                header::SET_COOKIE,
+                format!(
+                    "{SESSION_COOKIE}={session_id}; Path={}; HttpOnly; SameSite=Strict; Max-Age={}{secure}",
+                    auth.url("/"),
+                    lifetime.as_secs()
+                ),
+            ),
+            (header::LOCATION, auth.url("/")),
+        ],
+    )
+        .into_response()
+}
+
+async fn logout_route(Extension(auth): Extension<Arc<Auth>>, headers: HeaderMap) -> Response {
+    if let Some(session_id) = cookie(&headers, SESSION_COOKIE) {
+        auth.end_session(session_id);
+    }
+
+    (
+        StatusCode::SEE_OTHER,
+        [
+            (
+                header::SET_COOKIE,
+                format!(
+                    "{SESSION_COOKIE}=; Path={}; HttpOnly; SameSite=Strict; Max-Age=0",
+                    auth.url("/")
+                ),
+            ),
+            (header::LOCATION, auth.url("/login")),
+        ],
+    )
+        .into_response()
+}
+
+fn login_page(auth: &Auth, error: Option<&str>) -> String {
+    let action = auth.url("/login");
+
+    let error = match error {
+        Some(x) => format!("<p class=\"error\">{x}</p>"),
+        None => String::with_capacity(0),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Jonitor</title>
+<style>
+:root {{ color-scheme: light dark; font-family: sans-serif; }}
+body {{ display: flex; justify-content: center; margin-top: 20vh; }}
+form {{ display: flex; flex-direction: column; gap: 0.75rem; width: 16rem; }}
+.error {{ color: #d32f2f; margin: 0; }}
+</style>
+</head>
+<body>
+<form method="post" action="{action}">
+<h1>Jonitor</h1>
+{error}
+<input name="username" placeholder="Username" autocomplete="username" required autofocus>
+<input name="password" type="password" placeholder="Password" autocomplete="current-password" required>
+<button type="submit">Log in</button>
+</form>
+</body>
+</html>
+"#
+    )
+}
+
+/// Verifies a password against a PHC string created by `jonitor hash-password`. A password is
+/// still hashed without `password_hash`, so unknown usernames take as long as known ones.
+fn verify_password(password: &str, password_hash: Option<&str>) -> bool {
+    let argon2 = Argon2::default();
+
+    match password_hash.and_then(|x| PasswordHash::new(x).ok()) {
+        Some(hash) => argon2.verify_password(password.as_bytes(), &hash).is_ok(),
+        None => {
+            let salt = SaltString::generate(&mut OsRng);
+            let _ = argon2.hash_password(password.as_bytes(), &salt);
+
+            false
+        }
+    }
+}
+
+pub(crate) fn is_password_hash(value: &str) -> bool {
+    PasswordHash::new(value).is_ok()
+}
+
+/// Returns the hex encoded SHA-256 hash of an API token, which is stored in the configuration
+/// file instead of the token
+pub(crate) fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+pub(crate) fn is_token_hash(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|x| x.is_ascii_hexdigit())
+}
+
+fn random_hex(length: usize) -> String {
+    let mut bytes = vec![0; length];
+    OsRng.fill_bytes(&mut bytes);
+
+    to_hex(&bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+/// Reads a password from the standard input and prints its hash, for `password_hash` in the
+/// configuration file. Returns the exit code.
+pub(crate) fn hash_password() -> i32 {
+    eprintln!("Enter the password:");
+
+    let mut password = String::new();
+    if let Err(e) = std::io::stdin().lock().read_line(&mut password) {
+        eprintln!("Failed to read the password: {e}");
+
+        return 1;
+    }
+
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        eprintln!("The password cannot be empty");
+
+        return 1;
+    }
+
+    let salt = SaltString::generate(&mut OsRng);
+
+    match Argon2::default().hash_password(password.as_bytes(), &salt) {
+        Ok(x) => {
+            println!("{x}");
+
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to hash the password: {e}");
+
+            1
+        }
+    }
+}
+
+/// Prints a new API token and the entry to be added to `tokens` in the configuration file.
+/// Returns the exit code.
+pub(crate) fn generate_token(name: &str, role: Role) -> i32 {
+    let token = random_hex(32);
+
+    let entry = serde_json::json!({
+        "name": name,
+        "hash": hash_token(&token),
+        "role": role,
+    });
+
+    println!("Token: {token}");
+    println!("Add the following to `tokens` in the configuration file:");
+    println!("{entry}");
+
+    0
+}
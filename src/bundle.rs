@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+
+use jonitor_client::SensorInfo;
+use serde_json::{Map, Value};
+
+use crate::log;
+use crate::store::{DashboardStore, StoreError};
+
+/// Version of the bundle format, bundles of other versions are rejected
+pub(crate) const BUNDLE_VERSION: u32 = 1;
+
+/// Bundles larger than this are rejected, hardcoded to 50 megabytes
+pub(crate) const MAX_BUNDLE_SIZE: usize = 50000000;
+
+/// Dashboards exported from one computer, with the names of the readings shown by each dataset so
+/// that the ids can be replaced by the ids of the same readings on another computer
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Bundle {
+    pub(crate) version: u32,
+    pub(crate) dashboards: Vec<BundleDashboard>,
+}
+
+impl Bundle {
+    /// Returns an error if the bundle has another format than this version of Jonitor
+    pub(crate) fn check_version(&self) -> Result<(), String> {
+        if self.version != BUNDLE_VERSION {
+            return Err(format!("Unsupported bundle version {}", self.version));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct BundleDashboard {
+    pub(crate) name: String,
+    // the dashboard file as JSON instead of text
+    pub(crate) content: Value,
+    // every dataset with ids
+    #[serde(default)]
+    pub(crate) datasets: Vec<DatasetReference>,
+}
+
+/// The reading shown by a dataset when it was exported
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DatasetReference {
+    // JSON path of the dataset
+    pub(crate) path: String,
+    pub(crate) sensor_id: u32,
+    pub(crate) sensor_instance: u32,
+    pub(crate) reading_id: u32,
+    // `None` if the reading was not found, e.g. HWiNFO had not been read yet
+    #[serde(default)]
+    pub(crate) sensor_name: Option<String>,
+    #[serde(default)]
+    pub(crate) reading_name: Option<String>,
+}
+
+/// Result of importing a bundle, every dashboard is imported separately
+#[derive(serde::Serialize)]
+pub(crate) struct ImportReport {
+    pub(crate) imported: Vec<ImportedDashboard>,
+    pub(crate) failed: Vec<FailedDashboard>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportedDashboard {
+    pub(crate) name: String,
+    pub(crate) etag: String,
+    // `false` if an existing dashboard was replaced
+    pub(crate) created: bool,
+    // datasets whose ids are replaced by the ids of the current sensors
+    pub(crate) remapped: usize,
+    // datasets which keep the ids from the bundle, because the reading is not found
+    pub(crate) unmatched: Vec<UnmatchedDataset>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UnmatchedDataset {
+    pub(crate) path: String,
+    pub(crate) sensor_name: Option<String>,
+    pub(crate) reading_name: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub(crate) struct FailedDashboard {
+    pub(crate) name: String,
+    pub(crate) error: String,
+}
+
+/// Returns a bundle of dashboards, given as names and contents. The names of the readings are
+/// taken from the current sensors.
+pub(crate) fn export(
+    dashboards: Vec<(String, String)>,
+    sensor_info: Option<&SensorInfo>,
+) -> Bundle {
+    let dashboards = dashboards
+        .into_iter()
+        .filter_map(|(name, content)| {
+            let mut content = serde_json::from_str::<Value>(&content).ok()?;
+            let mut datasets = Vec::new();
+
+            for_each_dataset(&mut content, |path, dataset| {
+                let (sensor_id, sensor_instance, reading_id) = match dataset_ids(dataset) {
+                    Some(x) => x,
+                    None => return,
+                };
+
+                let sensor = sensor_info.and_then(|x| {
+                    x.sensors
+                        .iter()
+                        .find(|x| x.id == sensor_id && x.instance == sensor_instance)
+                });
+
+                let reading = sensor.and_then(|x| x.readings.iter().find(|x| x.id == reading_id));
+
+                datasets.push(DatasetReference {
+                    path,
+                    sensor_id,
+                    sensor_instance,
+                    reading_id,
+                    sensor_name: sensor.map(|x| x.name.clone()),
+                    reading_name: reading.map(|x| x.name.clone()),
+                });
+            });
+
+            Some(BundleDashboard {
+                name,
+                content,
+                datasets,
+            })
+        })
+        .collect();
+
+    Bundle {
+        version: BUNDLE_VERSION,
+        dashboards,
+    }
+}
+
+/// Saves every dashboard of a bundle with the ids of the datasets replaced by the readings of the
+/// current sensors with the same names. Existing dashboards are only replaced if `overwrite` is
+/// set.
+pub(crate) fn import(
+    store: &DashboardStore,
+    bundle: Bundle,
+    sensor_info: Option<&SensorInfo>,
+    overwrite: bool,
+    author: Option<&str>,
+) -> ImportReport {
+    let mut report = ImportReport {
+        imported: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for dashboard in bundle.dashboards {
+        let BundleDashboard {
+            name,
+            mut content,
+            datasets,
+        } = dashboard;
+
+        let (remapped, unmatched) = remap(&mut content, datasets, sensor_info);
+
+        let content = match serde_json::to_string_pretty(&content) {
+            Ok(x) => x,
+            Err(e) => {
+                report.failed.push(FailedDashboard {
+                    name,
+                    error: e.to_string(),
+                });
+
+                continue;
+            }
+        };
+
+        // `*` replaces the dashboard whatever its current ETag is
+        let if_match = if overwrite { Some("*") } else { None };
+
+        match store.write(&name, &content, if_match, !overwrite, author) {
+            Ok((etag, created)) => report.imported.push(ImportedDashboard {
+                name,
+                etag,
+                created,
+                remapped,
+                unmatched,
+            }),
+            Err(e) => {
+                let error = match e {
+                    StoreError::NotFound | StoreError::InvalidName => {
+                        String::from("The name is not a valid file name")
+                    }
+                    StoreError::ReservedName => String::from("The name is reserved"),
+                    StoreError::TooLarge => String::from("The dashboard exceeds 5 megabytes"),
+                    StoreError::Invalid(e) => e.to_string(),
+                    StoreError::AlreadyExists
+                    | StoreError::PreconditionRequired
+                    | StoreError::PreconditionFailed => {
+                        String::from("The dashboard already exists")
+                    }
+                    StoreError::Io(e) => {
+                        log(e);
+
+                        String::from("Failed to save the dashboard")
+                    }
+                };
+
+                report.failed.push(FailedDashboard { name, error });
+            }
+        }
+    }
+
+    report
+}
+
+/// Replaces the ids of every dataset in `datasets` by the reading with the same sensor and
+/// reading names. Returns the number of replaced datasets and the datasets without a match.
+fn remap(
+    content: &mut Value,
+    datasets: Vec<DatasetReference>,
+    sensor_info: Option<&SensorInfo>,
+) -> (usize, Vec<UnmatchedDataset>) {
+    let mut datasets: HashMap<String, DatasetReference> =
+        datasets.into_iter().map(|x| (x.path.clone(), x)).collect();
+
+    let mut remapped = 0;
+    let mut unmatched = Vec::new();
+
+    for_each_dataset(content, |path, dataset| {
+        let reference = match datasets.remove(&path) {
+            Some(x) => x,
+            None => return,
+        };
+
+        // the dataset in the bundle has been edited
+        if dataset_ids(dataset)
+            != Some((
+                reference.sensor_id,
+                reference.sensor_instance,
+                reference.reading_id,
+            ))
+        {
+            return;
+        }
+
+        let ids = match (&reference.sensor_name, &reference.reading_name, sensor_info) {
+            (Some(sensor_name), Some(reading_name), Some(sensor_info)) => {
+                // sensors with the same name, e.g. two identical drives, are told apart by the
+                // instance
+                let mut sensors: Vec<_> = sensor_info
+                    .sensors
+                    .iter()
+                    .filter(|x| x.name == *sensor_name)
+                    .collect();
+                sensors.sort_by_key(|x| x.instance != reference.sensor_instance);
+
+                sensors.into_iter().find_map(|sensor| {
+                    sensor
+                        .readings
+                        .iter()
+                        .find(|x| x.name == *reading_name)
+                        .map(|reading| (sensor.id, sensor.instance, reading.id))
+                })
+            }
+            _ => None,
+        };
+
+        match ids {
+            Some((sensor_id, sensor_instance, reading_id)) => {
+                dataset.insert(String::from("sensorId"), sensor_id.into());
+                dataset.insert(String::from("sensorInstance"), sensor_instance.into());
+                dataset.insert(String::from("readingId"), reading_id.into());
+
+                remapped += 1;
+            }
+            None => unmatched.push(UnmatchedDataset {
+                path,
+                sensor_name: reference.sensor_name,
+                reading_name: reference.reading_name,
+            }),
+        }
+    });
+
+    (remapped, unmatched)
+}
+
+/// Returns the sensor id, sensor instance and reading id of a dataset
+fn dataset_ids(dataset: &Map<String, Value>) -> Option<(u32, u32, u32)> {
+    let id = |key: &str| {
+        dataset
+            .get(key)
+            .and_then(|x| x.as_u64())
+            .and_then(|x| u32::try_from(x).ok())
+    };
+
+    Some((id("sensorId")?, id("sensorInstance")?, id("readingId")?))
+}
+
+/// Calls `f` with the JSON path of every dataset of a dashboard or template
+fn for_each_dataset<F>(content: &mut Value, mut f: F)
+where
+    F: FnMut(String, &mut Map<String, Value>),
+{
+    let (prefix, groups) = match content {
+        Value::Array(x) => ("$", x),
+        Value::Object(x) => match x.get_mut("groups") {
+            Some(Value::Array(x)) => ("$.groups", x),
+            _ => return,
+        },
+        _ => return,
+    };
+
+    for (i, group) in groups.iter_mut().enumerate() {
+        let charts = group
+            .get_mut("chartConfigs")
+            .and_then(|x| x.as_array_mut())
+            .into_iter()
+            .flatten();
+
+        for (j, chart) in charts.enumerate() {
+            let datasets = chart
+                .get_mut("datasets")
+                .and_then(|x| x.as_array_mut())
+                .into_iter()
+                .flatten();
+
+            for (k, dataset) in datasets.enumerate() {
+                if let Value::Object(dataset) = dataset {
+                    f(
+                        format!("{prefix}[{i}].chartConfigs[{j}].datasets[{k}]"),
+                        dataset,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns sensors with one reading each, given as ids, instance and names
+    fn sensor_info(sensors: &[(u32, u32, &str, u32, &str)]) -> SensorInfo {
+        let sensors: Vec<Value> = sensors
+            .iter()
+            .map(|(id, instance, name, reading_id, reading_name)| {
+                serde_json::json!({
+                    "id": id, "instance": instance, "name": name, "offset": 0,
+                    "readings": [{ "id": reading_id, "name": reading_name, "unit": "°C" }]
+                })
+            })
+            .collect();
+
+        serde_json::from_value(serde_json::json!({ "updatedCount": 1, "sensors": sensors }))
+            .unwrap()
+    }
+
+    /// Returns a dashboard with one chart showing the readings given as ids
+    fn dashboard(datasets: &[(u32, u32, u32)]) -> String {
+        let datasets: Vec<Value> = datasets
+            .iter()
+            .map(|(sensor_id, sensor_instance, reading_id)| {
+                serde_json::json!({
+                    "sensorId": sensor_id, "sensorInstance": sensor_instance,
+                    "readingId": reading_id, "label": "Temperature", "unit": "°C", "color": "red"
+                })
+            })
+            .collect();
+
+        serde_json::json!([{
+            "gridBreakpoints": {
+                "smallColumns": 1, "smallWidth": 0,
+                "mediumColumns": 1, "mediumWidth": 768,
+                "largeColumns": 2, "largeWidth": 992,
+                "extraLargeColumns": 2, "extraLargeWidth": 1200
+            },
+            "chartConfigs": [{
+                "title": "Temperatures",
+                "chartType": "line",
+                "dataCount": 60,
+                "maximumValue": 100,
+                "height": 200,
+                "animationDuration": 0,
+                "showLegend": true,
+                "showLabels": true,
+                "autoColors": false,
+                "datasets": datasets
+            }]
+        }])
+        .to_string()
+    }
+
+    fn temp_store(name: &str) -> (DashboardStore, std::path::PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("jonitor-bundle-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        (DashboardStore::new(dir.clone()), dir)
+    }
+
+    fn saved_content(store: &DashboardStore) -> String {
+        let Ok(dashboard) = store.read("main") else {
+            panic!("failed to read the dashboard");
+        };
+
+        dashboard.content
+    }
+
+    fn dataset_ids_of(content: &str) -> Vec<(u32, u32, u32)> {
+        let mut content: Value = serde_json::from_str(content).unwrap();
+        let mut ids = Vec::new();
+
+        for_each_dataset(&mut content, |_, dataset| ids.extend(dataset_ids(dataset)));
+
+        ids
+    }
+
+    #[test]
+    fn datasets_are_remapped_by_name() {
+        let exported_sensors = sensor_info(&[
+            (1, 0, "CPU [#0]: AMD Ryzen 7", 2, "Core Temperatures"),
+            (3, 0, "S.M.A.R.T.: Drive", 4, "Drive Temperature"),
+            (3, 1, "S.M.A.R.T.: Drive", 5, "Drive Temperature"),
+            (6, 0, "GPU [#0]: NVIDIA GeForce", 7, "GPU Temperature"),
+        ]);
+        let current_sensors = sensor_info(&[
+            (10, 0, "CPU [#0]: AMD Ryzen 7", 20, "Core Temperatures"),
+            (30, 0, "S.M.A.R.T.: Drive", 40, "Drive Temperature"),
+            (30, 1, "S.M.A.R.T.: Drive", 50, "Drive Temperature"),
+        ]);
+
+        let content = dashboard(&[(1, 0, 2), (3, 1, 5), (6, 0, 7)]);
+        let bundle = export(
+            vec![(String::from("main"), content)],
+            Some(&exported_sensors),
+        );
+
+        let datasets = &bundle.dashboards[0].datasets;
+        assert_eq!(datasets.len(), 3);
+        assert_eq!(datasets[1].path, "$[0].chartConfigs[0].datasets[1]");
+        assert_eq!(
+            datasets[1].sensor_name.as_deref(),
+            Some("S.M.A.R.T.: Drive")
+        );
+        assert_eq!(
+            datasets[1].reading_name.as_deref(),
+            Some("Drive Temperature")
+        );
+
+        let (store, dir) = temp_store("remap");
+        let report = import(&store, bundle, Some(&current_sensors), false, None);
+
+        assert!(report.failed.is_empty());
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.imported[0].remapped, 2);
+
+        // the GPU is not found, so its dataset keeps the ids from the bundle
+        let unmatched = &report.imported[0].unmatched;
+        assert_eq!(unmatched.len(), 1);
+        assert_eq!(unmatched[0].path, "$[0].chartConfigs[0].datasets[2]");
+        assert_eq!(
+            unmatched[0].sensor_name.as_deref(),
+            Some("GPU [#0]: NVIDIA GeForce")
+        );
+        assert_eq!(
+            unmatched[0].reading_name.as_deref(),
+            Some("GPU Temperature")
+        );
+
+        // the second drive keeps its instance among the drives with the same name
+        let content = saved_content(&store);
+        assert_eq!(
+            dataset_ids_of(&content),
+            [(10, 0, 20), (30, 1, 50), (6, 0, 7)]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn existing_dashboards_are_only_replaced_with_overwrite() {
+        let sensors = sensor_info(&[(1, 0, "CPU [#0]: AMD Ryzen 7", 2, "Core Temperatures")]);
+        let bundle = || {
+            export(
+                vec![(String::from("main"), dashboard(&[(1, 0, 2)]))],
+                Some(&sensors),
+            )
+        };
+
+        let (store, dir) = temp_store("overwrite");
+        assert!(store
+            .write("main", &dashboard(&[(1, 0, 9)]), None, false, None)
+            .is_ok());
+
+        let report = import(&store, bundle(), Some(&sensors), false, None);
+        assert!(report.imported.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].error, "The dashboard already exists");
+
+        assert_eq!(dataset_ids_of(&saved_content(&store)), [(1, 0, 9)]);
+
+        let report = import(&store, bundle(), Some(&sensors), true, None);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.imported.len(), 1);
+        assert!(!report.imported[0].created);
+
+        let content = saved_content(&store);
+        assert_eq!(dataset_ids_of(&content), [(1, 0, 2)]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut bundle = export(Vec::new(), None);
+        assert!(bundle.check_version().is_ok());
+
+        bundle.version = BUNDLE_VERSION + 1;
+        assert_eq!(
+            bundle.check_version(),
+            Err(format!("Unsupported bundle version {}", BUNDLE_VERSION + 1))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use jonitor_client::ReadingType;
+
+use crate::auth::Role;
+use crate::dump::Format;
+
+/// A web application for displaying real time hardware information read from HWiNFO.
+///
+/// Values are taken from command line options first, then `JONITOR_*` environment variables,
+/// then the configuration file.
+#[derive(Parser)]
+#[command(version)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+    // options of `serve`, used when no subcommand is given and ignored otherwise
+    #[command(flatten)]
+    pub(crate) serve: ConfigArgs,
+    /// Close Jonitor immediately when an error occurs, instead of waiting for the Enter key
+    #[arg(long, global = true, env = "JONITOR_CLOSE_ON_ERROR")]
+    pub(crate) close_on_error: bool,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Start the web server (default)
+    Serve(ConfigArgs),
+    /// Print the current readings and exit
+    Dump(DumpArgs),
+    /// Check the configuration file with the overrides applied, and exit
+    ValidateConfig(ConfigArgs),
+    /// Save the readings from HWiNFO to a file, to be replayed later
+    Record(RecordArgs),
+    /// Start the web server with the readings from a recording instead of HWiNFO
+    Replay(ReplayArgs),
+    /// Read a password from the standard input and print the hash for the configuration file
+    HashPassword,
+    /// Print a new API token and the entry for the configuration file
+    GenerateToken(GenerateTokenArgs),
+}
+
+#[derive(Clone, Args)]
+pub(crate) struct ConfigArgs {
+    /// Path to the configuration file [default: config.json in the folder of jonitor.exe]
+    #[arg(long, env = "JONITOR_CONFIG")]
+    pub(crate) config: Option<PathBuf>,
+    /// Addresses to listen on, replacing the listeners in the configuration file, e.g.
+    /// 0.0.0.0:10110, [::1]:10110 or unix:jonitor.sock
+    #[arg(long, env = "JONITOR_LISTEN", value_delimiter = ',')]
+    pub(crate) listen: Vec<String>,
+    /// The IP address of the first listener
+    #[arg(long, env = "JONITOR_IP")]
+    pub(crate) ip: Option<String>,
+    /// The port of the first listener
+    #[arg(long, env = "JONITOR_PORT")]
+    pub(crate) port: Option<u16>,
+    /// The interval in milliseconds at which data is read from HWiNFO
+    #[arg(long, env = "JONITOR_POLLING_INTERVAL")]
+    pub(crate) polling_interval: Option<u32>,
+    /// Path to the bundled web application [default: public in the folder of jonitor.exe]
+    #[arg(long, env = "JONITOR_PUBLIC_DIR")]
+    pub(crate) public_dir: Option<PathBuf>,
+    /// Path to the folder of the dashboards and recordings [default: %LOCALAPPDATA%\Jonitor]
+    #[arg(long, env = "JONITOR_DATA_DIR")]
+    pub(crate) data_dir: Option<PathBuf>,
+    /// URL path Jonitor is served under behind a reverse proxy, e.g. /monitor [default: /]
+    #[arg(long, env = "JONITOR_BASE_PATH")]
+    pub(crate) base_path: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct DumpArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    pub(crate) format: Format,
+    /// Query a running Jonitor instance, e.g. http://127.0.0.1:10110, instead of HWiNFO
+    #[arg(long)]
+    pub(crate) url: Option<String>,
+    /// Only include sensors with a name containing the text (case-insensitive)
+    #[arg(long)]
+    pub(crate) sensor: Option<String>,
+    /// API token of the Jonitor instance given by --url
+    #[arg(long, env = "JONITOR_TOKEN", requires = "url")]
+    pub(crate) token: Option<String>,
+    /// Only include readings with a name containing the text (case-insensitive)
+    #[arg(long)]
+    pub(crate) reading: Option<String>,
+    /// Only include readings of the given types, separated by commas
+    #[arg(long = "type", value_delimiter = ',', value_parser = parse_reading_type)]
+    pub(crate) types: Vec<ReadingType>,
+}
+
+#[derive(Args)]
+pub(crate) struct RecordArgs {
+    /// Path to the recording file to be created
+    pub(crate) output: PathBuf,
+    /// Stop recording after the given number of seconds, otherwise record until Jonitor is
+    /// closed
+    #[arg(long)]
+    pub(crate) duration: Option<u64>,
+    #[command(flatten)]
+    pub(crate) config: ConfigArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct ReplayArgs {
+    /// Path to a file created by `jonitor record`
+    pub(crate) input: PathBuf,
+    /// Start from the beginning when the end of the recording is reached
+    #[arg(long = "loop")]
+    pub(crate) is_looping: bool,
+    /// Playback speed, e.g. 2 replays the recording twice as fast
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    pub(crate) speed: f64,
+    #[command(flatten)]
+    pub(crate) config: ConfigArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct GenerateTokenArgs {
+    /// Name of the token, e.g. the client using it
+    pub(crate) name: String,
+    /// Role of the token: viewer, editor or admin
+    #[arg(long, default_value = "viewer", value_parser = parse_role)]
+    pub(crate) role: Role,
+}
+
+fn parse_reading_type(value: &str) -> Result<ReadingType, String> {
+    ReadingType::from_name(value.trim()).ok_or_else(|| {
+        let names = ReadingType::ALL.map(|x| x.as_str()).join(", ");
+
+        format!("invalid reading type, expected one of: {names}")
+    })
+}
+
+fn parse_role(value: &str) -> Result<Role, String> {
+    serde_json::from_value(serde_json::Value::from(value))
+        .map_err(|_| String::from("expected viewer, editor or admin"))
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(x) if x > 0.0 && x.is_finite() => Ok(x),
+        _ => Err(String::from("expected a positive number")),
+    }
+}
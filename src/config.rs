@@ -0,0 +1,1112 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+use crate::access::{Access, IpNet};
+use crate::auth::Role;
+use crate::cli::ConfigArgs;
+use crate::listener::ListenAddress;
+
+/// Polling interval in milliseconds used when creating the configuration file
+pub(crate) const DEFAULT_POLLING_INTERVAL: u32 = 2000;
+pub(crate) const MINIMUM_POLLING_INTERVAL: u32 = 1000;
+
+/// Version of the configuration file written by this version of Jonitor
+pub(crate) const CONFIG_VERSION: u32 = 3;
+
+/// `MIGRATIONS[i]` migrates a configuration file from version `i + 1` to version `i + 2`
+const MIGRATIONS: [fn(&mut serde_json::Map<String, serde_json::Value>); 2] =
+    [migrate_v1_to_v2, migrate_v2_to_v3];
+
+const DEFAULT_IP: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 10110;
+
+/// How often the configuration file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct Config {
+    // URL of the JSON Schema used by editors, kept when the file is rewritten
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub(crate) schema: Option<String>,
+    pub(crate) version: u32,
+    pub(crate) listeners: Vec<ListenerConfig>,
+    pub(crate) polling_interval: u32,
+    // path to the bundled web application, relative to the folder of jonitor.exe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) public_dir: Option<PathBuf>,
+    // path to the dashboards and recordings, relative to the folder of jonitor.exe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data_dir: Option<PathBuf>,
+    // URL path Jonitor is served under behind a reverse proxy, e.g. `/monitor`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) base_path: Option<String>,
+    // authentication is disabled when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) auth: Option<AuthConfig>,
+    // every client is allowed without limits when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) access: Option<AccessConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            schema: None,
+            version: CONFIG_VERSION,
+            listeners: vec![ListenerConfig {
+                address: socket_address(DEFAULT_IP, DEFAULT_PORT),
+                ..Default::default()
+            }],
+            polling_interval: DEFAULT_POLLING_INTERVAL,
+            public_dir: None,
+            data_dir: None,
+            base_path: None,
+            auth: None,
+            access: None,
+        }
+    }
+}
+
+/// An address the web server listens on
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ListenerConfig {
+    // `ip:port`, `[ipv6]:port` or `unix:<path>`
+    pub(crate) address: String,
+    // also accept IPv4 connections on an IPv6 address
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) dual_stack: bool,
+    // serve HTTPS instead of HTTP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tls: Option<TlsConfig>,
+    // redirect every request to the HTTPS listener on this port instead of serving Jonitor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) redirect_https_port: Option<u16>,
+    // users and API tokens of this listener instead of `Config::auth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) auth: Option<AuthConfig>,
+}
+
+impl std::fmt::Display for ListenerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.address)?;
+
+        if self.dual_stack {
+            write!(f, " (dual stack)")?;
+        }
+
+        if self.tls.is_some() {
+            write!(f, " (TLS)")?;
+        }
+
+        if let Some(port) = self.redirect_https_port {
+            write!(f, " (redirect to HTTPS port {port})")?;
+        }
+
+        if let Some(auth) = &self.auth {
+            write!(
+                f,
+                " (auth: {} users, {} tokens)",
+                auth.users.len(),
+                auth.tokens.len()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Users and API tokens allowed to access Jonitor
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct AuthConfig {
+    #[serde(default)]
+    pub(crate) users: Vec<UserConfig>,
+    #[serde(default)]
+    pub(crate) tokens: Vec<TokenConfig>,
+    // how long a browser stays logged in
+    #[serde(default = "default_session_hours")]
+    pub(crate) session_hours: u32,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct UserConfig {
+    pub(crate) username: String,
+    // created by `jonitor hash-password`
+    pub(crate) password_hash: String,
+    #[serde(default)]
+    pub(crate) role: Role,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TokenConfig {
+    pub(crate) name: String,
+    // hex encoded SHA-256 hash of the token, created by `jonitor generate-token`
+    pub(crate) hash: String,
+    #[serde(default)]
+    pub(crate) role: Role,
+}
+
+pub(crate) fn default_session_hours() -> u32 {
+    168
+}
+
+impl AuthConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.users.is_empty() && self.tokens.is_empty() {
+            return Err(String::from(
+                "Error: auth requires at least 1 user or token. Remove auth to disable authentication",
+            ));
+        }
+
+        for (i, user) in self.users.iter().enumerate() {
+            if user.username.is_empty() {
+                return Err(String::from("Error: username cannot be empty"));
+            }
+
+            if self.users[..i].iter().any(|x| x.username == user.username) {
+                return Err(format!(
+                    "Error: username `{}` is used by more than 1 user",
+                    user.username
+                ));
+            }
+
+            if !crate::auth::is_password_hash(&user.password_hash) {
+                return Err(format!("Error: invalid password_hash of user `{}`. Use `jonitor hash-password` to create it", user.username));
+            }
+        }
+
+        for (i, token) in self.tokens.iter().enumerate() {
+            if self.tokens[..i].iter().any(|x| x.name == token.name) {
+                return Err(format!(
+                    "Error: name `{}` is used by more than 1 token",
+                    token.name
+                ));
+            }
+
+            if !crate::auth::is_token_hash(&token.hash) {
+                return Err(format!(
+                    "Error: invalid hash of token `{}`. Use `jonitor generate-token` to create it",
+                    token.name
+                ));
+            }
+        }
+
+        if self.session_hours == 0 {
+            return Err(String::from("Error: session_hours should be at least 1"));
+        }
+
+        Ok(())
+    }
+}
+
+/// IP addresses allowed to access Jonitor, and limits on the number of connections
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct AccessConfig {
+    // CIDR ranges, e.g. `192.168.1.0/24`. Every address is allowed when empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) allow: Vec<String>,
+    // CIDR ranges rejected even if they are allowed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) deny: Vec<String>,
+    // CIDR ranges of reverse proxies whose `X-Forwarded-For` header is used for logging
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) trusted_proxies: Vec<String>,
+    // open WebSocket connections of every client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_websocket_connections: Option<u32>,
+    // open connections, including WebSocket connections, of each IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_connections_per_ip: Option<u32>,
+}
+
+impl AccessConfig {
+    fn validate(&self) -> Result<(), String> {
+        for value in self
+            .allow
+            .iter()
+            .chain(self.deny.iter())
+            .chain(self.trusted_proxies.iter())
+        {
+            IpNet::parse(value).map_err(|e| format!("Error: {e}"))?;
+        }
+
+        if self.max_websocket_connections == Some(0) {
+            return Err(String::from(
+                "Error: max_websocket_connections should be at least 1",
+            ));
+        }
+
+        if self.max_connections_per_ip == Some(0) {
+            return Err(String::from(
+                "Error: max_connections_per_ip should be at least 1",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Certificate of a HTTPS listener. The files are PEM encoded, and relative paths are relative to
+/// the folder of jonitor.exe.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TlsConfig {
+    pub(crate) cert: PathBuf,
+    pub(crate) key: PathBuf,
+    // create a self-signed certificate if the files do not exist
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) self_signed: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Formats an IP address and a port as a listen address, with brackets around IPv6 addresses
+fn socket_address(ip: &str, port: impl std::fmt::Display) -> String {
+    if ip.contains(':') && !ip.starts_with('[') {
+        format!("[{ip}]:{port}")
+    } else {
+        format!("{ip}:{port}")
+    }
+}
+
+impl Config {
+    /// Replaces the values from the configuration file with the ones given by command line
+    /// options or `JONITOR_*` environment variables
+    pub(crate) fn apply_overrides(&mut self, args: &ConfigArgs) {
+        if !args.listen.is_empty() {
+            self.listeners = args
+                .listen
+                .iter()
+                .map(|x| ListenerConfig {
+                    address: x.clone(),
+                    ..Default::default()
+                })
+                .collect();
+        }
+
+        if args.ip.is_some() || args.port.is_some() {
+            // --ip and --port change the first TCP listener
+            let first_tcp = self.listeners.iter().position(|x| {
+                matches!(ListenAddress::parse(&x.address), Ok(ListenAddress::Tcp(_)))
+            });
+
+            let current =
+                first_tcp.and_then(|i| match ListenAddress::parse(&self.listeners[i].address) {
+                    Ok(ListenAddress::Tcp(addr)) => Some(addr),
+                    _ => None,
+                });
+
+            let ip = match (&args.ip, current) {
+                (Some(ip), _) => ip.clone(),
+                (None, Some(addr)) => addr.ip().to_string(),
+                (None, None) => String::from(DEFAULT_IP),
+            };
+
+            let port = args
+                .port
+                .or(current.map(|x| x.port()))
+                .unwrap_or(DEFAULT_PORT);
+
+            let address = socket_address(&ip, port);
+
+            match first_tcp {
+                Some(i) => self.listeners[i].address = address,
+                None => self.listeners.insert(
+                    0,
+                    ListenerConfig {
+                        address,
+                        ..Default::default()
+                    },
+                ),
+            }
+        }
+
+        if let Some(polling_interval) = args.polling_interval {
+            self.polling_interval = polling_interval;
+        }
+
+        if let Some(public_dir) = &args.public_dir {
+            self.public_dir = Some(public_dir.clone());
+        }
+
+        if let Some(data_dir) = &args.data_dir {
+            self.data_dir = Some(data_dir.clone());
+        }
+
+        if let Some(base_path) = &args.base_path {
+            self.base_path = Some(base_path.clone());
+        }
+    }
+
+    /// Checks the values that cannot be checked when deserializing
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.listeners.is_empty() {
+            return Err(String::from("Error: at least 1 listener is required"));
+        }
+
+        let mut addresses = Vec::with_capacity(self.listeners.len());
+
+        for listener in self.listeners.iter() {
+            let address = ListenAddress::parse(&listener.address)?;
+
+            if listener.dual_stack && !matches!(address, ListenAddress::Tcp(x) if x.is_ipv6()) {
+                return Err(format!("Error: dual_stack is only supported by IPv6 addresses. Current value of address is `{}`", listener.address));
+            }
+
+            if listener.tls.is_some() && listener.redirect_https_port.is_some() {
+                return Err(format!("Error: a listener with tls cannot have redirect_https_port. Current value of address is `{}`", listener.address));
+            }
+
+            if listener.auth.is_some() && listener.redirect_https_port.is_some() {
+                return Err(format!("Error: a listener with redirect_https_port cannot have auth. Current value of address is `{}`", listener.address));
+            }
+
+            if let Some(auth) = &listener.auth {
+                auth.validate()?;
+            }
+
+            if listener.redirect_https_port == Some(0) {
+                return Err(format!(
+                    "Error: redirect_https_port cannot be 0. Current value of address is `{}`",
+                    listener.address
+                ));
+            }
+
+            if addresses.contains(&address) {
+                return Err(format!(
+                    "Error: `{}` is used by more than 1 listener",
+                    listener.address
+                ));
+            }
+
+            addresses.push(address);
+        }
+
+        if let Some(auth) = &self.auth {
+            auth.validate()?;
+        }
+
+        if let Some(access) = &self.access {
+            access.validate()?;
+        }
+
+        if let Some(base_path) = &self.base_path {
+            let is_valid = base_path.starts_with('/')
+                && base_path
+                    .bytes()
+                    .all(|x| x.is_ascii_alphanumeric() || b"-._~/".contains(&x))
+                && base_path
+                    .trim_matches('/')
+                    .split('/')
+                    .all(|x| !matches!(x, "." | ".."))
+                && !base_path.contains("//");
+
+            if !is_valid {
+                return Err(format!("Error: base_path should start with `/` and contain only letters, digits, `-`, `.`, `_`, `~` and `/`. Current value of base_path is `{base_path}`"));
+            }
+        }
+
+        if self.polling_interval < MINIMUM_POLLING_INTERVAL {
+            return Err(format!("Error: the minimum polling interval should be {MINIMUM_POLLING_INTERVAL} milliseconds. Current value of polling_interval is `{}`", self.polling_interval));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path to the bundled web application, `public` in the folder of jonitor.exe
+    /// by default
+    pub(crate) fn public_dir_path(&self, exe_dir: &Path) -> PathBuf {
+        match &self.public_dir {
+            Some(x) => exe_dir.join(x),
+            None => exe_dir.join("public"),
+        }
+    }
+
+    /// Returns the base path without a trailing slash, e.g. `/monitor`, or an empty string if
+    /// Jonitor is served at the root
+    pub(crate) fn base_path(&self) -> &str {
+        self.base_path
+            .as_deref()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+    }
+
+    /// Returns the path to the folder of the dashboards and recordings, which is kept when the
+    /// web application is updated. `%LOCALAPPDATA%\Jonitor` by default, or `data` in the folder
+    /// of jonitor.exe if it is not set.
+    pub(crate) fn data_dir_path(&self, exe_dir: &Path) -> PathBuf {
+        if let Some(x) = &self.data_dir {
+            return exe_dir.join(x);
+        }
+
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(x) if !x.is_empty() => PathBuf::from(x).join("Jonitor"),
+            _ => exe_dir.join("data"),
+        }
+    }
+}
+
+/// Returns the differences between 2 configurations, one line per changed value
+pub(crate) fn diff(old: &Config, new: &Config) -> Vec<String> {
+    fn display_path(path: &Option<PathBuf>) -> String {
+        match path {
+            Some(x) => x.to_string_lossy().into_owned(),
+            None => String::from("(default)"),
+        }
+    }
+
+    fn display_listeners(listeners: &[ListenerConfig]) -> String {
+        listeners
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn display_base_path(base_path: &str) -> String {
+        match base_path {
+            "" => String::from("/"),
+            x => x.to_owned(),
+        }
+    }
+
+    fn display_auth(auth: &Option<AuthConfig>) -> String {
+        match auth {
+            Some(x) => format!("{} users, {} tokens", x.users.len(), x.tokens.len()),
+            None => String::from("disabled"),
+        }
+    }
+
+    let mut lines = Vec::with_capacity(5);
+
+    if old.listeners != new.listeners {
+        lines.push(format!(
+            "listeners: `{}` -> `{}`",
+            display_listeners(&old.listeners),
+            display_listeners(&new.listeners)
+        ));
+    }
+
+    if old.polling_interval != new.polling_interval {
+        lines.push(format!(
+            "polling_interval: `{}` -> `{}`",
+            old.polling_interval, new.polling_interval
+        ));
+    }
+
+    if old.public_dir != new.public_dir {
+        lines.push(format!(
+            "public_dir: `{}` -> `{}`",
+            display_path(&old.public_dir),
+            display_path(&new.public_dir)
+        ));
+    }
+
+    if old.data_dir != new.data_dir {
+        lines.push(format!(
+            "data_dir: `{}` -> `{}`",
+            display_path(&old.data_dir),
+            display_path(&new.data_dir)
+        ));
+    }
+
+    if old.base_path() != new.base_path() {
+        lines.push(format!(
+            "base_path: `{}` -> `{}`",
+            display_base_path(old.base_path()),
+            display_base_path(new.base_path())
+        ));
+    }
+
+    if old.access != new.access {
+        lines.push(String::from(
+            "access: the IP access rules or limits have changed",
+        ));
+    }
+
+    // hashes are not logged
+    if old.auth != new.auth {
+        lines.push(format!(
+            "auth: `{}` -> `{}`",
+            display_auth(&old.auth),
+            display_auth(&new.auth)
+        ));
+    }
+
+    lines
+}
+
+/// Values of the running configuration that can be changed without restarting Jonitor
+pub(crate) struct LiveConfig {
+    // read by the timer thread before each sleep
+    pub(crate) polling_interval: Arc<AtomicU64>,
+    // the web server starts and stops listeners when they change
+    pub(crate) listeners_tx: tokio::sync::watch::Sender<Vec<ListenerConfig>>,
+    pub(crate) auth: Arc<crate::auth::Auth>,
+    pub(crate) access: Arc<Access>,
+}
+
+/// Server settings which admins can read and change through `/api/settings`, with the names of
+/// the configuration file
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Settings {
+    pub(crate) polling_interval: u32,
+}
+
+pub(crate) enum SettingsError {
+    // the value is given by a command line option or an environment variable
+    Overridden(&'static str),
+    Invalid(String),
+    Io(String),
+}
+
+/// The configuration file changed by the settings API
+pub(crate) struct SettingsFile {
+    pub(crate) path: PathBuf,
+    pub(crate) args: ConfigArgs,
+    // the running value, shared with `LiveConfig`
+    pub(crate) polling_interval: Arc<AtomicU64>,
+}
+
+impl SettingsFile {
+    /// Returns the running settings
+    pub(crate) fn get(&self) -> Settings {
+        Settings {
+            polling_interval: self.polling_interval.load(Ordering::Relaxed) as u32,
+        }
+    }
+
+    /// Saves the settings to the configuration file and applies them. The other values of the
+    /// file are kept, and the watcher logs the change when it reloads the file.
+    pub(crate) fn save(&self, settings: &Settings) -> Result<(), SettingsError> {
+        if self.args.polling_interval.is_some() {
+            return Err(SettingsError::Overridden(
+                "--polling-interval or JONITOR_POLLING_INTERVAL",
+            ));
+        }
+
+        let mut config = load(&self.path).map_err(SettingsError::Io)?.config;
+        config.polling_interval = settings.polling_interval;
+
+        let mut running = config.clone();
+        running.apply_overrides(&self.args);
+        running.validate().map_err(SettingsError::Invalid)?;
+
+        write(&self.path, &config).map_err(SettingsError::Io)?;
+
+        self.polling_interval
+            .store(u64::from(settings.polling_interval), Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+/// Spawns a thread which checks the configuration file for changes, and applies valid changes
+/// to `live`. Invalid changes are logged and the old configuration keeps running.
+pub(crate) fn spawn_watcher(
+    path: PathBuf,
+    args: ConfigArgs,
+    mut config: Config,
+    live: LiveConfig,
+) -> std::io::Result<()> {
+    let modified_time = |path: &Path| path.metadata().and_then(|x| x.modified()).ok();
+
+    let mut last_modified: Option<SystemTime> = modified_time(&path);
+
+    thread::Builder::new()
+        .name(String::from("config"))
+        .spawn(move || loop {
+            thread::sleep(WATCH_INTERVAL);
+
+            let modified = modified_time(&path);
+            if modified.is_none() || modified == last_modified {
+                continue;
+            }
+
+            last_modified = modified;
+
+            let mut new_config = match load(&path) {
+                Ok(x) => {
+                    for warning in x.warnings.iter() {
+                        crate::log(format!("Warning: {warning}"));
+                    }
+
+                    x.config
+                }
+                Err(e) => {
+                    crate::log(format!(
+                        "The configuration file has changed but is not applied. {e}"
+                    ));
+
+                    continue;
+                }
+            };
+
+            new_config.apply_overrides(&args);
+
+            let lines = diff(&config, &new_config);
+            if lines.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = new_config.validate() {
+                crate::log("The configuration file has changed but is not applied:");
+                for line in lines.iter() {
+                    crate::log(format!("  {line}"));
+                }
+                crate::log(e);
+
+                continue;
+            }
+
+            crate::log("The configuration file has changed:");
+            for line in lines.iter() {
+                crate::log(format!("  {line}"));
+            }
+
+            if new_config.polling_interval != config.polling_interval {
+                live.polling_interval
+                    .store(u64::from(new_config.polling_interval), Ordering::Relaxed);
+            }
+
+            if new_config.listeners != config.listeners {
+                let _ = live.listeners_tx.send(new_config.listeners.clone());
+            }
+
+            if new_config.auth != config.auth {
+                live.auth.update(new_config.auth.clone());
+            }
+
+            if new_config.access != config.access {
+                live.access.update(new_config.access.as_ref());
+            }
+
+            if new_config.public_dir != config.public_dir {
+                crate::log("Restart Jonitor to serve files from the new public_dir");
+            }
+
+            if new_config.data_dir != config.data_dir {
+                crate::log("Restart Jonitor to use the new data_dir");
+            }
+
+            if new_config.base_path() != config.base_path() {
+                crate::log("Restart Jonitor to serve it under the new base_path");
+            }
+
+            config = new_config;
+        })?;
+
+    Ok(())
+}
+
+/// Returns the path to the configuration file, `config.json` in the folder of jonitor.exe by
+/// default
+pub(crate) fn config_path(exe_dir: &Path, args: &ConfigArgs) -> PathBuf {
+    match &args.config {
+        Some(x) => x.clone(),
+        None => exe_dir.join("config.json"),
+    }
+}
+
+/// Reads the configuration file, or creates it with default values if it does not exist. A
+/// configuration file of an older version is migrated and rewritten, with a backup of the old
+/// file. The overrides are applied and the result is validated.
+pub(crate) fn load_or_create(path: &Path, args: &ConfigArgs) -> Result<Config, String> {
+    let mut config = if path.exists() && path.is_file() {
+        let loaded = load(path)?;
+
+        for warning in loaded.warnings.iter() {
+            crate::log(format!("Warning: {warning}"));
+        }
+
+        if loaded.needs_rewrite {
+            rewrite(path, &loaded)?;
+        }
+
+        loaded.config
+    } else {
+        let config = Config::default();
+
+        write(path, &config)?;
+
+        crate::log("Configuration file created");
+
+        config
+    };
+
+    config.apply_overrides(args);
+    config.validate()?;
+
+    Ok(config)
+}
+
+pub(crate) struct LoadedConfig {
+    pub(crate) config: Config,
+    // version of the configuration file before migration
+    pub(crate) file_version: u32,
+    // the file is of an older version or has missing values
+    pub(crate) needs_rewrite: bool,
+    // unknown keys and missing values
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Reads and parses the configuration file, and migrates it to `CONFIG_VERSION` in memory.
+/// Missing values are replaced with default values.
+pub(crate) fn load(path: &Path) -> Result<LoadedConfig, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read the configuration file: {e}"))?;
+
+    let mut value = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("Failed to parse the configuration file: {e}"))?;
+
+    let object = match value.as_object_mut() {
+        Some(x) => x,
+        None => {
+            return Err(String::from(
+                "Failed to parse the configuration file: expected a JSON object",
+            ))
+        }
+    };
+
+    // the version field was added in version 2
+    let file_version = match object.get("version") {
+        None => 1,
+        Some(x) => match x.as_u64().and_then(|x| u32::try_from(x).ok()) {
+            Some(x) if x >= 1 => x,
+            _ => {
+                return Err(format!(
+                    "Failed to parse the configuration file: invalid version `{x}`"
+                ))
+            }
+        },
+    };
+
+    if file_version > CONFIG_VERSION {
+        return Err(format!("The configuration file is created by a newer version of Jonitor (version {file_version}). The latest supported version is {CONFIG_VERSION}"));
+    }
+
+    for version in file_version..CONFIG_VERSION {
+        MIGRATIONS[version as usize - 1](object);
+        object.insert(
+            String::from("version"),
+            serde_json::Value::from(version + 1),
+        );
+    }
+
+    let mut warnings = Vec::with_capacity(0);
+
+    let config: Config = serde_ignored::deserialize(&value, |path| {
+        warnings.push(format!(
+            "unknown key `{path}` in the configuration file is ignored"
+        ));
+    })
+    .map_err(|e| format!("Failed to parse the configuration file: {e}"))?;
+
+    // keys written by this version of Jonitor but missing in the file
+    let mut has_missing_values = false;
+
+    if let (Ok(serde_json::Value::Object(expected)), Some(object)) =
+        (serde_json::to_value(&config), value.as_object())
+    {
+        for key in expected.keys().filter(|x| !object.contains_key(*x)) {
+            has_missing_values = true;
+
+            warnings.push(format!(
+                "`{key}` is missing in the configuration file, the default value is used"
+            ));
+        }
+    }
+
+    Ok(LoadedConfig {
+        config,
+        file_version,
+        needs_rewrite: file_version != CONFIG_VERSION || has_missing_values,
+        warnings,
+    })
+}
+
+/// Replaces the configuration file with the migrated configuration, keeping a backup of the
+/// old file
+fn rewrite(path: &Path, loaded: &LoadedConfig) -> Result<(), String> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(format!(".v{}.bak", loaded.file_version));
+    let backup_path = PathBuf::from(backup_path);
+
+    std::fs::copy(path, &backup_path)
+        .map_err(|e| format!("Failed to back up the configuration file: {e}"))?;
+
+    write(path, &loaded.config)?;
+
+    if loaded.file_version != CONFIG_VERSION {
+        crate::log(format!(
+            "The configuration file is migrated from version {} to version {CONFIG_VERSION}. The old file is saved to {}",
+            loaded.file_version,
+            backup_path.to_string_lossy()
+        ));
+    } else {
+        crate::log(format!(
+            "The missing values are added to the configuration file. The old file is saved to {}",
+            backup_path.to_string_lossy()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Writes the configuration file. The content is written to a temporary file first, so the
+/// existing file is not corrupted if writing fails.
+fn write(path: &Path, config: &Config) -> Result<(), String> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = File::create(&temp_path)
+        .map_err(|e| format!("Failed to create the configuration file: {e}"))?;
+
+    // indent using 4 spaces
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(Vec::with_capacity(100), formatter);
+    config
+        .serialize(&mut serializer)
+        .map_err(|e| format!("Failed to serialize configuration file content: {e}"))?;
+
+    file.write_all(&serializer.into_inner())
+        .map_err(|e| format!("Failed to write the configuration file: {e}"))?;
+
+    file.sync_all()
+        .map_err(|e| format!("Failed to save the configuration file: {e}"))?;
+
+    drop(file);
+
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to save the configuration file: {e}"))
+}
+
+/// Migrates a configuration file of Jonitor 1.1.0 and older, which has no version field
+fn migrate_v1_to_v2(_: &mut serde_json::Map<String, serde_json::Value>) {
+    // the fields of version 1 are unchanged in version 2
+}
+
+/// Migrates a configuration file with a single address given by ip and port
+fn migrate_v2_to_v3(object: &mut serde_json::Map<String, serde_json::Value>) {
+    let ip = match object.remove("ip") {
+        Some(serde_json::Value::String(x)) => x,
+        Some(x) => x.to_string(),
+        None => String::from(DEFAULT_IP),
+    };
+
+    // an invalid port is kept so it is reported when the configuration is validated
+    let port = match object.remove("port") {
+        Some(x) => x.to_string(),
+        None => DEFAULT_PORT.to_string(),
+    };
+
+    object.insert(
+        String::from("listeners"),
+        serde_json::json!([{ "address": socket_address(&ip, port) }]),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads a configuration file with the given content from a temporary file
+    fn load_str(name: &str, content: &str) -> Result<LoadedConfig, String> {
+        let path =
+            std::env::temp_dir().join(format!("jonitor-config-{name}-{}.json", std::process::id()));
+
+        std::fs::write(&path, content).unwrap();
+        let result = load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        result
+    }
+
+    fn addresses(config: &Config) -> Vec<&str> {
+        config
+            .listeners
+            .iter()
+            .map(|x| x.address.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn migrate_v1() {
+        // written by Jonitor 1.1.0, without a version
+        let loaded = load_str(
+            "v1",
+            r#"{ "ip": "0.0.0.0", "port": 8080, "polling_interval": 3000 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(loaded.file_version, 1);
+        assert!(loaded.needs_rewrite);
+        assert_eq!(loaded.config.version, CONFIG_VERSION);
+        assert_eq!(addresses(&loaded.config), ["0.0.0.0:8080"]);
+        assert_eq!(loaded.config.polling_interval, 3000);
+    }
+
+    #[test]
+    fn migrate_v2_ipv6() {
+        let loaded = load_str(
+            "v2-ipv6",
+            r#"{ "version": 2, "ip": "::1", "port": 10110, "polling_interval": 2000 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(loaded.file_version, 2);
+        assert_eq!(addresses(&loaded.config), ["[::1]:10110"]);
+    }
+
+    #[test]
+    fn migrate_v2_defaults() {
+        let loaded = load_str("v2-defaults", r#"{ "version": 2 }"#).unwrap();
+
+        assert_eq!(
+            addresses(&loaded.config),
+            [socket_address(DEFAULT_IP, DEFAULT_PORT).as_str()]
+        );
+        assert_eq!(loaded.config.polling_interval, DEFAULT_POLLING_INTERVAL);
+    }
+
+    #[test]
+    fn migrate_v2_invalid_port_is_reported() {
+        let loaded = load_str(
+            "v2-invalid-port",
+            r#"{ "version": 2, "ip": "127.0.0.1", "port": 70000, "polling_interval": 2000 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(addresses(&loaded.config), ["127.0.0.1:70000"]);
+        assert!(loaded.config.validate().is_err());
+    }
+
+    #[test]
+    fn current_version_is_not_rewritten() {
+        let content = serde_json::to_string(&Config::default()).unwrap();
+        let loaded = load_str("current", &content).unwrap();
+
+        assert_eq!(loaded.file_version, CONFIG_VERSION);
+        assert!(!loaded.needs_rewrite);
+        assert!(loaded.warnings.is_empty());
+        assert!(loaded.config == Config::default());
+    }
+
+    #[test]
+    fn missing_and_unknown_keys_are_warned() {
+        let loaded = load_str(
+            "warnings",
+            r#"{ "version": 3, "listeners": [{ "address": "127.0.0.1:1" }], "colour": "red" }"#,
+        )
+        .unwrap();
+
+        assert!(loaded.needs_rewrite);
+        assert!(loaded.warnings.iter().any(|x| x.contains("`colour`")));
+        assert!(loaded
+            .warnings
+            .iter()
+            .any(|x| x.contains("`polling_interval`")));
+    }
+
+    #[test]
+    fn invalid_versions_are_rejected() {
+        assert!(load_str("newer", r#"{ "version": 99 }"#).is_err());
+        assert!(load_str("zero", r#"{ "version": 0 }"#).is_err());
+        assert!(load_str("string", r#"{ "version": "2" }"#).is_err());
+        assert!(load_str("array", "[]").is_err());
+    }
+
+    #[test]
+    fn listener_auth_is_validated() {
+        let validate_listener = |listener: serde_json::Value| {
+            let config = Config {
+                listeners: vec![serde_json::from_value(listener).unwrap()],
+                ..Config::default()
+            };
+            config.validate()
+        };
+
+        // at least 1 user or token is required, as for the top-level auth
+        assert!(
+            validate_listener(serde_json::json!({ "address": "127.0.0.1:1", "auth": {} })).is_err()
+        );
+        assert!(validate_listener(serde_json::json!({
+            "address": "127.0.0.1:1",
+            "redirect_https_port": 443,
+            "auth": { "tokens": [{ "name": "ci", "hash": crate::auth::hash_token("x") }] }
+        }))
+        .is_err());
+        assert!(validate_listener(serde_json::json!({
+            "address": "127.0.0.1:1",
+            "auth": { "tokens": [{ "name": "ci", "hash": crate::auth::hash_token("x") }] }
+        }))
+        .is_ok());
+    }
+
+    #[test]
+    fn settings_are_saved() {
+        let path = std::env::temp_dir().join(format!(
+            "jonitor-config-settings-{}.json",
+            std::process::id()
+        ));
+        write(&path, &Config::default()).unwrap();
+
+        let mut settings_file = SettingsFile {
+            path: path.clone(),
+            args: ConfigArgs {
+                config: None,
+                listen: Vec::new(),
+                ip: None,
+                port: None,
+                polling_interval: None,
+                public_dir: None,
+                data_dir: None,
+                base_path: None,
+            },
+            polling_interval: Arc::new(AtomicU64::new(u64::from(DEFAULT_POLLING_INTERVAL))),
+        };
+
+        assert!(settings_file
+            .save(&Settings {
+                polling_interval: 5000
+            })
+            .is_ok());
+        assert_eq!(settings_file.get().polling_interval, 5000);
+        assert_eq!(load(&path).unwrap().config.polling_interval, 5000);
+
+        // invalid settings are not written
+        assert!(matches!(
+            settings_file.save(&Settings {
+                polling_interval: 10
+            }),
+            Err(SettingsError::Invalid(_))
+        ));
+        assert_eq!(load(&path).unwrap().config.polling_interval, 5000);
+
+        // the names of the configuration file are used, other names are rejected
+        let settings: Settings = serde_json::from_str(r#"{"polling_interval": 4000}"#).unwrap();
+        assert_eq!(settings.polling_interval, 4000);
+        assert!(serde_json::from_str::<Settings>(r#"{"pollingInterval": 4000}"#).is_err());
+
+        settings_file.args.polling_interval = Some(3000);
+        assert!(matches!(
+            settings_file.save(&Settings {
+                polling_interval: 4000
+            }),
+            Err(SettingsError::Overridden(_))
+        ));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
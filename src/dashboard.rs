@@ -0,0 +1,977 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::{NonZeroU16, NonZeroU32};
+
+use jonitor_client::SensorInfo;
+use serde::{Deserialize, Deserializer};
+
+use crate::selector::{self, DatasetSelector};
+
+/// Values of `ChartConfig::chart_type`
+pub(crate) const CHART_TYPES: [&str; 8] = [
+    "bar",
+    "line",
+    "gauge",
+    "table",
+    "stat",
+    "sparkline",
+    "heatmap",
+    "scatter",
+];
+
+/// Rules of the custom validations. Their errors start with the tag of the rule, which is
+/// removed from the message, since the rest of a message may contain user input.
+#[derive(Clone, Copy)]
+enum Rule {
+    EmptyString,
+    EmptyArray,
+    ChartType,
+    NegativeNumber,
+    DatasetBinding,
+    Variable,
+    ChartField,
+    Range,
+    Threshold,
+    InvalidValue,
+}
+
+// encloses the tag of a rule, serde never starts a message with it
+const RULE_TAG: char = '\u{1}';
+
+impl Rule {
+    const ALL: [Rule; 10] = [
+        Rule::EmptyString,
+        Rule::EmptyArray,
+        Rule::ChartType,
+        Rule::NegativeNumber,
+        Rule::DatasetBinding,
+        Rule::Variable,
+        Rule::ChartField,
+        Rule::Range,
+        Rule::Threshold,
+        Rule::InvalidValue,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Rule::EmptyString => "empty_string",
+            Rule::EmptyArray => "empty_array",
+            Rule::ChartType => "chart_type",
+            Rule::NegativeNumber => "negative_number",
+            Rule::DatasetBinding => "dataset_binding",
+            Rule::Variable => "variable",
+            Rule::ChartField => "chart_field",
+            Rule::Range => "range",
+            Rule::Threshold => "threshold",
+            Rule::InvalidValue => "invalid_value",
+        }
+    }
+
+    /// Returns a message tagged with the rule
+    fn message(self, message: impl fmt::Display) -> String {
+        format!("{RULE_TAG}{}{RULE_TAG}{message}", self.as_str())
+    }
+
+    fn error<E: serde::de::Error>(self, message: impl fmt::Display) -> E {
+        E::custom(self.message(message))
+    }
+
+    /// Returns the error of a value which is not `expected`, like `Error::invalid_value`
+    fn invalid_value<E: serde::de::Error>(
+        self,
+        unexpected: serde::de::Unexpected,
+        expected: &str,
+    ) -> E {
+        self.error(format!("invalid value: {unexpected}, expected {expected}"))
+    }
+
+    /// Returns the rule a message is tagged with, and the message without the tag
+    fn untag(message: &str) -> Option<(Rule, &str)> {
+        let (tag, message) = message.strip_prefix(RULE_TAG)?.split_once(RULE_TAG)?;
+
+        Rule::ALL
+            .into_iter()
+            .find(|x| x.as_str() == tag)
+            .map(|x| (x, message))
+    }
+}
+
+/// Values are shown with at most this many decimal places
+pub(crate) const MAX_DECIMALS: u8 = 6;
+
+/// Returns the number of datasets a chart of the type needs to be shown
+pub(crate) fn minimum_datasets(chart_type: &str) -> usize {
+    match chart_type {
+        "scatter" => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the warning of a chart which is removed, as `removed` of its datasets have no reading
+/// and it requires `minimum`
+pub(crate) fn removed_chart_warning(
+    path: String,
+    removed: usize,
+    minimum: usize,
+) -> ValidationWarning {
+    ValidationWarning {
+        path,
+        message: format!(
+            "the chart is removed, {removed} of its datasets match no reading and it requires \
+             {minimum}"
+        ),
+    }
+}
+
+/// Returns the warning of a template whose variables cannot match a sensor, as the sensors have
+/// not been read yet
+pub(crate) fn no_sensors_warning() -> ValidationWarning {
+    ValidationWarning {
+        path: String::from("$.variables"),
+        message: String::from(
+            "the sensors have not been read from HWiNFO yet, no variable matches a sensor",
+        ),
+    }
+}
+
+/// The first error found in a dashboard
+#[derive(serde::Serialize)]
+pub(crate) struct ValidationError {
+    // JSON path of the invalid value, e.g. `$[0].chartConfigs[1].title`
+    pub(crate) path: String,
+    // the position is not known for errors found after parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) column: Option<usize>,
+    // `syntax`, `type`, `missing_field`, `empty_string`, `empty_array`, `chart_type`,
+    // `negative_number`, `dataset_binding`, `variable`, `chart_field`, `range`, `threshold` or
+    // `invalid_value`
+    pub(crate) rule: &'static str,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.path)?;
+
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " (line {line} column {column})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A valid dashboard which may not show what is expected
+#[derive(serde::Serialize)]
+pub(crate) struct ValidationWarning {
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+#[derive(serde::Serialize)]
+pub(crate) struct Validation {
+    pub(crate) valid: bool,
+    pub(crate) errors: Vec<ValidationError>,
+    pub(crate) warnings: Vec<ValidationWarning>,
+}
+
+impl From<ValidationError> for Validation {
+    fn from(error: ValidationError) -> Self {
+        Validation {
+            valid: false,
+            errors: vec![error],
+            warnings: Vec::with_capacity(0),
+        }
+    }
+}
+
+/// Parses and validates the content of a dashboard file, the charts config shown by the web
+/// application. The file is either an array of groups, or a template with variables.
+pub(crate) fn parse(content: &str) -> Result<DashboardConfig, ValidationError> {
+    if is_template(content) {
+        deserialize::<DashboardConfig>(content)
+    } else {
+        let groups = deserialize::<Vec<ChartGroupConfig>>(content)?;
+
+        // templates are objects, the errors of arrays are reported the same way
+        if let Some(i) = groups.iter().position(|x| x.repeat.is_some()) {
+            return Err(ValidationError {
+                path: format!("$[{i}].repeat"),
+                line: None,
+                column: None,
+                rule: Rule::Variable.as_str(),
+                message: String::from(
+                    "repeat is only supported by templates, which have variables",
+                ),
+            });
+        }
+
+        Ok(DashboardConfig {
+            variables: BTreeMap::new(),
+            groups,
+        })
+    }
+}
+
+/// Returns whether a dashboard file is a template, which is an object instead of an array
+pub(crate) fn is_template(content: &str) -> bool {
+    content.trim_start().starts_with('{')
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(content: &str) -> Result<T, ValidationError> {
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+
+    let value = serde_path_to_error::deserialize::<_, T>(&mut deserializer).map_err(|e| {
+        let path = match e.path().to_string() {
+            x if x == "." => String::from("$"),
+            x if x.starts_with('[') => format!("${x}"),
+            x => format!("$.{x}"),
+        };
+
+        validation_error(path, e.into_inner())
+    })?;
+
+    // trailing characters
+    deserializer
+        .end()
+        .map_err(|e| validation_error(String::from("$"), e))?;
+
+    Ok(value)
+}
+
+fn validation_error(path: String, error: serde_json::Error) -> ValidationError {
+    let (line, column) = (error.line(), error.column());
+
+    let message = error.to_string();
+    let message = message
+        .strip_suffix(&format!(" at line {line} column {column}"))
+        .unwrap_or(&message)
+        .to_owned();
+
+    let (rule, message) = match error.classify() {
+        serde_json::error::Category::Data => match Rule::untag(&message) {
+            Some((rule, message)) => (rule.as_str(), message.to_owned()),
+            // the messages of serde start with the kind of error
+            None if message.starts_with("missing field") => ("missing_field", message),
+            None if message.starts_with("invalid type")
+                || message.starts_with("invalid length") =>
+            {
+                ("type", message)
+            }
+            None => ("invalid_value", message),
+        },
+        _ => ("syntax", message),
+    };
+
+    ValidationError {
+        path,
+        line: Some(line),
+        column: Some(column),
+        rule,
+        message,
+    }
+}
+
+/// Returns whether a dataset is removed when the selectors are resolved, as its selector matches
+/// no reading and it has no ids
+fn is_unresolved(dataset: &ChartDataset, sensor_info: &SensorInfo) -> bool {
+    dataset.sensor_id.is_none()
+        && dataset
+            .selector
+            .as_ref()
+            .is_some_and(|x| !x.uses_variables() && selector::resolve(x, sensor_info).is_empty())
+}
+
+/// Returns why a dataset does not show a reading of `sensor_info`
+fn dataset_warning(dataset: &ChartDataset, sensor_info: &SensorInfo) -> Option<String> {
+    if let Some(selector) = &dataset.selector {
+        // the variables are only replaced when the template is instantiated
+        if selector.uses_variables() || !selector::resolve(selector, sensor_info).is_empty() {
+            return None;
+        }
+
+        if dataset.sensor_id.is_none() {
+            return Some(String::from("the selector matches no reading"));
+        }
+    }
+
+    let (sensor_id, sensor_instance, reading_id) = (
+        dataset.sensor_id?,
+        dataset.sensor_instance?,
+        dataset.reading_id?,
+    );
+
+    let sensor = sensor_info
+        .sensors
+        .iter()
+        .find(|x| x.id == sensor_id && x.instance == sensor_instance);
+
+    match sensor {
+        None => Some(format!(
+            "sensor {sensor_id} (instance {sensor_instance}) is not found"
+        )),
+        Some(sensor) if !sensor.readings.iter().any(|x| x.id == reading_id) => Some(format!(
+            "reading {reading_id} is not found in sensor `{}`",
+            sensor.name
+        )),
+        Some(_) => None,
+    }
+}
+
+/// Validates a dashboard, and checks that every dataset refers to a reading in `sensor_info`
+/// when it is available
+pub(crate) fn validate(content: &str, sensor_info: Option<&SensorInfo>) -> Validation {
+    let config = match parse(content) {
+        Ok(x) => x,
+        Err(e) => return e.into(),
+    };
+
+    let mut warnings = Vec::new();
+
+    if let Some(sensor_info) = sensor_info {
+        for (name, variable) in config.variables.iter() {
+            let is_matched = sensor_info
+                .sensors
+                .iter()
+                .any(|x| selector::matches_pattern(&variable.sensor, &x.name));
+
+            if !is_matched {
+                warnings.push(ValidationWarning {
+                    path: format!("$.variables.{name}"),
+                    message: String::from("the variable matches no sensor"),
+                });
+            }
+        }
+
+        let groups_path = if is_template(content) {
+            "$.groups"
+        } else {
+            "$"
+        };
+
+        for (i, group) in config.groups.iter().enumerate() {
+            for (j, chart) in group.chart_configs.iter().enumerate() {
+                let chart_path = format!("{groups_path}[{i}].chartConfigs[{j}]");
+
+                for (k, dataset) in chart.datasets.iter().enumerate() {
+                    if let Some(message) = dataset_warning(dataset, sensor_info) {
+                        warnings.push(ValidationWarning {
+                            path: format!("{chart_path}.datasets[{k}]"),
+                            message,
+                        });
+                    }
+                }
+
+                let removed = chart
+                    .datasets
+                    .iter()
+                    .filter(|x| is_unresolved(x, sensor_info))
+                    .count();
+                let minimum = minimum_datasets(&chart.chart_type);
+
+                if removed > 0 && chart.datasets.len() - removed < minimum {
+                    warnings.push(removed_chart_warning(chart_path, removed, minimum));
+                }
+            }
+        }
+    } else if !config.variables.is_empty() {
+        warnings.push(no_sensors_warning());
+    }
+
+    Validation {
+        valid: true,
+        errors: Vec::with_capacity(0),
+        warnings,
+    }
+}
+
+/// The groups of a dashboard file, and the variables of a template
+#[derive(serde::Deserialize)]
+#[serde(try_from = "TemplateFields")]
+pub(crate) struct DashboardConfig {
+    pub(crate) variables: BTreeMap<String, VariableConfig>,
+    pub(crate) groups: Vec<ChartGroupConfig>,
+}
+
+#[derive(serde::Deserialize)]
+struct TemplateFields {
+    #[serde(default)]
+    variables: BTreeMap<String, VariableConfig>,
+    groups: Vec<ChartGroupConfig>,
+}
+
+impl TryFrom<TemplateFields> for DashboardConfig {
+    type Error = String;
+
+    fn try_from(fields: TemplateFields) -> Result<Self, Self::Error> {
+        for name in fields.variables.keys() {
+            if name.is_empty() || !name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_') {
+                return Err(Rule::Variable.message(format!(
+                    "`{name}` is not a valid variable name, only letters, digits and `_` are allowed"
+                )));
+            }
+        }
+
+        for (i, group) in fields.groups.iter().enumerate() {
+            if let Some(name) = &group.repeat {
+                if !fields.variables.contains_key(name) {
+                    return Err(Rule::Variable
+                        .message(format!("group {i} repeats an undefined variable `{name}`")));
+                }
+            }
+        }
+
+        Ok(DashboardConfig {
+            variables: fields.variables,
+            groups: fields.groups,
+        })
+    }
+}
+
+/// A variable of a template, e.g. `$gpu` in any text of the template is replaced by the name of
+/// a sensor matching `sensor`
+#[derive(serde::Deserialize)]
+pub(crate) struct VariableConfig {
+    // pattern of the sensor name, the same as `DatasetSelector::sensor`
+    #[serde(deserialize_with = "no_empty_string")]
+    pub(crate) sensor: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub(crate) struct ChartGroupConfig {
+    grid_breakpoints: ChartGridBreakpoints,
+    #[serde(deserialize_with = "no_empty_array")]
+    chart_configs: Vec<ChartConfig>,
+    // name of a template variable, the group is repeated for every sensor it matches
+    #[serde(default)]
+    repeat: Option<String>,
+}
+
+impl ChartGroupConfig {
+    pub(crate) fn chart_count(&self) -> usize {
+        self.chart_configs.len()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub(crate) struct ChartGridBreakpoints {
+    small_columns: u16,
+    small_width: u16,
+    medium_columns: u16,
+    medium_width: u16,
+    large_columns: u16,
+    large_width: u16,
+    extra_large_columns: u16,
+    extra_large_width: u16,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(try_from = "ChartFields")]
+#[allow(dead_code)]
+pub(crate) struct ChartConfig {
+    title: String,
+    chart_type: String,
+    data_count: u32,
+    maximum_value: f32,
+    height: u16,
+    animation_duration: u16,
+    show_legend: bool,
+    show_labels: bool,
+    auto_colors: bool,
+    datasets: Vec<ChartDataset>,
+    // lower end of the axis or gauge, 0 if not set
+    minimum_value: Option<f32>,
+    // colors of value ranges, sorted by value
+    thresholds: Vec<Threshold>,
+    // decimal places of the values, unless set by the dataset
+    decimals: Option<u8>,
+    // "stat", number of previous values the trend is calculated from
+    trend_count: Option<NonZeroU32>,
+    // "sparkline", whether the area under the line is filled
+    fill: Option<bool>,
+    // "heatmap", number of ranges from `minimum_value` to `maximum_value` the values are counted
+    // in
+    bucket_count: Option<NonZeroU16>,
+    // "scatter", maximum of the x axis, which shows the first dataset
+    x_maximum_value: Option<f32>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartFields {
+    #[serde(deserialize_with = "no_empty_string")]
+    title: String,
+    #[serde(deserialize_with = "no_invalid_chart")]
+    chart_type: String,
+    data_count: u32,
+    #[serde(deserialize_with = "no_negative_float")]
+    maximum_value: f32,
+    height: u16,
+    animation_duration: u16,
+    show_legend: bool,
+    show_labels: bool,
+    auto_colors: bool,
+    #[serde(deserialize_with = "no_invalid_datasets")]
+    datasets: Vec<ChartDataset>,
+    #[serde(default)]
+    minimum_value: Option<f32>,
+    #[serde(default, deserialize_with = "no_unsorted_thresholds")]
+    thresholds: Vec<Threshold>,
+    #[serde(default, deserialize_with = "no_invalid_decimals")]
+    decimals: Option<u8>,
+    #[serde(default)]
+    trend_count: Option<NonZeroU32>,
+    #[serde(default)]
+    fill: Option<bool>,
+    #[serde(default)]
+    bucket_count: Option<NonZeroU16>,
+    #[serde(default, deserialize_with = "no_negative_optional_float")]
+    x_maximum_value: Option<f32>,
+}
+
+impl TryFrom<ChartFields> for ChartConfig {
+    type Error = String;
+
+    fn try_from(fields: ChartFields) -> Result<Self, Self::Error> {
+        let chart_type = fields.chart_type.as_str();
+
+        // `maximum_value` is 0 for the chart types without it, and scatter charts scaled to the
+        // values
+        if fields.maximum_value > 0.0
+            && fields
+                .minimum_value
+                .is_some_and(|x| x >= fields.maximum_value)
+        {
+            return Err(Rule::Range.message("minimumValue must be less than maximumValue"));
+        }
+
+        // the chart type supporting each field
+        let type_fields = [
+            ("trendCount", "stat", fields.trend_count.is_some()),
+            ("fill", "sparkline", fields.fill.is_some()),
+            ("bucketCount", "heatmap", fields.bucket_count.is_some()),
+            ("xMaximumValue", "scatter", fields.x_maximum_value.is_some()),
+        ];
+
+        for (name, supported_type, is_set) in type_fields {
+            if is_set && chart_type != supported_type {
+                return Err(Rule::ChartField.message(format!(
+                    r#"{name} is not supported by "{chart_type}" charts"#
+                )));
+            }
+        }
+
+        if chart_type == "heatmap" && fields.bucket_count.is_none() {
+            return Err(Rule::ChartField.message(r#"bucketCount is required by "heatmap" charts"#));
+        }
+
+        let dataset_count = match chart_type {
+            "stat" | "heatmap" => Some((1, "1 dataset")),
+            "scatter" => Some((2, "2 datasets, the x and y axes")),
+            _ => None,
+        };
+
+        if let Some((count, description)) = dataset_count {
+            if fields.datasets.len() != count {
+                return Err(Rule::ChartField
+                    .message(format!(r#""{chart_type}" charts require {description}"#)));
+            }
+        }
+
+        Ok(ChartConfig {
+            title: fields.title,
+            chart_type: fields.chart_type,
+            data_count: fields.data_count,
+            maximum_value: fields.maximum_value,
+            height: fields.height,
+            animation_duration: fields.animation_duration,
+            show_legend: fields.show_legend,
+            show_labels: fields.show_labels,
+            auto_colors: fields.auto_colors,
+            datasets: fields.datasets,
+            minimum_value: fields.minimum_value,
+            thresholds: fields.thresholds,
+            decimals: fields.decimals,
+            trend_count: fields.trend_count,
+            fill: fields.fill,
+            bucket_count: fields.bucket_count,
+            x_maximum_value: fields.x_maximum_value,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub(crate) struct ChartDataset {
+    // the ids are optional when `selector` is set, and are used if it matches no reading
+    #[serde(default)]
+    sensor_id: Option<u32>,
+    #[serde(default)]
+    sensor_instance: Option<u32>,
+    #[serde(default)]
+    reading_id: Option<u32>,
+    #[serde(default)]
+    selector: Option<DatasetSelector>,
+    label: String,
+    #[serde(deserialize_with = "no_empty_string")]
+    unit: String,
+    #[serde(deserialize_with = "no_empty_string")]
+    color: String,
+    // the value is shown as `value * scale + offset` in `unit`, e.g. 1.8 and 32 for °C to °F
+    #[serde(default, deserialize_with = "no_zero_float")]
+    scale: Option<f32>,
+    #[serde(default)]
+    offset: Option<f32>,
+    #[serde(default, deserialize_with = "no_invalid_decimals")]
+    decimals: Option<u8>,
+}
+
+/// Values from `value` up to the value of the next threshold are shown in `color`, e.g. a gauge
+/// turns red above a temperature. The values are compared after the unit conversion.
+#[derive(serde::Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct Threshold {
+    value: f32,
+    #[serde(deserialize_with = "no_empty_string")]
+    color: String,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+fn no_empty_array<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let vec = <Vec<T>>::deserialize(deserializer)?;
+
+    if vec.is_empty() {
+        return Err(Rule::EmptyArray.error("unexpected empty array"));
+    }
+
+    Ok(vec)
+}
+
+/// Checks that the datasets are not empty, and that every dataset has the ids of a reading or a
+/// selector
+fn no_invalid_datasets<'de, D>(deserializer: D) -> Result<Vec<ChartDataset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let datasets = no_empty_array::<D, ChartDataset>(deserializer)?;
+
+    for (i, dataset) in datasets.iter().enumerate() {
+        let has_ids = dataset.sensor_id.is_some()
+            && dataset.sensor_instance.is_some()
+            && dataset.reading_id.is_some();
+
+        match &dataset.selector {
+            None if !has_ids => {
+                return Err(Rule::DatasetBinding.error(format!(
+                    "dataset {i} requires sensorId, sensorInstance and readingId, or a selector"
+                )))
+            }
+            Some(selector) if selector.is_empty() => {
+                return Err(Rule::DatasetBinding
+                    .error(format!("dataset {i} has a selector without any condition")))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(datasets)
+}
+
+fn no_empty_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let str = String::deserialize(deserializer)?;
+
+    if str.trim().is_empty() {
+        return Err(Rule::EmptyString.error("unexpected empty string"));
+    }
+
+    Ok(str)
+}
+
+fn no_invalid_chart<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let str = String::deserialize(deserializer)?;
+
+    if !CHART_TYPES.contains(&str.as_str()) {
+        return Err(Rule::ChartType.invalid_value(
+            serde::de::Unexpected::Str(&str),
+            r#""bar", "line", "gauge", "table", "stat", "sparkline", "heatmap" or "scatter""#,
+        ));
+    }
+
+    Ok(str)
+}
+
+fn no_negative_float<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let num = f32::deserialize(deserializer)?;
+
+    if num < 0.0 {
+        return Err(Rule::NegativeNumber.invalid_value(
+            serde::de::Unexpected::Float(f64::from(num)),
+            "0 or a positive floating point number",
+        ));
+    }
+
+    Ok(num)
+}
+
+fn no_negative_optional_float<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    no_negative_float(deserializer).map(Some)
+}
+
+/// Checks that the values of the thresholds are in ascending order
+fn no_unsorted_thresholds<'de, D>(deserializer: D) -> Result<Vec<Threshold>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let thresholds = <Vec<Threshold>>::deserialize(deserializer)?;
+
+    for (i, pair) in thresholds.windows(2).enumerate() {
+        if pair[1].value <= pair[0].value {
+            return Err(Rule::Threshold.error(format!(
+                "threshold {} must be greater than the value of the previous threshold",
+                i + 1
+            )));
+        }
+    }
+
+    Ok(thresholds)
+}
+
+fn no_invalid_decimals<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let num = u8::deserialize(deserializer)?;
+
+    if num > MAX_DECIMALS {
+        return Err(Rule::InvalidValue.invalid_value(
+            serde::de::Unexpected::Unsigned(u64::from(num)),
+            "0 to 6 decimal places",
+        ));
+    }
+
+    Ok(Some(num))
+}
+
+fn no_zero_float<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let num = f32::deserialize(deserializer)?;
+
+    if num == 0.0 {
+        return Err(Rule::InvalidValue.invalid_value(
+            serde::de::Unexpected::Float(f64::from(num)),
+            "a number other than 0",
+        ));
+    }
+
+    Ok(Some(num))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a dashboard with one chart, with `chart` merged into its config
+    fn dashboard(chart: serde_json::Value) -> String {
+        let mut config = serde_json::json!({
+            "title": "CPU",
+            "chartType": "line",
+            "dataCount": 60,
+            "maximumValue": 100,
+            "height": 200,
+            "animationDuration": 0,
+            "showLegend": true,
+            "showLabels": true,
+            "autoColors": false,
+            "datasets": [{
+                "sensorId": 1, "sensorInstance": 0, "readingId": 2,
+                "label": "Temperature", "unit": "°C", "color": "red"
+            }]
+        });
+
+        if let (Some(config), serde_json::Value::Object(chart)) = (config.as_object_mut(), chart) {
+            config.extend(chart);
+        }
+
+        serde_json::json!([{
+            "gridBreakpoints": {
+                "smallColumns": 1, "smallWidth": 0,
+                "mediumColumns": 1, "mediumWidth": 768,
+                "largeColumns": 2, "largeWidth": 992,
+                "extraLargeColumns": 2, "extraLargeWidth": 1200
+            },
+            "chartConfigs": [config]
+        }])
+        .to_string()
+    }
+
+    fn error(content: &str) -> ValidationError {
+        let Err(e) = parse(content) else {
+            panic!("the dashboard is valid: {content}");
+        };
+
+        e
+    }
+
+    #[test]
+    fn valid_dashboard() {
+        assert!(parse(&dashboard(serde_json::json!({}))).is_ok());
+    }
+
+    #[test]
+    fn custom_rules_are_tagged() {
+        let e = error(&dashboard(serde_json::json!({ "title": " " })));
+        assert_eq!(e.rule, "empty_string");
+        assert_eq!(e.message, "unexpected empty string");
+        assert_eq!(e.path, "$[0].chartConfigs[0].title");
+        assert!(e.line.is_some() && e.column.is_some());
+
+        let e = error(&dashboard(serde_json::json!({ "chartType": "pie" })));
+        assert_eq!(e.rule, "chart_type");
+        assert!(e
+            .message
+            .starts_with(r#"invalid value: string "pie", expected "bar""#));
+
+        let e = error(&dashboard(serde_json::json!({ "minimumValue": 100 })));
+        assert_eq!(e.rule, "range");
+        assert_eq!(e.message, "minimumValue must be less than maximumValue");
+        assert!(parse(&dashboard(serde_json::json!({
+            "chartType": "bar", "maximumValue": 0, "minimumValue": 20
+        })))
+        .is_ok());
+
+        let e = error(&dashboard(serde_json::json!({ "fill": true })));
+        assert_eq!(e.rule, "chart_field");
+        assert_eq!(e.message, r#"fill is not supported by "line" charts"#);
+
+        let e = error(&dashboard(serde_json::json!({
+            "thresholds": [{ "value": 80, "color": "red" }, { "value": 60, "color": "orange" }]
+        })));
+        assert_eq!(e.rule, "threshold");
+
+        let e = error(&dashboard(serde_json::json!({ "decimals": 7 })));
+        assert_eq!(e.rule, "invalid_value");
+        assert_eq!(
+            e.message,
+            "invalid value: integer `7`, expected 0 to 6 decimal places"
+        );
+    }
+
+    #[test]
+    fn user_input_does_not_change_the_rule() {
+        // the messages of the custom rules are found in user input
+        let e = error(&dashboard(
+            serde_json::json!({ "height": "charts require" }),
+        ));
+        assert_eq!(e.rule, "type");
+        assert!(e
+            .message
+            .starts_with(r#"invalid type: string "charts require""#));
+
+        let e = error(&dashboard(serde_json::json!({
+            "chartType": "unexpected empty string"
+        })));
+        assert_eq!(e.rule, "chart_type");
+
+        let e = error(&dashboard(serde_json::json!({
+            "title": format!("{RULE_TAG}range{RULE_TAG}"),
+            "height": -1
+        })));
+        assert_eq!(e.rule, "invalid_value");
+
+        let e = error(&dashboard(serde_json::json!({ "datasets": [] })));
+        assert_eq!(e.rule, "empty_array");
+    }
+
+    #[test]
+    fn serde_errors() {
+        let e = error("[");
+        assert_eq!(e.rule, "syntax");
+
+        let e = error(&dashboard(serde_json::json!({})).replace(r#""height":200,"#, ""));
+        assert_eq!(e.rule, "missing_field");
+        assert_eq!(e.message, "missing field `height`");
+    }
+
+    #[test]
+    fn repeat_outside_template_has_no_position() {
+        let mut content: serde_json::Value =
+            serde_json::from_str(&dashboard(serde_json::json!({}))).unwrap();
+        content[0]["repeat"] = serde_json::Value::from("cpu");
+
+        let e = error(&content.to_string());
+        assert_eq!(e.rule, "variable");
+        assert_eq!(e.path, "$[0].repeat");
+        assert_eq!(e.line, None);
+        assert_eq!(
+            e.to_string(),
+            "repeat is only supported by templates, which have variables at $[0].repeat"
+        );
+
+        let json = serde_json::to_value(&e).unwrap();
+        assert!(json.get("line").is_none() && json.get("column").is_none());
+    }
+
+    #[test]
+    fn charts_with_too_few_readings_are_reported() {
+        let sensor_info = SensorInfo {
+            updated_count: 1,
+            sensors: Vec::new(),
+        };
+        let dataset = serde_json::json!({
+            "selector": { "sensor": "CPU*" }, "label": "A", "unit": "°C", "color": "red"
+        });
+
+        let content = dashboard(serde_json::json!({ "datasets": [dataset] }));
+        let validation = validate(&content, Some(&sensor_info));
+
+        assert!(validation.valid);
+        let paths: Vec<&str> = validation
+            .warnings
+            .iter()
+            .map(|x| x.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            ["$[0].chartConfigs[0].datasets[0]", "$[0].chartConfigs[0]"]
+        );
+    }
+
+    #[test]
+    fn template_variables_are_checked() {
+        let groups: serde_json::Value =
+            serde_json::from_str(&dashboard(serde_json::json!({}))).unwrap();
+
+        let mut template = serde_json::json!({ "variables": {}, "groups": groups });
+        template["groups"][0]["repeat"] = serde_json::Value::from("cpu");
+
+        let e = error(&template.to_string());
+        assert_eq!(e.rule, "variable");
+        assert_eq!(e.message, "group 0 repeats an undefined variable `cpu`");
+    }
+}
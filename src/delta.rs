@@ -0,0 +1,266 @@
+use byteorder::{ByteOrder, LittleEndian};
+use jonitor_client::frame::{
+    Precision, DELTA_HEADER_SIZE, FRAME_KIND_DELTA, FRAME_KIND_KEYFRAME, FULL_HEADER_SIZE,
+};
+
+/// Number of delta frames sent between two keyframes
+const KEYFRAME_INTERVAL: u32 = 30;
+
+/// Converts full data frames into delta-encoded frames for a single WebSocket client. The frame
+/// layout is described in `jonitor_client::frame`.
+///
+/// A keyframe is sent for the first frame, every `KEYFRAME_INTERVAL` delta frames, and whenever
+/// the list of sensors/readings changes.
+///
+/// # Example content of a delta frame
+/// ```
+/// Vec<u8>[
+///     d2, 02, 96, 49, 00, 00, 00, 00, // last_poll_time (i64)
+///     01, 00, 00, 00, 00, 00, 00, 00, // sensor_info_updated_count (u64)
+///     01, 00, 00, 00,                 // frame kind, delta (u32)
+///     01, 00, 00, 00,                 // entry count (u32)
+///     04, 00, 00, 00,                 // offset 4 (u32)
+///     8f, c2, f5, 28, 5c, 8f, 1f, 40, // value at offset 4 (f64)
+/// ]
+/// ```
+pub(crate) struct DeltaEncoder {
+    precision: Precision,
+    // values of the last frame sent to the client, stored as bits so NaN compares equal
+    last_values: Vec<u64>,
+    // sensor_info_updated_count of the last frame sent to the client
+    last_updated_count: u64,
+    // number of delta frames sent since the last keyframe, `None` before the first keyframe
+    frames_since_keyframe: Option<u32>,
+}
+
+impl DeltaEncoder {
+    pub(crate) fn new(precision: Precision) -> DeltaEncoder {
+        DeltaEncoder {
+            precision,
+            last_values: Vec::with_capacity(0),
+            last_updated_count: 0,
+            frames_since_keyframe: None,
+        }
+    }
+
+    /// Encodes a full data frame, as returned by `SharedMemory::read()`, into a keyframe or a
+    /// delta frame.
+    pub(crate) fn encode(&mut self, frame: &[u8]) -> Vec<u8> {
+        if frame.len() < FULL_HEADER_SIZE {
+            return frame.to_vec();
+        }
+
+        let updated_count = LittleEndian::read_u64(&frame[8..16]);
+
+        let values = frame[FULL_HEADER_SIZE..]
+            .chunks_exact(8)
+            .map(|chunk| {
+                let value = LittleEndian::read_f64(chunk);
+
+                match self.precision {
+                    Precision::F32 => u64::from((value as f32).to_bits()),
+                    Precision::F64 => value.to_bits(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let is_keyframe = match self.frames_since_keyframe {
+            None => true,
+            Some(count) => {
+                count >= KEYFRAME_INTERVAL
+                    || updated_count != self.last_updated_count
+                    || values.len() != self.last_values.len()
+            }
+        };
+
+        let mut output = Vec::with_capacity(DELTA_HEADER_SIZE + values.len() * 12);
+        output.extend_from_slice(&frame[0..FULL_HEADER_SIZE]);
+
+        if is_keyframe {
+            output.extend_from_slice(&FRAME_KIND_KEYFRAME.to_le_bytes());
+            output.extend_from_slice(&(values.len() as u32).to_le_bytes());
+
+            for value in values.iter() {
+                self.push_value(&mut output, *value);
+            }
+
+            self.frames_since_keyframe = Some(0);
+        } else {
+            output.extend_from_slice(&FRAME_KIND_DELTA.to_le_bytes());
+            // replaced with the real count after the changed values are written
+            output.extend_from_slice(&0u32.to_le_bytes());
+
+            let mut count = 0u32;
+
+            for (i, (value, last_value)) in values.iter().zip(self.last_values.iter()).enumerate() {
+                if value == last_value {
+                    continue;
+                }
+
+                // offsets 0 and 1 are used by the header of a full data frame
+                output.extend_from_slice(&(2 + i as u32).to_le_bytes());
+                self.push_value(&mut output, *value);
+
+                count += 1;
+            }
+
+            output[20..24].copy_from_slice(&count.to_le_bytes());
+
+            self.frames_since_keyframe = self.frames_since_keyframe.map(|x| x + 1);
+        }
+
+        self.last_updated_count = updated_count;
+        self.last_values = values;
+
+        output
+    }
+
+    fn push_value(&self, output: &mut Vec<u8>, bits: u64) {
+        match self.precision {
+            Precision::F32 => output.extend_from_slice(&(bits as u32).to_le_bytes()),
+            Precision::F64 => output.extend_from_slice(&bits.to_le_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jonitor_client::frame::{DecodeError, DeltaDecoder};
+
+    use super::*;
+
+    fn full_frame(poll_time: i64, updated_count: u64, values: &[f64]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&poll_time.to_le_bytes());
+        frame.extend_from_slice(&updated_count.to_le_bytes());
+
+        for value in values {
+            frame.extend_from_slice(&value.to_le_bytes());
+        }
+
+        frame
+    }
+
+    fn kind_and_count(frame: &[u8]) -> (u32, u32) {
+        (
+            LittleEndian::read_u32(&frame[16..20]),
+            LittleEndian::read_u32(&frame[20..24]),
+        )
+    }
+
+    #[test]
+    fn first_frame_is_keyframe() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        let output = encoder.encode(&full_frame(1, 1, &[1.0, 2.0, 3.0, 4.0]));
+
+        assert_eq!(kind_and_count(&output), (FRAME_KIND_KEYFRAME, 4));
+        assert_eq!(output.len(), DELTA_HEADER_SIZE + 4 * 8);
+    }
+
+    #[test]
+    fn only_changed_values_are_sent() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        encoder.encode(&full_frame(1, 1, &[1.0, 2.0, 3.0, 4.0]));
+        let output = encoder.encode(&full_frame(2, 1, &[1.0, 2.5, 3.0, 4.0]));
+
+        assert_eq!(kind_and_count(&output), (FRAME_KIND_DELTA, 1));
+        // offset 3 is the second value
+        assert_eq!(LittleEndian::read_u32(&output[24..28]), 3);
+        assert_eq!(LittleEndian::read_f64(&output[28..36]), 2.5);
+    }
+
+    #[test]
+    fn unchanged_nan_is_not_sent() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        encoder.encode(&full_frame(1, 1, &[f64::NAN, 1.0]));
+        let output = encoder.encode(&full_frame(2, 1, &[f64::NAN, 1.0]));
+
+        assert_eq!(kind_and_count(&output), (FRAME_KIND_DELTA, 0));
+    }
+
+    #[test]
+    fn keyframe_after_interval() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        encoder.encode(&full_frame(0, 1, &[1.0]));
+
+        for i in 1..=KEYFRAME_INTERVAL {
+            let output = encoder.encode(&full_frame(i64::from(i), 1, &[1.0]));
+            assert_eq!(kind_and_count(&output).0, FRAME_KIND_DELTA);
+        }
+
+        let output = encoder.encode(&full_frame(100, 1, &[1.0]));
+        assert_eq!(kind_and_count(&output).0, FRAME_KIND_KEYFRAME);
+    }
+
+    #[test]
+    fn keyframe_when_sensors_change() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        encoder.encode(&full_frame(1, 1, &[1.0, 2.0]));
+
+        let output = encoder.encode(&full_frame(2, 2, &[1.0, 2.0]));
+        assert_eq!(kind_and_count(&output).0, FRAME_KIND_KEYFRAME);
+
+        let output = encoder.encode(&full_frame(3, 2, &[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(kind_and_count(&output), (FRAME_KIND_KEYFRAME, 4));
+    }
+
+    #[test]
+    fn short_frame_is_unchanged() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+
+        assert_eq!(encoder.encode(&[1, 2, 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn decoder_reproduces_full_frames() {
+        let frames = [
+            full_frame(1, 1, &[1.0, 2.0, 3.0, 4.0]),
+            full_frame(2, 1, &[1.5, 2.0, 3.0, 4.5]),
+            full_frame(3, 1, &[1.5, 2.0, 3.0, 4.5]),
+            full_frame(4, 2, &[7.0, 8.0]),
+            full_frame(5, 2, &[7.0, 9.0]),
+        ];
+
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        let mut decoder = DeltaDecoder::new(Precision::F64);
+
+        for frame in frames.iter() {
+            let decoded = decoder.decode(&encoder.encode(frame)).unwrap();
+            let expected = jonitor_client::frame::decode_full(frame).unwrap();
+
+            assert_eq!(*decoded, expected);
+        }
+    }
+
+    #[test]
+    fn f32_precision_rounds_values() {
+        let mut encoder = DeltaEncoder::new(Precision::F32);
+        let mut decoder = DeltaDecoder::new(Precision::F32);
+
+        let output = encoder.encode(&full_frame(1, 1, &[0.1, 2.0]));
+        assert_eq!(output.len(), DELTA_HEADER_SIZE + 2 * 4);
+        assert_eq!(
+            decoder.decode(&output).unwrap().values,
+            vec![f64::from(0.1f32), 2.0]
+        );
+
+        // a change smaller than the precision of f32 is not sent
+        let output = encoder.encode(&full_frame(2, 1, &[0.1 + 1e-12, 3.0]));
+        assert_eq!(kind_and_count(&output), (FRAME_KIND_DELTA, 1));
+        assert_eq!(
+            decoder.decode(&output).unwrap().values,
+            vec![f64::from(0.1f32), 3.0]
+        );
+    }
+
+    #[test]
+    fn delta_without_keyframe_is_rejected() {
+        let mut encoder = DeltaEncoder::new(Precision::F64);
+        encoder.encode(&full_frame(1, 1, &[1.0]));
+        let delta = encoder.encode(&full_frame(2, 1, &[2.0]));
+
+        let mut decoder = DeltaDecoder::new(Precision::F64);
+
+        assert_eq!(decoder.decode(&delta), Err(DecodeError::MissingKeyframe));
+    }
+}
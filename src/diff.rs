@@ -0,0 +1,384 @@
+use std::fmt::Write;
+
+/// Number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// The middle of two texts is shown as entirely replaced when they differ by more lines than
+/// this, which bounds the memory used by the diff
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Returns the differences between two texts in the unified diff format, or an empty string if
+/// the texts are the same
+pub(crate) fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let ops = edits(&a, &b);
+
+    let mut output = String::new();
+
+    if ops.iter().all(|x| *x == Op::Equal) {
+        return output;
+    }
+
+    let _ = writeln!(output, "--- {old_label}");
+    let _ = writeln!(output, "+++ {new_label}");
+
+    // line positions in both texts before each operation
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut x, mut y) = (0, 0);
+    for op in ops.iter() {
+        positions.push((x, y));
+
+        match op {
+            Op::Equal => {
+                x += 1;
+                y += 1;
+            }
+            Op::Delete => x += 1,
+            Op::Insert => y += 1,
+        }
+    }
+    positions.push((x, y));
+
+    let changes: Vec<usize> = (0..ops.len()).filter(|i| ops[*i] != Op::Equal).collect();
+
+    let mut i = 0;
+    while i < changes.len() {
+        // changes separated by a few unchanged lines are shown in the same hunk
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1] - changes[j] <= CONTEXT_LINES * 2 + 1 {
+            j += 1;
+        }
+
+        let start = changes[i].saturating_sub(CONTEXT_LINES);
+        let end = (changes[j] + CONTEXT_LINES + 1).min(ops.len());
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+
+        let _ = writeln!(
+            output,
+            "@@ -{} +{} @@",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        );
+
+        for k in start..end {
+            let (x, y) = positions[k];
+
+            let _ = match ops[k] {
+                Op::Equal => writeln!(output, " {}", a[x]),
+                Op::Delete => writeln!(output, "-{}", a[x]),
+                Op::Insert => writeln!(output, "+{}", b[y]),
+            };
+        }
+
+        i = j + 1;
+    }
+
+    output
+}
+
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        // an empty range refers to the line before it
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+/// Returns the operations which change `a` to `b`
+fn edits(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let middle_a = &a[prefix..a.len() - suffix];
+    let middle_b = &b[prefix..b.len() - suffix];
+
+    let mut ops = vec![Op::Equal; prefix];
+
+    match myers(middle_a, middle_b) {
+        Some(x) => ops.extend(x),
+        None => {
+            ops.resize(ops.len() + middle_a.len(), Op::Delete);
+            ops.resize(ops.len() + middle_b.len(), Op::Insert);
+        }
+    }
+
+    ops.resize(ops.len() + suffix, Op::Equal);
+
+    ops
+}
+
+/// Returns the shortest edit script found by Myers' algorithm, or `None` if it is longer than
+/// `MAX_EDIT_DISTANCE`
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+
+    // furthest x reached on each diagonal k = x - y, indexed by k + offset
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // v of diagonals -d..=d before each round d, for backtracking
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut distance = None;
+
+    'outer: for d in 0..=max {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[index] = x;
+
+            if x >= n && y >= m {
+                distance = Some(d);
+                break 'outer;
+            }
+        }
+    }
+
+    let distance = distance?;
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for d in (1..=distance).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let get = |k: isize| v[(k + d) as usize];
+
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+
+        if x == prev_x {
+            ops.push(Op::Insert);
+        } else {
+            ops.push(Op::Delete);
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    while x > 0 && y > 0 {
+        ops.push(Op::Equal);
+        x -= 1;
+        y -= 1;
+    }
+
+    ops.reverse();
+
+    Some(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies a unified diff to `old`, checking the hunk ranges and the context lines
+    fn apply(old: &str, diff: &str) -> String {
+        let old: Vec<&str> = old.lines().collect();
+        let mut new = Vec::new();
+        let mut position = 0;
+
+        let parse_range = |range: &str| -> (usize, usize) {
+            match range.split_once(',') {
+                Some((start, count)) => (start.parse().unwrap(), count.parse().unwrap()),
+                None => (range.parse().unwrap(), 1),
+            }
+        };
+
+        for line in diff.lines().skip(2) {
+            if let Some(header) = line.strip_prefix("@@ -") {
+                let (old_range, _) = header.trim_end_matches(" @@").split_once(" +").unwrap();
+                let (start, count) = parse_range(old_range);
+
+                // an empty range refers to the line before it
+                let start = if count == 0 { start } else { start - 1 };
+
+                new.extend_from_slice(&old[position..start]);
+                position = start;
+            } else if let Some(text) = line.strip_prefix(' ') {
+                assert_eq!(old[position], text);
+                new.push(text);
+                position += 1;
+            } else if let Some(text) = line.strip_prefix('-') {
+                assert_eq!(old[position], text);
+                position += 1;
+            } else if let Some(text) = line.strip_prefix('+') {
+                new.push(text);
+            } else {
+                panic!("unexpected line `{line}`");
+            }
+        }
+
+        new.extend_from_slice(&old[position..]);
+
+        new.join("\n")
+    }
+
+    fn lines(values: impl IntoIterator<Item = String>) -> String {
+        values.into_iter().collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn same_texts_have_no_diff() {
+        assert_eq!(unified("", "", "a", "b"), "");
+        assert_eq!(unified("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+
+    #[test]
+    fn single_change() {
+        let diff = unified("a\nb\nc\n", "a\nx\nc\n", "old", "new");
+
+        assert_eq!(diff, "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n");
+    }
+
+    #[test]
+    fn empty_ranges() {
+        assert_eq!(
+            unified("", "a\n", "old", "new"),
+            "--- old\n+++ new\n@@ -0,0 +1 @@\n+a\n"
+        );
+        assert_eq!(
+            unified("a\n", "", "old", "new"),
+            "--- old\n+++ new\n@@ -1 +0,0 @@\n-a\n"
+        );
+
+        // the lines are added after the first line
+        assert_eq!(
+            unified("a\n", "a\nb\n", "old", "new"),
+            "--- old\n+++ new\n@@ -1 +1,2 @@\n a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_are_separate_hunks() {
+        let old = lines((1..=20).map(|x| x.to_string()));
+        let new = old
+            .replace("\n2\n", "\ntwo\n")
+            .replace("\n19\n", "\nnineteen\n");
+
+        let diff = unified(&old, &new, "old", "new");
+
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n"));
+        assert_eq!(apply(&old, &diff), new);
+    }
+
+    #[test]
+    fn close_changes_share_a_hunk() {
+        let old = lines((1..=20).map(|x| x.to_string()));
+        let new = old
+            .replace("\n5\n", "\nfive\n")
+            .replace("\n12\n", "\ntwelve\n");
+
+        let diff = unified(&old, &new, "old", "new");
+
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert_eq!(apply(&old, &diff), new);
+    }
+
+    #[test]
+    fn edit_script_is_minimal() {
+        let diff = unified("a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n", "old", "new");
+
+        let changed = diff
+            .lines()
+            .skip(2)
+            .filter(|x| x.starts_with('-') || x.starts_with('+'))
+            .count();
+
+        // the example of Myers' paper has an edit distance of 5
+        assert_eq!(changed, 5);
+        assert_eq!(apply("a\nb\nc\na\nb\nb\na\n", &diff), "c\nb\na\nb\na\nc");
+    }
+
+    #[test]
+    fn generated_texts_round_trip() {
+        // a small linear congruential generator, so the texts are the same on every run
+        let mut seed: u32 = 12345;
+        let mut next = move |range: u32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % range
+        };
+
+        for _ in 0..200 {
+            let old: Vec<String> = (0..next(30)).map(|_| next(5).to_string()).collect();
+            let mut new = old.clone();
+
+            for _ in 0..next(6) {
+                let i = next(new.len() as u32 + 1) as usize;
+
+                match next(3) {
+                    0 if i < new.len() => {
+                        new.remove(i);
+                    }
+                    1 if i < new.len() => new[i] = next(5).to_string(),
+                    _ => new.insert(i, next(5).to_string()),
+                }
+            }
+
+            let (old, new) = (lines(old), lines(new));
+            let diff = unified(&old, &new, "old", "new");
+
+            assert_eq!(
+                apply(&old, &diff),
+                new,
+                "old:\n{old}\nnew:\n{new}\ndiff:\n{diff}"
+            );
+        }
+    }
+
+    #[test]
+    fn large_differences_are_replaced_entirely() {
+        let old = lines((0..MAX_EDIT_DISTANCE).map(|x| format!("old {x}")));
+        let new = lines((0..MAX_EDIT_DISTANCE).map(|x| format!("new {x}")));
+
+        let diff = unified(&old, &new, "old", "new");
+
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert_eq!(apply(&old, &diff), new);
+    }
+}
@@ -0,0 +1,323 @@
+use jonitor_client::frame::{self, Frame};
+use jonitor_client::{Client, Event, SensorInfo, StreamOptions};
+use tokio::runtime::Builder;
+
+use crate::cli::DumpArgs;
+use crate::hwinfo::{self, ReadError};
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Format {
+    Table,
+    Json,
+    Csv,
+    Prometheus,
+}
+
+#[derive(serde::Serialize)]
+struct DumpRow<'a> {
+    sensor_id: u32,
+    sensor_instance: u32,
+    sensor: &'a str,
+    reading_id: u32,
+    reading: &'a str,
+    #[serde(rename = "type")]
+    reading_type: &'static str,
+    unit: &'a str,
+    value: f64,
+    maximum: f64,
+}
+
+/// Prints the readings and returns the exit code
+pub(crate) fn run(args: &DumpArgs) -> i32 {
+    match dump(args) {
+        Ok(output) => {
+            print!("{output}");
+
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+
+            1
+        }
+    }
+}
+
+/// Reads the sensor information and values once and formats them
+fn dump(options: &DumpArgs) -> Result<String, String> {
+    let (sensor_info, frame) = match &options.url {
+        Some(url) => read_remote(url, options.token.as_deref())?,
+        None => read_hwinfo()?,
+    };
+
+    let rows = filter_rows(&sensor_info, &frame, options);
+
+    Ok(match options.format {
+        Format::Table => format_table(&rows),
+        Format::Json => serde_json::to_string_pretty(&rows)
+            .map(|x| x + "\n")
+            .map_err(|e| format!("failed to convert to json string: {e}"))?,
+        Format::Csv => format_csv(&rows),
+        Format::Prometheus => format_prometheus(&rows),
+    })
+}
+
+fn read_hwinfo() -> Result<(SensorInfo, Frame), String> {
+    let mut hwinfo = hwinfo::SharedMemory::init().map_err(read_error_message)?;
+    let (data, json) = hwinfo.read().map_err(read_error_message)?;
+
+    let json = json.ok_or_else(|| String::from("failed to get sensors from HWiNFO"))?;
+
+    let sensor_info = serde_json::from_str::<SensorInfo>(&json)
+        .map_err(|e| format!("failed to parse sensors: {e}"))?;
+    let frame = frame::decode_full(&data).map_err(|e| e.to_string())?;
+
+    Ok((sensor_info, frame))
+}
+
+fn read_remote(url: &str, token: Option<&str>) -> Result<(SensorInfo, Frame), String> {
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("failed to create a Tokio runtime: {e}"))?;
+
+    runtime.block_on(async {
+        let mut client = Client::new(url);
+        if let Some(token) = token {
+            client = client.with_token(token);
+        }
+
+        let mut stream = client.data_stream(StreamOptions {
+            reconnect: false,
+            ..StreamOptions::default()
+        });
+
+        let mut sensor_info = None;
+
+        while let Some(event) = stream.next().await {
+            match event {
+                Event::Sensors(x) => sensor_info = Some(x),
+                Event::Data(frame) => {
+                    if let Some(sensor_info) = sensor_info {
+                        return Ok((sensor_info, frame));
+                    }
+                }
+                Event::Disconnected(e) => return Err(e.to_string()),
+            }
+        }
+
+        Err(String::from("connection closed"))
+    })
+}
+
+fn read_error_message(e: ReadError) -> String {
+    match e {
+        ReadError::Os(err) | ReadError::Other(err) => err,
+        ReadError::HwinfoClosed => {
+            String::from("failed to read data from HWiNFO. HWiNFO is closed")
+        }
+    }
+}
+
+fn filter_rows<'a>(
+    sensor_info: &'a SensorInfo,
+    frame: &Frame,
+    options: &DumpArgs,
+) -> Vec<DumpRow<'a>> {
+    let sensor_text = options.sensor.as_ref().map(|x| x.to_lowercase());
+    let reading_text = options.reading.as_ref().map(|x| x.to_lowercase());
+
+    let mut rows = Vec::with_capacity(frame.values.len() / 2);
+
+    for sensor in sensor_info.sensors.iter() {
+        if let Some(text) = &sensor_text {
+            if !sensor.name.to_lowercase().contains(text) {
+                continue;
+            }
+        }
+
+        for (i, reading) in sensor.readings.iter().enumerate() {
+            if let Some(text) = &reading_text {
+                if !reading.name.to_lowercase().contains(text) {
+                    continue;
+                }
+            }
+
+            if !options.types.is_empty() && !options.types.contains(&reading.reading_type) {
+                continue;
+            }
+
+            let Some(value) = frame.reading(sensor, i) else {
+                continue;
+            };
+
+            rows.push(DumpRow {
+                sensor_id: sensor.id,
+                sensor_instance: sensor.instance,
+                sensor: &sensor.name,
+                reading_id: reading.id,
+                reading: &reading.name,
+                reading_type: reading.reading_type.as_str(),
+                unit: &reading.unit,
+                value: value.current,
+                maximum: value.maximum,
+            });
+        }
+    }
+
+    rows
+}
+
+fn format_table(rows: &[DumpRow]) -> String {
+    let header = ["Sensor", "Reading", "Value", "Maximum", "Unit"];
+
+    let cells = rows
+        .iter()
+        .map(|row| {
+            [
+                row.sensor.to_owned(),
+                row.reading.to_owned(),
+                format!("{:.3}", row.value),
+                format!("{:.3}", row.maximum),
+                row.unit.to_owned(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = header.map(|x| x.chars().count());
+    for row in cells.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::with_capacity((widths.iter().sum::<usize>() + 10) * (rows.len() + 2));
+
+    let mut push_line = |line: &[String]| {
+        for (i, (cell, width)) in line.iter().zip(widths.iter()).enumerate() {
+            // right-align the numbers
+            if i == 2 || i == 3 {
+                output.push_str(&format!("{cell:>width$}  "));
+            } else {
+                output.push_str(&format!("{cell:<width$}  "));
+            }
+        }
+
+        output.truncate(output.trim_end().len());
+        output.push('\n');
+    };
+
+    push_line(&header.map(String::from));
+    push_line(&widths.map(|x| "-".repeat(x)));
+
+    for row in cells.iter() {
+        push_line(row);
+    }
+
+    output
+}
+
+fn format_csv(rows: &[DumpRow]) -> String {
+    fn escape(str: &str) -> String {
+        if str.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", str.replace('"', "\"\""))
+        } else {
+            str.to_owned()
+        }
+    }
+
+    let mut output = String::from(
+        "sensor_id,sensor_instance,sensor,reading_id,reading,type,unit,value,maximum\n",
+    );
+
+    for row in rows {
+        output.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            row.sensor_id,
+            row.sensor_instance,
+            escape(row.sensor),
+            row.reading_id,
+            escape(row.reading),
+            row.reading_type,
+            escape(row.unit),
+            row.value,
+            row.maximum
+        ));
+    }
+
+    output
+}
+
+fn format_prometheus(rows: &[DumpRow]) -> String {
+    fn escape(str: &str) -> String {
+        str.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    let labels = rows
+        .iter()
+        .map(|row| {
+            format!(
+                r#"sensor_id="{}",sensor_instance="{}",sensor="{}",reading_id="{}",reading="{}",type="{}",unit="{}""#,
+                row.sensor_id,
+                row.sensor_instance,
+                escape(row.sensor),
+                row.reading_id,
+                escape(row.reading),
+                row.reading_type,
+                escape(row.unit)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut output = String::with_capacity(rows.len() * 400);
+
+    output.push_str("# HELP jonitor_reading_value Current value of a HWiNFO reading\n");
+    output.push_str("# TYPE jonitor_reading_value gauge\n");
+    for (row, labels) in rows.iter().zip(labels.iter()) {
+        output.push_str(&format!(
+            "jonitor_reading_value{{{labels}}} {}\n",
+            prometheus_value(row.value)
+        ));
+    }
+
+    output.push_str("# HELP jonitor_reading_maximum Highest value of a HWiNFO reading\n");
+    output.push_str("# TYPE jonitor_reading_maximum gauge\n");
+    for (row, labels) in rows.iter().zip(labels.iter()) {
+        output.push_str(&format!(
+            "jonitor_reading_maximum{{{labels}}} {}\n",
+            prometheus_value(row.maximum)
+        ));
+    }
+
+    output
+}
+
+/// Formats a sample value, with the spellings of infinity and NaN used by the Prometheus text
+/// format instead of `inf` and `NaN` of `Display`
+fn prometheus_value(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value == f64::INFINITY {
+        String::from("+Inf")
+    } else if value == f64::NEG_INFINITY {
+        String::from("-Inf")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prometheus_non_finite_values() {
+        assert_eq!(prometheus_value(f64::NAN), "NaN");
+        assert_eq!(prometheus_value(f64::INFINITY), "+Inf");
+        assert_eq!(prometheus_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(prometheus_value(42.5), "42.5");
+        assert_eq!(prometheus_value(-1.0), "-1");
+    }
+}
@@ -0,0 +1,290 @@
+use jonitor_client::{ReadingData, ReadingType, SensorData, SensorInfo};
+use serde_json::{json, Value};
+
+/// Colors of the datasets in each chart
+const COLORS: [&str; 8] = [
+    "#36a2eb", "#ff6384", "#4bc0c0", "#ff9f40", "#9966ff", "#ffcd56", "#c9cbcf", "#2ecc71",
+];
+
+/// Line charts show at most this many readings, tables twice as many
+const MAX_DATASETS: usize = 8;
+
+/// Each device has at most this many gauges
+const MAX_GAUGES: usize = 4;
+
+/// Readings of the sensors of a device, e.g. every sensor of `CPU [#0]`
+struct Device<'a> {
+    title: String,
+    // CPUs first, then GPUs, the motherboard and drives
+    order: u8,
+    key: String,
+    sensors: Vec<&'a SensorData>,
+}
+
+/// Returns a dashboard with a group for each device found in `sensor_info`. Usage is shown by
+/// gauges, temperatures and clocks by line charts, and voltages and fans by tables.
+pub(crate) fn generate(sensor_info: &SensorInfo) -> String {
+    let mut devices: Vec<Device> = Vec::new();
+
+    for sensor in sensor_info.sensors.iter() {
+        let (order, key, title) = device_of(&sensor.name);
+
+        match devices.iter_mut().find(|x| x.key == key) {
+            Some(device) => device.sensors.push(sensor),
+            None => devices.push(Device {
+                title,
+                order,
+                key,
+                sensors: vec![sensor],
+            }),
+        }
+    }
+
+    // the order of devices of the same kind is kept
+    devices.sort_by_key(|x| x.order);
+
+    let groups: Vec<Value> = devices.iter().filter_map(group).collect();
+
+    Value::Array(groups).to_string()
+}
+
+/// Returns the order, key and title of the device of a sensor. HWiNFO names the sensors of CPUs
+/// and GPUs like `CPU [#0]: AMD Ryzen 7 5800X: Enhanced`.
+fn device_of(sensor_name: &str) -> (u8, String, String) {
+    let mut parts = sensor_name.split(':').map(str::trim);
+    let first = parts.next().unwrap_or_default();
+
+    let order = if first.starts_with("CPU") {
+        0
+    } else if first.starts_with("GPU") {
+        1
+    } else if first.starts_with("S.M.A.R.T.") || first.starts_with("Drive") {
+        return (3, String::from("drives"), String::from("Drives"));
+    } else {
+        return (2, String::from("motherboard"), String::from("Motherboard"));
+    };
+
+    let title = match parts.next() {
+        Some(model) if !model.is_empty() => format!("{first}: {model}"),
+        _ => first.to_owned(),
+    };
+
+    (order, first.to_owned(), title)
+}
+
+fn group(device: &Device) -> Option<Value> {
+    let readings_of = |reading_type: ReadingType| {
+        device
+            .sensors
+            .iter()
+            .flat_map(move |sensor| {
+                sensor
+                    .readings
+                    .iter()
+                    // the unit of a dataset cannot be empty, and the name is the title of a
+                    // gauge or the label of a dataset
+                    .filter(move |x| {
+                        x.reading_type == reading_type
+                            && !x.unit.trim().is_empty()
+                            && !x.name.trim().is_empty()
+                    })
+                    .map(move |reading| (*sensor, reading))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut charts = Vec::new();
+
+    // gauges need a maximum, which is only known for percentages
+    let mut usage = readings_of(ReadingType::Usage);
+    usage.retain(|(_, reading)| reading.unit == "%");
+    // totals first, e.g. `Total CPU Usage` instead of the usage of each core
+    usage.sort_by_key(|(_, reading)| !reading.name.to_lowercase().contains("total"));
+
+    for (sensor, reading) in usage.into_iter().take(MAX_GAUGES) {
+        charts.push(chart(
+            &reading.name,
+            "gauge",
+            100,
+            200,
+            vec![dataset(sensor, reading, 0)],
+        ));
+    }
+
+    for (kind, reading_type) in [
+        ("temperatures", ReadingType::Temperature),
+        ("clocks", ReadingType::Clock),
+    ] {
+        let datasets: Vec<Value> = readings_of(reading_type)
+            .into_iter()
+            .take(MAX_DATASETS)
+            .enumerate()
+            .map(|(i, (sensor, reading))| dataset(sensor, reading, i))
+            .collect();
+
+        if !datasets.is_empty() {
+            let title = format!("{} {kind}", device.title);
+            charts.push(chart(&title, "line", 0, 300, datasets));
+        }
+    }
+
+    for (kind, reading_type) in [
+        ("voltages", ReadingType::Voltage),
+        ("fans", ReadingType::Fan),
+    ] {
+        let datasets: Vec<Value> = readings_of(reading_type)
+            .into_iter()
+            .take(MAX_DATASETS * 2)
+            .enumerate()
+            .map(|(i, (sensor, reading))| dataset(sensor, reading, i))
+            .collect();
+
+        if !datasets.is_empty() {
+            let title = format!("{} {kind}", device.title);
+            charts.push(chart(&title, "table", 0, 300, datasets));
+        }
+    }
+
+    if charts.is_empty() {
+        return None;
+    }
+
+    Some(json!({
+        "gridBreakpoints": {
+            "smallColumns": 1,
+            "smallWidth": 0,
+            "mediumColumns": 2,
+            "mediumWidth": 0,
+            "largeColumns": 2,
+            "largeWidth": 0,
+            "extraLargeColumns": 3,
+            "extraLargeWidth": 0
+        },
+        "chartConfigs": charts
+    }))
+}
+
+fn chart(
+    title: &str,
+    chart_type: &str,
+    maximum_value: u32,
+    height: u16,
+    datasets: Vec<Value>,
+) -> Value {
+    json!({
+        "title": title,
+        "chartType": chart_type,
+        // only used by line charts
+        "dataCount": if chart_type == "line" { 60 } else { 0 },
+        // only used by gauges
+        "maximumValue": maximum_value,
+        "height": height,
+        "animationDuration": 200,
+        "showLegend": datasets.len() > 1,
+        "showLabels": true,
+        "autoColors": false,
+        "datasets": datasets
+    })
+}
+
+fn dataset(sensor: &SensorData, reading: &ReadingData, index: usize) -> Value {
+    json!({
+        "sensorId": sensor.id,
+        "sensorInstance": sensor.instance,
+        "readingId": reading.id,
+        "label": reading.name,
+        "unit": reading.unit,
+        "color": COLORS[index % COLORS.len()]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard;
+
+    fn sensor_info() -> SensorInfo {
+        let reading = |id: u32, name: &str, unit: &str, reading_type: &str| json!({ "id": id, "name": name, "unit": unit, "type": reading_type });
+        let sensor = |id: u32, name: &str, readings: Vec<Value>| json!({ "id": id, "instance": 0, "name": name, "offset": 0, "readings": readings });
+
+        serde_json::from_value(json!({
+            "updatedCount": 1,
+            "sensors": [
+                sensor(1, "S.M.A.R.T.: Samsung SSD 980", vec![
+                    reading(1, "Drive Temperature", "°C", "temperature")
+                ]),
+                sensor(2, "CPU [#0]: AMD Ryzen 7 5800X", vec![
+                    reading(1, "Core 0 Usage", "%", "usage"),
+                    reading(2, "Total CPU Usage", "%", "usage"),
+                    // readings without a name are skipped
+                    reading(3, " ", "%", "usage"),
+                    reading(4, "CPU (Tctl/Tdie)", "°C", "temperature"),
+                    reading(5, "Core 0 Clock", "MHz", "clock")
+                ]),
+                sensor(3, "Nuvoton NCT6798D", vec![
+                    reading(1, "Vcore", "V", "voltage"),
+                    reading(2, "", "V", "voltage"),
+                    reading(3, "CPU", "RPM", "fan")
+                ]),
+                sensor(4, "GPU [#0]: NVIDIA GeForce RTX 3080", vec![
+                    reading(1, "GPU Temperature", "°C", "temperature")
+                ]),
+                // no reading can be shown, so the GPU has no group
+                sensor(5, "GPU [#1]: AMD Radeon", vec![
+                    reading(1, "GPU Errors", "", "other")
+                ])
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn generated_dashboard_is_valid() {
+        let content = generate(&sensor_info());
+
+        let Ok(config) = dashboard::parse(&content) else {
+            panic!("the generated dashboard is invalid: {content}");
+        };
+        assert_eq!(config.groups.len(), 4);
+
+        let groups: Vec<Value> = serde_json::from_str(&content).unwrap();
+        let charts: Vec<Vec<(&str, &str)>> = groups
+            .iter()
+            .map(|group| {
+                group["chartConfigs"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|x| {
+                        (
+                            x["chartType"].as_str().unwrap(),
+                            x["title"].as_str().unwrap(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            charts,
+            [
+                vec![
+                    ("gauge", "Total CPU Usage"),
+                    ("gauge", "Core 0 Usage"),
+                    ("line", "CPU [#0]: AMD Ryzen 7 5800X temperatures"),
+                    ("line", "CPU [#0]: AMD Ryzen 7 5800X clocks")
+                ],
+                vec![("line", "GPU [#0]: NVIDIA GeForce RTX 3080 temperatures")],
+                vec![
+                    ("table", "Motherboard voltages"),
+                    ("table", "Motherboard fans")
+                ],
+                vec![("line", "Drives temperatures")]
+            ]
+        );
+
+        let voltages = groups[2]["chartConfigs"][0]["datasets"].as_array().unwrap();
+        assert_eq!(voltages.len(), 1);
+        assert_eq!(voltages[0]["label"], "Vcore");
+    }
+}
@@ -1,6 +1,7 @@
 use std::ffi::c_void;
 
 use byteorder::ByteOrder;
+use jonitor_client::{ReadingData, ReadingType, SensorData, SensorInfo};
 use windows_sys::{
     w,
     Win32::{
@@ -27,6 +28,7 @@ pub(crate) struct SharedMemory {
     reading_ids: Vec<u32>,
     reading_names: Vec<[u8; 128]>,
     reading_units: Vec<[u8; 16]>,
+    reading_types: Vec<i32>,
     // current value and highest value of all readings in sequence
     reading_values: Vec<f64>,
     // incremented when sensor information is updated
@@ -67,6 +69,7 @@ impl SharedMemory {
             reading_ids: Vec::with_capacity(data.reading_count as usize),
             reading_names: Vec::with_capacity(data.reading_count as usize),
             reading_units: Vec::with_capacity(data.reading_count as usize),
+            reading_types: Vec::with_capacity(data.reading_count as usize),
             reading_values: Vec::with_capacity(2 + data.reading_count as usize * 2),
             sensor_info_updated_count: 0,
         })
@@ -109,12 +112,14 @@ impl SharedMemory {
     ///                 {
     ///                     "id": 4560,
     ///                     "name": "Reading 1",
-    ///                     "unit": "%"
+    ///                     "unit": "%",
+    ///                     "type": "usage"
     ///                 },
     ///                 {
     ///                     "id": 4561,
     ///                     "name": "Reading 2",
-    ///                     "unit": "V"
+    ///                     "unit": "V",
+    ///                     "type": "voltage"
     ///                 }
     ///             ],
     ///             "offset": 2
@@ -127,7 +132,8 @@ impl SharedMemory {
     ///                 {
     ///                     "id": 7890,
     ///                     "name": "Reading 3",
-    ///                     "unit": "A"
+    ///                     "unit": "A",
+    ///                     "type": "current"
     ///                 }
     ///             ],
     ///             "offset": 6
@@ -171,6 +177,7 @@ impl SharedMemory {
             self.reading_ids.resize(reading_count, 0);
             self.reading_names.resize(reading_count, [0; 128]);
             self.reading_units.resize(reading_count, [0; 16]);
+            self.reading_types.resize(reading_count, 0);
         }
 
         for i in 0..reading_count {
@@ -222,6 +229,7 @@ impl SharedMemory {
                 reading
                     .unit
                     .clone_into(self.reading_units.get_mut(i).unwrap());
+                *self.reading_types.get_mut(i).unwrap() = reading.reading_type;
             } else if *reading_id != reading.id {
                 sensor_info_updated = true;
 
@@ -232,6 +240,7 @@ impl SharedMemory {
                 reading
                     .unit
                     .clone_into(self.reading_units.get_mut(i).unwrap());
+                *self.reading_types.get_mut(i).unwrap() = reading.reading_type;
             }
 
             self.reading_values.push(reading.value);
@@ -293,6 +302,7 @@ impl SharedMemory {
                         id: self.reading_ids[j],
                         name: reading_name,
                         unit,
+                        reading_type: ReadingType::from_hwinfo(self.reading_types[j]),
                     });
                 }
 
@@ -426,26 +436,3 @@ pub(crate) enum ReadError {
     Other(String),
     HwinfoClosed,
 }
-
-#[derive(serde::Serialize)]
-struct SensorInfo {
-    #[serde(rename = "updatedCount")]
-    updated_count: u64,
-    sensors: Vec<SensorData>,
-}
-
-#[derive(serde::Serialize)]
-struct SensorData {
-    id: u32,
-    instance: u32,
-    name: String,
-    readings: Vec<ReadingData>,
-    offset: u32,
-}
-
-#[derive(serde::Serialize)]
-struct ReadingData {
-    id: u32,
-    name: String,
-    unit: String,
-}
@@ -0,0 +1,519 @@
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use socket2::{Domain, Protocol, SockAddr, SockRef, Socket, Type};
+use tokio::io::{AsyncRead, AsyncWrite, Interest};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, watch};
+use tokio_rustls::TlsAcceptor;
+use tower_service::Service;
+
+use crate::access::{self, Access, ConnectionSlot};
+use crate::auth::Auth;
+use crate::config::ListenerConfig;
+use crate::log;
+use crate::tls::{self, CertificateResolver};
+
+/// How often the certificate files of HTTPS listeners are checked for changes
+const CERTIFICATE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Connections which have not completed the TLS handshake in time are closed
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Address of a listener, parsed from `ListenerConfig::address`
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum ListenAddress {
+    Tcp(SocketAddr),
+    // path of a Unix domain socket, given as `unix:<path>`
+    Unix(PathBuf),
+}
+
+impl ListenAddress {
+    /// Parses `ListenerConfig::address`, e.g. `0.0.0.0:10110`, `[::]:10110` or
+    /// `unix:jonitor.sock`
+    pub(crate) fn parse(address: &str) -> Result<ListenAddress, String> {
+        if let Some(path) = address.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err(format!("Invalid listen address `{address}`: empty path"));
+            }
+
+            return Ok(ListenAddress::Unix(PathBuf::from(path)));
+        }
+
+        address
+            .parse()
+            .map(ListenAddress::Tcp)
+            .map_err(|e| format!("Invalid listen address `{address}`: {e}"))
+    }
+}
+
+/// Address of a connected client, available to handlers as `ConnectInfo<PeerAddr>`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PeerAddr {
+    Tcp(SocketAddr),
+    // clients connected through a Unix domain socket have no address
+    Unix,
+}
+
+impl PeerAddr {
+    /// Returns the IP address of a TCP client. IPv4 addresses of clients connected to a dual
+    /// stack listener are returned as IPv4 addresses.
+    pub(crate) fn ip(&self) -> Option<IpAddr> {
+        match self {
+            PeerAddr::Tcp(addr) => Some(addr.ip().to_canonical()),
+            PeerAddr::Unix => None,
+        }
+    }
+}
+
+impl fmt::Debug for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerAddr::Tcp(addr) => write!(f, "{addr:?}"),
+            PeerAddr::Unix => write!(f, "unix socket client"),
+        }
+    }
+}
+
+/// Available to handlers as an extension when the connection is encrypted by TLS
+#[derive(Clone, Copy)]
+pub(crate) struct TlsConnection;
+
+enum SocketListener {
+    Tcp(TcpListener),
+    // tokio does not support Unix domain sockets on Windows, so the listening socket is
+    // registered as a stream, which is readable when a connection can be accepted
+    Unix(TcpStream, PathBuf),
+}
+
+pub(crate) struct Listener {
+    socket: SocketListener,
+    // the certificate resolver is checked periodically for changed certificate files
+    tls: Option<(TlsAcceptor, Arc<CertificateResolver>)>,
+    redirect_https_port: Option<u16>,
+}
+
+/// A listener which is checked but not bound yet
+pub(crate) struct PreparedListener {
+    address: ListenAddress,
+    dual_stack: bool,
+    tls: Option<(TlsAcceptor, Arc<CertificateResolver>)>,
+    redirect_https_port: Option<u16>,
+}
+
+impl Listener {
+    /// Binds to the address of `config`. Relative paths of the TLS certificate and the Unix
+    /// domain socket are relative to `base_dir`.
+    pub(crate) fn bind(config: &ListenerConfig, base_dir: &Path) -> Result<Listener, String> {
+        Listener::prepare(config, base_dir)?.bind()
+    }
+
+    /// Parses the address and loads the TLS certificate of `config` without binding to the
+    /// address, which may still be used by the previous listener
+    pub(crate) fn prepare(
+        config: &ListenerConfig,
+        base_dir: &Path,
+    ) -> Result<PreparedListener, String> {
+        let address = match ListenAddress::parse(&config.address)? {
+            ListenAddress::Unix(path) => ListenAddress::Unix(base_dir.join(path)),
+            x => x,
+        };
+
+        let tls = match &config.tls {
+            Some(x) => Some(tls::acceptor(x, base_dir, &address)?),
+            None => None,
+        };
+
+        Ok(PreparedListener {
+            address,
+            dual_stack: config.dual_stack,
+            tls,
+            redirect_https_port: config.redirect_https_port,
+        })
+    }
+
+    /// Returns the bound address for logging
+    pub(crate) fn address(&self) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+
+        match &self.socket {
+            SocketListener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => format!("{scheme}://{addr}"),
+                Err(_) => String::from("unknown address"),
+            },
+            SocketListener::Unix(_, path) => format!("{scheme}+unix:{}", path.to_string_lossy()),
+        }
+    }
+}
+
+impl PreparedListener {
+    pub(crate) fn bind(self) -> Result<Listener, String> {
+        let socket = match self.address {
+            ListenAddress::Tcp(addr) => bind_tcp(addr, self.dual_stack)
+                .map(SocketListener::Tcp)
+                .map_err(|e| format!("Failed to bind to {:?}: {e}", addr))?,
+            ListenAddress::Unix(path) => bind_unix(&path)
+                .map(|x| SocketListener::Unix(x, path.clone()))
+                .map_err(|e| format!("Failed to bind to {}: {e}", path.to_string_lossy()))?,
+        };
+
+        Ok(Listener {
+            socket,
+            tls: self.tls,
+            redirect_https_port: self.redirect_https_port,
+        })
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        // the socket file is not removed when the socket is closed
+        if let SocketListener::Unix(_, path) = &self.socket {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn bind_tcp(addr: SocketAddr, dual_stack: bool) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+
+    if addr.is_ipv6() {
+        // IPv6 sockets only accept IPv6 connections by default on Windows
+        socket.set_only_v6(!dual_stack)?;
+    }
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    TcpListener::from_std(socket.into())
+}
+
+/// Binds a Unix domain socket, which is supported since Windows 10 version 1803
+fn bind_unix(path: &Path) -> std::io::Result<TcpStream> {
+    // remove the socket file left by a previous run, which is a reparse point that cannot be
+    // followed by `Path::exists()` on Windows
+    if std::fs::symlink_metadata(path).is_ok() {
+        std::fs::remove_file(path)?;
+    }
+
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&SockAddr::unix(path)?)?;
+    socket.listen(1024)?;
+
+    TcpStream::from_std(socket.into())
+}
+
+/// Accepts connections and serves them with `router` until `stop_rx` receives a value or is
+/// dropped. `auth` is available to the middleware of every request as an extension.
+/// `unbound_tx` is notified when the address is no longer bound, then the pending requests are
+/// completed before returning.
+pub(crate) async fn run(
+    listener: Listener,
+    router: axum::Router,
+    auth: Arc<Auth>,
+    access: Arc<Access>,
+    mut stop_rx: oneshot::Receiver<()>,
+    unbound_tx: oneshot::Sender<()>,
+) {
+    // the IP access rules also apply to the redirects
+    let router = match listener.redirect_https_port {
+        Some(port) => tls::redirect_router(port).layer(axum::middleware::from_fn_with_state(
+            access.clone(),
+            access::middleware,
+        )),
+        None => router,
+    };
+
+    // notifies the connections to shut down
+    let (signal_tx, signal_rx) = watch::channel(());
+    // closed when every connection has finished
+    let (close_tx, close_rx) = watch::channel(());
+
+    let connection_state = ConnectionState {
+        signal_rx,
+        _close_rx: close_rx,
+        auth,
+        access,
+    };
+
+    let mut certificate_check = tokio::time::interval(CERTIFICATE_CHECK_INTERVAL);
+
+    loop {
+        let accepted = tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = certificate_check.tick(), if listener.tls.is_some() => {
+                if let Some((_, resolver)) = &listener.tls {
+                    resolver.reload_if_changed();
+                }
+
+                continue;
+            }
+            result = accept(&listener, &router, &connection_state) => result,
+        };
+
+        if let Err(e) = accepted {
+            // e.g. too many open files, wait for other connections to be closed
+            log(format!("Failed to accept a connection: {e}"));
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    drop(listener);
+    let _ = unbound_tx.send(());
+
+    drop(connection_state);
+
+    let _ = signal_tx.send(());
+    close_tx.closed().await;
+}
+
+/// Held by every connection of a listener
+#[derive(Clone)]
+struct ConnectionState {
+    signal_rx: watch::Receiver<()>,
+    // only held, the sender is closed when every clone is dropped
+    _close_rx: watch::Receiver<()>,
+    auth: Arc<Auth>,
+    access: Arc<Access>,
+}
+
+async fn accept(
+    listener: &Listener,
+    router: &axum::Router,
+    connection_state: &ConnectionState,
+) -> std::io::Result<()> {
+    let tls = listener.tls.as_ref().map(|(acceptor, _)| acceptor.clone());
+
+    match &listener.socket {
+        SocketListener::Tcp(socket) => {
+            let (stream, addr) = socket.accept().await?;
+            let peer_addr = PeerAddr::Tcp(addr);
+
+            // IPv4 clients of a dual stack listener are counted by their IPv4 address
+            let slot = match connection_state
+                .access
+                .open_connection(addr.ip().to_canonical())
+            {
+                Some(x) => x,
+                None => {
+                    log(format!(
+                        "{:?} is rejected, the maximum number of connections per IP address is reached",
+                        peer_addr
+                    ));
+
+                    return Ok(());
+                }
+            };
+
+            let _ = stream.set_nodelay(true);
+
+            spawn_connection(
+                stream,
+                peer_addr,
+                Some(Arc::new(slot)),
+                tls,
+                router.clone(),
+                connection_state.clone(),
+            );
+        }
+        SocketListener::Unix(socket, _) => {
+            let stream = socket
+                .async_io(Interest::READABLE, || SockRef::from(socket).accept())
+                .await?
+                .0;
+
+            // the stream is only read and written, which works the same for every socket type
+            stream.set_nonblocking(true)?;
+            let stream = TcpStream::from_std(stream.into())?;
+
+            spawn_connection(
+                stream,
+                PeerAddr::Unix,
+                None,
+                tls,
+                router.clone(),
+                connection_state.clone(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn spawn_connection<S>(
+    stream: S,
+    peer_addr: PeerAddr,
+    // clients of a Unix domain socket are not counted, they are connected through a local proxy
+    slot: Option<Arc<ConnectionSlot>>,
+    tls: Option<TlsAcceptor>,
+    router: axum::Router,
+    connection_state: ConnectionState,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        match tls {
+            Some(acceptor) => {
+                // failed handshakes are caused by clients, e.g. not trusting a self-signed
+                // certificate
+                if let Ok(Ok(stream)) =
+                    tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                {
+                    serve_connection(stream, peer_addr, slot, true, router, connection_state).await;
+                }
+            }
+            None => {
+                serve_connection(stream, peer_addr, slot, false, router, connection_state).await
+            }
+        }
+    });
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    peer_addr: PeerAddr,
+    slot: Option<Arc<ConnectionSlot>>,
+    is_tls: bool,
+    router: axum::Router,
+    mut connection_state: ConnectionState,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let auth = connection_state.auth.clone();
+
+    let service = hyper::service::service_fn(move |mut request: hyper::Request<Incoming>| {
+        request.extensions_mut().insert(ConnectInfo(peer_addr));
+        request.extensions_mut().insert(auth.clone());
+
+        if is_tls {
+            request.extensions_mut().insert(TlsConnection);
+        }
+
+        // a WebSocket handler keeps a clone, so the connection is still counted after upgrading
+        if let Some(slot) = &slot {
+            request.extensions_mut().insert(slot.clone());
+        }
+
+        router.clone().call(request)
+    });
+
+    let connection = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades();
+    tokio::pin!(connection);
+
+    // errors are caused by clients, e.g. disconnecting in the middle of a request
+    tokio::select! {
+        _ = connection.as_mut() => {}
+        _ = connection_state.signal_rx.changed() => {
+            // upgraded websocket connections are not affected
+            connection.as_mut().graceful_shutdown();
+            let _ = connection.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+
+    #[test]
+    fn parse_addresses() {
+        assert!(
+            ListenAddress::parse("127.0.0.1:10110")
+                == Ok(ListenAddress::Tcp("127.0.0.1:10110".parse().unwrap()))
+        );
+        assert!(
+            ListenAddress::parse("[::]:10110")
+                == Ok(ListenAddress::Tcp("[::]:10110".parse().unwrap()))
+        );
+        assert!(
+            ListenAddress::parse("unix:jonitor.sock")
+                == Ok(ListenAddress::Unix(PathBuf::from("jonitor.sock")))
+        );
+
+        assert!(ListenAddress::parse("unix:").is_err());
+        assert!(ListenAddress::parse("localhost:10110").is_err());
+        assert!(ListenAddress::parse("::1:10110").is_err());
+    }
+
+    #[test]
+    fn serve_unix_domain_socket() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // relative to the base folder
+        let config = ListenerConfig {
+            address: format!("unix:jonitor-test-{}.sock", std::process::id()),
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(&config.address["unix:".len()..]);
+
+        runtime.block_on(async {
+            let listener = Listener::bind(&config, &std::env::temp_dir()).unwrap();
+            assert!(listener.address().starts_with("http+unix:"));
+
+            let router = axum::Router::new().route(
+                "/",
+                axum::routing::get(|ConnectInfo(addr): ConnectInfo<PeerAddr>| async move {
+                    format!("{addr:?}")
+                }),
+            );
+
+            let (stop_tx, stop_rx) = oneshot::channel();
+            let (unbound_tx, unbound_rx) = oneshot::channel();
+
+            let handle = tokio::spawn(run(
+                listener,
+                router,
+                Arc::new(Auth::new(None, "")),
+                Access::new(None),
+                stop_rx,
+                unbound_tx,
+            ));
+
+            let client_path = path.clone();
+            let response = tokio::task::spawn_blocking(move || {
+                let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+                socket
+                    .connect(&SockAddr::unix(client_path).unwrap())
+                    .unwrap();
+
+                let mut stream = std::net::TcpStream::from(socket);
+                stream
+                    .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+
+                response
+            })
+            .await
+            .unwrap();
+
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(response.ends_with("unix socket client"));
+
+            let _ = stop_tx.send(());
+            unbound_rx.await.unwrap();
+            handle.await.unwrap();
+        });
+
+        // the socket file is removed when the listener stops
+        assert!(std::fs::symlink_metadata(&path).is_err());
+    }
+}
@@ -1,36 +1,94 @@
 use std::borrow::Cow;
-use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::mem::MaybeUninit;
-use std::net::SocketAddr;
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::TrySendError;
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use access::{Access, ClientAddr, ConnectionSlot};
+use auth::{Auth, Identity, Role};
 use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
-use axum::extract::{ConnectInfo, State};
+use axum::extract::{ConnectInfo, FromRef, Query, State};
 use axum::handler::HandlerWithoutStateExt;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::routing::get;
+use axum::Extension;
+use clap::Parser;
+use cli::{Cli, Command, ConfigArgs, RecordArgs, ReplayArgs};
+use config::{Config, ListenerConfig};
+use delta::DeltaEncoder;
 use futures_util::{SinkExt, StreamExt};
 use hwinfo::ReadError;
-use serde::{Deserialize, Deserializer, Serialize};
-use tokio::net::TcpListener;
+use jonitor_client::frame::{Encoding, Precision};
+use listener::{Listener, PeerAddr};
+use recording::{Player, Recorder};
+use source::Source;
+use store::DashboardStore;
 use tokio::runtime::Builder;
 use tokio::signal;
 use tokio::sync::RwLock;
 use tower_http::services::ServeDir;
+use web::WebFiles;
 use windows_sys::Win32::{
     Foundation::SYSTEMTIME,
     System::{Console::SetConsoleTitleW, SystemInformation::GetLocalTime},
 };
 
+mod access;
+mod api;
+mod auth;
+mod bundle;
+mod cli;
+mod config;
+mod dashboard;
+mod delta;
+mod diff;
+mod dump;
+mod generate;
 mod hwinfo;
+mod listener;
+mod recording;
+mod schema;
+mod selector;
+mod source;
+mod store;
+mod template;
+mod tls;
+mod web;
+
+// set by --close-on-error
+static CLOSE_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Folder in data_dir with the dashboards, which was in public_dir before
+const CONFIGS_DIR: &str = "configs";
+
+/// Folder in data_dir with the recordings given by a file name only
+const RECORDINGS_DIR: &str = "recordings";
 
 fn main() {
+    let cli = Cli::parse();
+
+    CLOSE_ON_ERROR.store(cli.close_on_error, Ordering::Relaxed);
+
+    match cli.command {
+        Some(Command::Serve(args)) => serve_hwinfo(&args),
+        Some(Command::Dump(args)) => std::process::exit(dump::run(&args)),
+        Some(Command::ValidateConfig(args)) => std::process::exit(validate_config(&args)),
+        Some(Command::Record(args)) => record(&args),
+        Some(Command::Replay(args)) => replay(&args),
+        Some(Command::HashPassword) => std::process::exit(auth::hash_password()),
+        Some(Command::GenerateToken(args)) => {
+            std::process::exit(auth::generate_token(&args.name, args.role))
+        }
+        None => serve_hwinfo(&cli.serve),
+    }
+}
+
+fn set_title() {
     let title = concat!("Jonitor v", env!("CARGO_PKG_VERSION"));
     log(title);
 
@@ -39,8 +97,21 @@ fn main() {
     if unsafe { SetConsoleTitleW(w_title.as_ptr()) } == 0 {
         log("Failed to set console title");
     }
+}
 
-    let mut hwinfo = match hwinfo::SharedMemory::init() {
+/// Returns the folder of jonitor.exe
+fn exe_dir() -> Result<PathBuf, String> {
+    let mut exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to get the current executable path: {e}"))?;
+    exe_path.pop();
+
+    Ok(exe_path)
+}
+
+fn serve_hwinfo(args: &ConfigArgs) {
+    set_title();
+
+    let hwinfo = match hwinfo::SharedMemory::init() {
         Ok(x) => x,
         Err(e) => {
             if let ReadError::Os(err) = e {
@@ -52,159 +123,309 @@ fn main() {
         }
     };
 
-    // store the latest values read from HWiNFO so they are always available when requested
-    let latest_sensors = Arc::new(RwLock::new(String::with_capacity(0)));
-    let latest_sensors_clone = latest_sensors.clone();
+    serve(Source::Hwinfo(hwinfo), args);
+}
 
-    let latest_data = Arc::new(RwLock::new(Vec::with_capacity(0)));
-    let latest_data_clone = latest_data.clone();
+fn replay(args: &ReplayArgs) {
+    set_title();
 
-    match hwinfo.read() {
-        Ok((data, json)) => {
-            match json {
-                Some(x) => *latest_sensors.blocking_write() = x,
-                None => {
-                    log("Failed to get sensors from HWiNFO");
-                    wait_for_enter();
+    let mut input = args.input.clone();
 
-                    return;
-                }
-            }
+    // a recording which is not found is looked for in the recordings folder
+    if !input.exists() {
+        if let Ok(dir) = exe_dir() {
+            let mut config = config::load(&config::config_path(&dir, &args.config))
+                .map(|x| x.config)
+                .unwrap_or_default();
+            config.apply_overrides(&args.config);
 
-            *latest_data.blocking_write() = data;
+            input = recording_path(&input, &config.data_dir_path(&dir));
         }
-        Err(e) => {
-            match e {
-                ReadError::Os(err) | ReadError::Other(err) => log(format!("Error: {err}")),
-                ReadError::HwinfoClosed => log("Failed to read data from HWiNFO. HWiNFO is closed"),
-            }
+    }
 
+    let player = match Player::open(&input, args.speed, args.is_looping) {
+        Ok(x) => x,
+        Err(e) => {
+            log(e);
             wait_for_enter();
 
             return;
         }
+    };
+
+    log(format!("Replaying {}", input.to_string_lossy()));
+
+    serve(Source::Replay(player), &args.config);
+}
+
+/// Returns the path of a recording file. A file name without a folder is in the recordings
+/// folder of data_dir.
+fn recording_path(path: &Path, data_dir: &Path) -> PathBuf {
+    let mut components = path.components();
+
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => data_dir.join(RECORDINGS_DIR).join(path),
+        _ => path.to_path_buf(),
     }
+}
 
-    let exe_path = match std::env::current_exe() {
+fn record(args: &RecordArgs) {
+    set_title();
+
+    let mut hwinfo = match hwinfo::SharedMemory::init() {
         Ok(x) => x,
         Err(e) => {
-            log(format!("Failed to get the current executable path: {e}"));
+            if let ReadError::Os(err) = e {
+                log(format!("Error: {err}"));
+                wait_for_enter();
+            }
+
+            return;
+        }
+    };
+
+    let (exe_dir, config) = match exe_dir().and_then(|dir| {
+        config::load_or_create(&config::config_path(&dir, &args.config), &args.config)
+            .map(|x| (dir, x))
+    }) {
+        Ok(x) => x,
+        Err(e) => {
+            log(e);
             wait_for_enter();
 
             return;
         }
     };
 
-    let config;
+    let output = recording_path(&args.output, &config.data_dir_path(&exe_dir));
+
+    if let Some(parent) = output.parent() {
+        // the recordings folder is created on the first recording
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let mut recorder = match Recorder::create(&output) {
+        Ok(x) => x,
+        Err(e) => {
+            log(format!("Failed to create the recording file: {e}"));
+            wait_for_enter();
 
-    let mut config_path = exe_path.clone();
-    config_path.pop();
-    config_path.push("config.json");
+            return;
+        }
+    };
 
-    if config_path.exists() && config_path.is_file() {
-        let content = match std::fs::read_to_string(config_path) {
-            Ok(x) => x,
+    log(format!(
+        "Recording to {}. Close Jonitor to stop recording",
+        output.to_string_lossy()
+    ));
+
+    let polling_interval = Duration::from_millis(u64::from(config.polling_interval));
+    let duration = args.duration.map(Duration::from_secs);
+    let start = Instant::now();
+
+    loop {
+        match hwinfo.read() {
+            Ok((data, json)) => {
+                // empty if the reading values have not changed
+                if !data.is_empty() {
+                    if let Err(e) = recorder.write(&data, json.as_deref()) {
+                        log(format!("Failed to write the recording file: {e}"));
+                        wait_for_enter();
+
+                        return;
+                    }
+                }
+            }
             Err(e) => {
-                log(format!("Failed to read the configuration file: {e}",));
+                match e {
+                    ReadError::Os(err) | ReadError::Other(err) => log(format!("Error: {err}")),
+                    ReadError::HwinfoClosed => {
+                        log("Failed to read data from HWiNFO. HWiNFO is closed")
+                    }
+                }
+
                 wait_for_enter();
 
                 return;
             }
-        };
+        }
+
+        if duration.is_some_and(|x| start.elapsed() >= x) {
+            break;
+        }
+
+        thread::sleep(polling_interval);
+    }
+
+    log("Recording finished");
+}
+
+/// Checks the configuration file with the overrides applied, and returns the exit code
+fn validate_config(args: &ConfigArgs) -> i32 {
+    let exe_dir = match exe_dir() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{e}");
 
-        match serde_json::from_str::<Config>(&content) {
-            Ok(x) => config = x,
+            return 1;
+        }
+    };
+
+    let config_path = config::config_path(&exe_dir, args);
+
+    let mut config = if config_path.exists() {
+        match config::load(&config_path) {
+            Ok(x) => {
+                for warning in x.warnings.iter() {
+                    println!("Warning: {warning}");
+                }
+
+                if x.file_version != config::CONFIG_VERSION {
+                    println!(
+                        "The configuration file is version {} and will be migrated to version {} when Jonitor starts",
+                        x.file_version,
+                        config::CONFIG_VERSION
+                    );
+                }
+
+                x.config
+            }
             Err(e) => {
-                log(format!("Failed to parse the configuration file. Delete config.json to re-create the file with default values. Error: {e}"));
-                wait_for_enter();
+                eprintln!("{e}");
 
-                return;
+                return 1;
             }
         }
     } else {
-        let mut file = match File::create(config_path) {
-            Ok(x) => x,
-            Err(e) => {
-                log(format!("Failed to create the configuration file: {e}"));
-                wait_for_enter();
+        println!(
+            "{} does not exist, the default values are used",
+            config_path.to_string_lossy()
+        );
 
-                return;
-            }
-        };
+        Config::default()
+    };
 
-        config = Config {
-            ip: String::from("127.0.0.1"),
-            port: 10110,
-            polling_interval: 2000,
-        };
+    config.apply_overrides(args);
 
-        // indent using 4 spaces
-        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
-        let mut serializer =
-            serde_json::Serializer::with_formatter(Vec::with_capacity(100), formatter);
-        if let Err(e) = config.serialize(&mut serializer) {
-            log(format!(
-                "Failed to serialize configuration file content: {e}"
-            ));
-            wait_for_enter();
+    if let Err(e) = config.validate() {
+        eprintln!("{e}");
 
-            return;
+        return 1;
+    }
+
+    let web_files = web::web_files(&config, &exe_dir);
+    if let WebFiles::Missing(path) = &web_files {
+        println!(
+            "Warning: {} does not exist and the web application is not embedded. Static files will not be served",
+            path.to_string_lossy()
+        );
+    }
+
+    println!("The configuration is valid");
+    for listener in config.listeners.iter() {
+        println!("listener: {listener}");
+    }
+    println!("polling_interval: {}", config.polling_interval);
+    match &web_files {
+        WebFiles::Embedded => println!("public_dir: (embedded)"),
+        WebFiles::Dir(path) | WebFiles::Missing(path) => {
+            println!("public_dir: {}", path.to_string_lossy())
+        }
+    }
+    println!(
+        "data_dir: {}",
+        config.data_dir_path(&exe_dir).to_string_lossy()
+    );
+    println!("base_path: {}/", config.base_path());
+
+    0
+}
+
+fn serve(mut source: Source, args: &ConfigArgs) {
+    // store the latest values read from HWiNFO so they are always available when requested
+    let latest_sensors = Arc::new(RwLock::new(String::with_capacity(0)));
+    let latest_sensors_clone = latest_sensors.clone();
+
+    let latest_data = Arc::new(RwLock::new(Vec::with_capacity(0)));
+    let latest_data_clone = latest_data.clone();
+
+    match source.read() {
+        Ok((data, json)) => {
+            match json {
+                Some(x) => *latest_sensors.blocking_write() = x,
+                None => {
+                    log("Failed to get sensors from HWiNFO");
+                    wait_for_enter();
+
+                    return;
+                }
+            }
+
+            *latest_data.blocking_write() = data;
         }
+        Err(e) => {
+            match e {
+                ReadError::Os(err) | ReadError::Other(err) => log(format!("Error: {err}")),
+                ReadError::HwinfoClosed => log("Failed to read data from HWiNFO. HWiNFO is closed"),
+            }
 
-        if let Err(e) = file.write_all(&serializer.into_inner()) {
-            log(format!("Failed to write the configuration file: {e}"));
             wait_for_enter();
 
             return;
         }
+    }
 
-        if let Err(e) = file.sync_all() {
-            log(format!("Failed to save the configuration file: {e}"));
+    let exe_dir = match exe_dir() {
+        Ok(x) => x,
+        Err(e) => {
+            log(e);
             wait_for_enter();
 
             return;
         }
+    };
 
-        log("Configuration file created");
-    }
+    let config_path = config::config_path(&exe_dir, args);
 
-    let addr: SocketAddr = match format!("{}:{}", config.ip, config.port).parse() {
+    let config = match config::load_or_create(&config_path, args) {
         Ok(x) => x,
         Err(e) => {
-            log(format!(
-                "Invalid IP address `{}:{}`: {e}",
-                config.ip, config.port
-            ));
+            log(e);
             wait_for_enter();
 
             return;
         }
     };
 
-    let polling_interval = u64::from(config.polling_interval);
-    if polling_interval < 1000 {
-        log(format!("Error: the minimum polling interval should be 1000 milliseconds. Current value of polling_interval in config.json is `{polling_interval}`"));
-        wait_for_enter();
-
-        return;
-    }
+    let polling_interval = Arc::new(AtomicU64::new(u64::from(config.polling_interval)));
+    let polling_interval_clone = polling_interval.clone();
 
-    let mut public_dir_path = exe_path;
-    public_dir_path.pop();
-    public_dir_path.push("public");
+    let public_dir_path = config.public_dir_path(&exe_dir);
+    let configs_dir_path = config.data_dir_path(&exe_dir).join(CONFIGS_DIR);
 
-    let mut serve_static_files = true;
+    match store::migrate(&public_dir_path.join(CONFIGS_DIR), &configs_dir_path) {
+        Ok(true) => log(format!(
+            "The dashboards are moved to {}",
+            configs_dir_path.to_string_lossy()
+        )),
+        Ok(false) => {}
+        // not fatal, the dashboards can be moved manually
+        Err(e) => log(e),
+    }
 
-    if !public_dir_path.exists() || !public_dir_path.is_dir() {
-        serve_static_files = false;
+    let web_files = web::web_files(&config, &exe_dir);
+    let base_path = config.base_path().to_owned();
 
-        log("`public` folder does not exist. Static files are not served");
+    if let WebFiles::Missing(path) = &web_files {
+        log(format!(
+            "{} does not exist and the web application is not embedded. Static files are not served",
+            path.to_string_lossy()
+        ));
     }
 
     // notify the hwinfo thread to read data from HWiNFO(true), or stop the hwinfo thread(false)
     let (timer_tx, timer_rx) = std::sync::mpsc::sync_channel::<bool>(1);
     let timer_tx_clone = timer_tx.clone();
-    let timer_tx_clone_2 = timer_tx.clone();
 
     // broadcast websocket messages from hwinfo thread to websocket send tasks
     let (message_tx, _) = tokio::sync::broadcast::channel(10);
@@ -219,7 +440,7 @@ fn main() {
             let is_running = timer_rx.recv().expect("Timer sender disconnected");
 
             if is_running {
-                match hwinfo.read() {
+                match source.read() {
                     Ok((data, json)) => {
                         if data.is_empty() {
                             // the reading values have not changed
@@ -278,7 +499,9 @@ fn main() {
     if let Err(e) = thread::Builder::new()
         .name(String::from("timer"))
         .spawn(move || loop {
-            thread::sleep(Duration::from_millis(polling_interval));
+            thread::sleep(Duration::from_millis(
+                polling_interval.load(Ordering::Relaxed),
+            ));
 
             if let Err(TrySendError::Disconnected(_)) = timer_tx.try_send(true) {
                 break;
@@ -291,7 +514,36 @@ fn main() {
         return;
     }
 
-    let runtime = match Builder::new_current_thread().enable_io().build() {
+    // notify the web server to start and stop listeners when the configuration file changes
+    let (listeners_tx, mut listeners_rx) = tokio::sync::watch::channel(config.listeners.clone());
+    let listener_configs = config.listeners.clone();
+
+    let auth = Arc::new(Auth::new(config.auth.clone(), &base_path));
+    let access = Access::new(config.access.as_ref());
+
+    let settings = Arc::new(config::SettingsFile {
+        path: config_path.clone(),
+        args: args.clone(),
+        polling_interval: polling_interval_clone.clone(),
+    });
+
+    let live_config = config::LiveConfig {
+        polling_interval: polling_interval_clone,
+        listeners_tx,
+        auth: auth.clone(),
+        access: access.clone(),
+    };
+
+    if let Err(e) = config::spawn_watcher(config_path, args.clone(), config, live_config) {
+        // not fatal, changes to the configuration file require a restart instead
+        log(format!("Failed to spawn the config thread: {e}"));
+    }
+
+    let runtime = match Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+    {
         Ok(x) => x,
         Err(e) => {
             log(format!("Failed to create a Tokio runtime: {e}"));
@@ -302,66 +554,162 @@ fn main() {
     };
 
     runtime.block_on(async {
-        let listener = match TcpListener::bind(addr).await {
-            Ok(x) => x,
-            Err(e) => {
-                let _ = timer_tx_clone.send(false); // stop the hwinfo thread
-
-                log(format!("Failed to bind to {:?}: {e}", addr));
-                wait_for_enter();
+        // bind every listener before starting the web server, so nothing is served if one fails
+        let mut listeners = Vec::with_capacity(listener_configs.len());
 
-                return;
-            }
-        };
+        for listener_config in listener_configs {
+            match Listener::bind(&listener_config, &exe_dir) {
+                Ok(x) => listeners.push((listener_config, x)),
+                Err(e) => {
+                    let _ = timer_tx_clone.send(false); // stop the hwinfo thread
 
-        log(format!("Starting web server on http://{}", addr));
+                    log(e);
+                    wait_for_enter();
 
-        let mut configs_dir_path = public_dir_path.clone();
-        configs_dir_path.push("configs");
+                    return;
+                }
+            }
+        }
 
         let app_state = AppState {
-            configs_dir_path,
+            dashboards: Arc::new(DashboardStore::new(configs_dir_path)),
             latest_sensors: latest_sensors_clone,
             latest_data: latest_data_clone,
             message_tx: message_tx_clone,
+            access: access.clone(),
+            settings,
         };
 
         let mut router = axum::Router::new()
             .route("/sensors", get(sensors_route))
-            .route("/data", get(data_route));
+            .route("/data", get(data_route))
+            .route("/api/session", get(auth::session_route))
+            .merge(api::router());
 
         async fn handle_404() -> (StatusCode, &'static str) {
             (StatusCode::NOT_FOUND, "Not Found")
         }
 
-        if serve_static_files {
-            log(format!(
-                "Serving files from {}",
-                public_dir_path.to_string_lossy()
-            ));
+        match &web_files {
+            WebFiles::Embedded => log("Serving the embedded web application"),
+            WebFiles::Dir(path) => log(format!("Serving files from {}", path.to_string_lossy())),
+            WebFiles::Missing(_) => {}
+        }
+
+        // every route added above is readable by viewers, the dashboard API also checks for editors
+        router = router.route_layer(axum::middleware::from_fn_with_state(
+            Role::Viewer,
+            auth::require_role,
+        ));
+
+        router = match web_files {
+            WebFiles::Embedded => router.fallback(web::embedded_route),
+            WebFiles::Dir(path) => {
+                let service_404 = handle_404.into_service();
+
+                router.fallback_service(ServeDir::new(path).fallback(service_404))
+            }
+            WebFiles::Missing(_) => router.fallback(handle_404),
+        };
 
-            let service_404 = handle_404.into_service();
+        // the login page and the schemas are added after the middleware so they can be requested
+        // without logging in
+        // the IP access rules apply to every request, including the login page
+        let router = router
+            .with_state(app_state)
+            .layer(axum::middleware::from_fn(auth::middleware))
+            .merge(auth::router())
+            .merge(schema::router())
+            .layer(axum::middleware::from_fn_with_state(
+                access.clone(),
+                access::middleware,
+            ));
 
-            // GET /configs/:name is not handled by ServeDir as the file content needs to be validated
-            router = router
-                .route("/configs/:name", get(configs_route))
-                .fallback_service(ServeDir::new(public_dir_path).fallback(service_404));
-        } else {
-            router = router.fallback(handle_404);
+        if !base_path.is_empty() {
+            log(format!("Serving Jonitor under {base_path}/"));
         }
 
-        if let Err(e) = axum::serve(
-            listener,
-            router
-                .with_state(app_state)
-                .into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .with_graceful_shutdown(shutdown_signal(timer_tx_clone, hwinfo_stopped_rx))
-        .await
-        {
-            log(format!("Server error: {e}"));
+        let router = web::with_base_path(router, &base_path);
+
+        let shutdown = shutdown_signal(timer_tx_clone, hwinfo_stopped_rx);
+        tokio::pin!(shutdown);
+
+        let mut servers: Vec<(ListenerConfig, Server)> = listeners
+            .into_iter()
+            .map(|(listener_config, listener)| {
+                let auth = listener_auth(&listener_config, &auth, &base_path);
+
+                (
+                    listener_config,
+                    start_server(listener, router.clone(), auth, access.clone()),
+                )
+            })
+            .collect();
+
+        let mut is_watching_listeners = true;
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                result = listeners_rx.changed(), if is_watching_listeners => {
+                    if result.is_err() {
+                        // the config thread has stopped
+                        is_watching_listeners = false;
+                        continue;
+                    }
+
+                    let new_configs = listeners_rx.borrow_and_update().clone();
+
+                    // start the new listeners before stopping the removed ones
+                    // a changed listener which fails to start keeps running with its previous settings
+                    let mut failed = Vec::new();
+
+                    let start = |listener_config: &ListenerConfig, listener| {
+                        let auth = listener_auth(listener_config, &auth, &base_path);
+
+                        start_server(listener, router.clone(), auth, access.clone())
+                    };
+
+                    for listener_config in new_configs.iter() {
+                        if servers.iter().any(|(x, _)| x == listener_config) {
+                            continue;
+                        }
+
+                        if let Err(e) = replace_server(&mut servers, listener_config, &exe_dir, &start).await {
+                            log(e);
+                            failed.push(listener_config.address.clone());
+                        }
+                    }
+
+                    let (kept, removed): (Vec<_>, Vec<_>) = servers
+                        .into_iter()
+                        .partition(|(x, _)| new_configs.contains(x) || failed.contains(&x.address));
+                    servers = kept;
+
+                    // connected clients of the removed listeners stay connected until they disconnect
+                    for (listener_config, server) in removed {
+                        log(format!("Stopping web server on {}", listener_config.address));
+                        let _ = server.stop_tx.send(());
+                    }
+
+                    if servers.is_empty() {
+                        log("Warning: no listener is running. Fix the listeners in the configuration file to start the web server");
+                    }
+                }
+            }
+        }
 
-            let _ = timer_tx_clone_2.send(false); // stop the hwinfo thread
+        // wait for the websocket connections to be closed
+        let handles: Vec<_> = servers
+            .into_iter()
+            .map(|(_, server)| {
+                let _ = server.stop_tx.send(());
+                server.handle
+            })
+            .collect();
+
+        for handle in handles {
+            let _ = handle.await;
         }
     });
 
@@ -373,6 +721,98 @@ fn main() {
     }
 }
 
+struct Server {
+    // stops accepting new connections
+    stop_tx: tokio::sync::oneshot::Sender<()>,
+    // notified when the address can be bound again
+    unbound_rx: tokio::sync::oneshot::Receiver<()>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+/// Starts a server for `listener_config`, replacing the server with the same address. The TLS
+/// certificate is loaded before the previous server is stopped, which is started again with its
+/// previous settings when the address cannot be bound.
+async fn replace_server(
+    servers: &mut Vec<(ListenerConfig, Server)>,
+    listener_config: &ListenerConfig,
+    exe_dir: &Path,
+    start: impl Fn(&ListenerConfig, Listener) -> Server,
+) -> Result<(), String> {
+    let prepared = Listener::prepare(listener_config, exe_dir)?;
+
+    // an address can only be bound once, so a changed listener is stopped first
+    let previous = match servers
+        .iter()
+        .position(|(x, _)| x.address == listener_config.address)
+    {
+        Some(i) => {
+            let (previous_config, server) = servers.remove(i);
+
+            log(format!(
+                "Restarting web server on {}",
+                listener_config.address
+            ));
+            let _ = server.stop_tx.send(());
+            let _ = server.unbound_rx.await;
+
+            Some(previous_config)
+        }
+        None => None,
+    };
+
+    let e = match prepared.bind() {
+        Ok(listener) => {
+            servers.push((listener_config.clone(), start(listener_config, listener)));
+
+            return Ok(());
+        }
+        Err(e) => e,
+    };
+
+    if let Some(previous_config) = previous {
+        match Listener::bind(&previous_config, exe_dir) {
+            Ok(listener) => {
+                let server = start(&previous_config, listener);
+                servers.push((previous_config, server));
+            }
+            Err(e) => log(e),
+        }
+    }
+
+    Err(e)
+}
+
+/// Returns the users and tokens of a listener, which has its own sessions when it overrides the
+/// top-level `auth`
+fn listener_auth(listener_config: &ListenerConfig, auth: &Arc<Auth>, base_path: &str) -> Arc<Auth> {
+    match &listener_config.auth {
+        Some(config) => Arc::new(Auth::new(Some(config.clone()), base_path)),
+        None => auth.clone(),
+    }
+}
+
+fn start_server(
+    listener: Listener,
+    router: axum::Router,
+    auth: Arc<Auth>,
+    access: Arc<Access>,
+) -> Server {
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let (unbound_tx, unbound_rx) = tokio::sync::oneshot::channel::<()>();
+
+    log(format!("Starting web server on {}", listener.address()));
+
+    let handle = tokio::spawn(listener::run(
+        listener, router, auth, access, stop_rx, unbound_tx,
+    ));
+
+    Server {
+        stop_tx,
+        unbound_rx,
+        handle,
+    }
+}
+
 async fn sensors_route(State(state): State<AppState>) -> impl IntoResponse {
     let json = state.latest_sensors.read().await;
 
@@ -384,22 +824,80 @@ async fn sensors_route(State(state): State<AppState>) -> impl IntoResponse {
 
 async fn data_route(
     State(state): State<AppState>,
+    Query(query): Query<DataQuery>,
     ws: WebSocketUpgrade,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ConnectInfo(addr): ConnectInfo<PeerAddr>,
+    headers: HeaderMap,
+    identity: Option<Extension<Identity>>,
+    connection_slot: Option<Extension<Arc<ConnectionSlot>>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(state, socket, addr))
+    let client = state.access.client_addr(addr, &headers);
+
+    let encoder = match query.encoding {
+        Encoding::Full => {
+            if query.precision != Precision::F64 {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "precision is only supported with delta encoding",
+                )
+                    .into_response();
+            }
+
+            None
+        }
+        Encoding::Delta => Some(DeltaEncoder::new(query.precision)),
+    };
+
+    let websocket_slot = match state.access.open_websocket() {
+        Some(x) => x,
+        None => {
+            log(format!(
+                "{:?} is rejected, the maximum number of websocket connections is reached",
+                client
+            ));
+
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many websocket connections",
+            )
+                .into_response();
+        }
+    };
+
+    let identity = identity.map(|Extension(x)| x);
+
+    ws.on_upgrade(move |socket| async move {
+        handle_websocket(state, socket, client, identity, encoder).await;
+
+        // the connections are counted until the websocket is closed
+        drop((websocket_slot, connection_slot));
+    })
 }
 
-async fn handle_websocket(state: AppState, mut ws: WebSocket, addr: SocketAddr) {
-    log(format!("{:?} connected", addr));
+async fn handle_websocket(
+    state: AppState,
+    mut ws: WebSocket,
+    client: ClientAddr,
+    identity: Option<Identity>,
+    mut encoder: Option<DeltaEncoder>,
+) {
+    match identity {
+        Some(identity) => log(format!("{:?} connected as {}", client, identity.name)),
+        None => log(format!("{:?} connected", client)),
+    }
 
     // send the last read data immediately
     {
         let lock = state.latest_data.read().await;
-        let data = (*lock).clone();
+        let data = match encoder.as_mut() {
+            Some(encoder) => encoder.encode(&lock),
+            None => (*lock).clone(),
+        };
+        drop(lock);
+
         if let Err(e) = ws.send(Message::Binary(data)).await {
             log(format!("Failed to send websocket message: {e}"));
-            log(format!("{:?} disconnected", addr));
+            log(format!("{:?} disconnected", client));
 
             return;
         }
@@ -411,6 +909,11 @@ async fn handle_websocket(state: AppState, mut ws: WebSocket, addr: SocketAddr)
 
     let mut send_task = tokio::spawn(async move {
         while let Ok(msg) = rx.recv().await {
+            let msg = match (msg, encoder.as_mut()) {
+                (Message::Binary(data), Some(encoder)) => Message::Binary(encoder.encode(&data)),
+                (msg, _) => msg,
+            };
+
             if let Err(e) = sender.send(msg).await {
                 log(format!("Failed to send websocket message: {e}"));
                 break;
@@ -438,108 +941,7 @@ async fn handle_websocket(state: AppState, mut ws: WebSocket, addr: SocketAddr)
         _ = (&mut receive_task) => send_task.abort(),
     }
 
-    log(format!("{:?} disconnected", addr));
-}
-
-async fn configs_route(
-    State(state): State<AppState>,
-    axum::extract::Path(name): axum::extract::Path<String>,
-) -> impl IntoResponse {
-    let name_with_ext = format!("{name}.json");
-    let path = Path::new(&name_with_ext);
-
-    let mut components = path.components();
-
-    if let Some(component) = components.next() {
-        match component {
-            Component::Prefix(_)
-            | Component::RootDir
-            | Component::CurDir
-            | Component::ParentDir => {
-                // the path component should not be a directory
-                return (StatusCode::NOT_FOUND, "Not Found").into_response();
-            }
-            Component::Normal(comp) => {
-                // check for paths like `/foo/c:/bar/baz` (see https://github.com/tower-rs/tower-http/pull/204)
-                if !Path::new(&comp)
-                    .components()
-                    .all(|c| matches!(c, Component::Normal(_)))
-                {
-                    return (StatusCode::NOT_FOUND, "Not Found").into_response();
-                }
-            }
-        }
-    } else {
-        return (StatusCode::NOT_FOUND, "Not Found").into_response();
-    }
-
-    // the path should only have 1 component
-    if components.next().is_some() {
-        return (StatusCode::NOT_FOUND, "Not Found").into_response();
-    }
-
-    let mut file_path = state.configs_dir_path.clone();
-    file_path.push(path);
-
-    if !file_path.exists() || !file_path.is_file() {
-        return (StatusCode::NOT_FOUND, "Not Found").into_response();
-    }
-
-    match file_path.metadata() {
-        Ok(metadata) => {
-            // hardcoded to 5 megabytes
-            if metadata.len() > 5000000 {
-                log(format!(
-                    "The size of the charts config file `{name_with_ext}` exceeds 5 megabytes"
-                ));
-
-                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
-                    .into_response();
-            }
-        }
-        Err(e) => {
-            log(format!("Failed to get metadata of a config file: {e}"));
-
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
-        }
-    };
-
-    let task_result = tokio::task::spawn_blocking(move || std::fs::read_to_string(file_path)).await;
-
-    let read_file_result = match task_result {
-        Ok(x) => x,
-        Err(e) => {
-            log(format!("Failed to complete the read file task: {e}"));
-
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
-        }
-    };
-
-    let file_content = match read_file_result {
-        Ok(x) => x,
-        Err(e) => {
-            log(format!(
-                "Failed to read the charts config file `{name_with_ext}`: {e}"
-            ));
-
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
-        }
-    };
-
-    // validate the file content
-    if let Err(e) = serde_json::from_str::<Vec<ChartGroupConfig>>(&file_content) {
-        log(format!(
-            "Failed to parse the charts config file `{name_with_ext}`: {e}"
-        ));
-
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response();
-    }
-
-    (
-        [(axum::http::header::CONTENT_TYPE, "application/json")],
-        file_content,
-    )
-        .into_response()
+    log(format!("{:?} disconnected", client));
 }
 
 async fn shutdown_signal(
@@ -590,7 +992,7 @@ fn log<S: AsRef<str>>(msg: S) {
 }
 
 fn wait_for_enter() {
-    if !std::env::args().any(|s| s == "--close-on-error") {
+    if !CLOSE_ON_ERROR.load(Ordering::Relaxed) {
         println!("\nPress Enter to exit");
         std::io::stdin()
             .read_exact(&mut [0u8])
@@ -600,135 +1002,108 @@ fn wait_for_enter() {
 
 #[derive(Clone)]
 struct AppState {
-    // path to the public\configs folder
-    configs_dir_path: std::path::PathBuf,
+    // dashboards in the configs folder of data_dir
+    dashboards: Arc<DashboardStore>,
     // the latest sensors json read from HWiNFO
     latest_sensors: Arc<RwLock<String>>,
     // the latest data read from HWiNFO
     latest_data: Arc<RwLock<Vec<u8>>>,
     // used for creating receiver part of the broadcast channel
     message_tx: tokio::sync::broadcast::Sender<Message>,
+    // limits the number of websocket connections
+    access: Arc<Access>,
+    // read and changed by admins through `/api/settings`
+    settings: Arc<config::SettingsFile>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct Config {
-    ip: String,
-    port: u16,
-    polling_interval: u32,
-}
-
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-struct ChartGroupConfig {
-    grid_breakpoints: ChartGridBreakpoints,
-    #[serde(deserialize_with = "no_empty_array")]
-    chart_configs: Vec<ChartConfig>,
-}
-
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-struct ChartGridBreakpoints {
-    small_columns: u16,
-    small_width: u16,
-    medium_columns: u16,
-    medium_width: u16,
-    large_columns: u16,
-    large_width: u16,
-    extra_large_columns: u16,
-    extra_large_width: u16,
-}
-
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-struct ChartConfig {
-    #[serde(deserialize_with = "no_empty_string")]
-    title: String,
-    #[serde(deserialize_with = "no_invalid_chart")]
-    chart_type: String,
-    data_count: u32,
-    #[serde(deserialize_with = "no_negative_float")]
-    maximum_value: f32,
-    height: u16,
-    animation_duration: u16,
-    show_legend: bool,
-    show_labels: bool,
-    auto_colors: bool,
-    #[serde(deserialize_with = "no_empty_array")]
-    datasets: Vec<ChartDataset>,
+impl FromRef<AppState> for Arc<DashboardStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.dashboards.clone()
+    }
 }
 
 #[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-struct ChartDataset {
-    sensor_id: u32,
-    sensor_instance: u32,
-    reading_id: u32,
-    label: String,
-    #[serde(deserialize_with = "no_empty_string")]
-    unit: String,
-    #[serde(deserialize_with = "no_empty_string")]
-    color: String,
+struct DataQuery {
+    // `full` sends every value in every frame, `delta` sends keyframes and changed values only
+    #[serde(default)]
+    encoding: Encoding,
+    // precision of values in delta-encoded frames
+    #[serde(default)]
+    precision: Precision,
 }
 
-fn no_empty_array<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
-where
-    D: Deserializer<'de>,
-    T: Deserialize<'de>,
-{
-    let vec = <Vec<T>>::deserialize(deserializer)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::TlsConfig;
 
-    if vec.is_empty() {
-        return Err(serde::de::Error::custom("unexpected empty array"));
-    }
-
-    Ok(vec)
-}
-
-fn no_empty_string<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let str = String::deserialize(deserializer)?;
-
-    if str.trim().is_empty() {
-        return Err(serde::de::Error::custom("unexpected empty string"));
+    fn start_test_server(_: &ListenerConfig, listener: Listener) -> Server {
+        start_server(
+            listener,
+            axum::Router::new(),
+            Arc::new(Auth::new(None, "")),
+            Access::new(None),
+        )
     }
 
-    Ok(str)
-}
-
-fn no_invalid_chart<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let str = String::deserialize(deserializer)?;
+    fn unused_address() -> String {
+        let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
 
-    if str != "bar" && str != "line" && str != "gauge" && str != "table" {
-        return Err(serde::de::Error::invalid_value(
-            serde::de::Unexpected::Str(&str),
-            &r#""bar", "line", "gauge" or "table""#,
-        ));
+        socket.local_addr().unwrap().to_string()
     }
 
-    Ok(str)
-}
-
-fn no_negative_float<'de, D>(deserializer: D) -> Result<f32, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let num = f32::deserialize(deserializer)?;
-
-    if num < 0.0 {
-        return Err(serde::de::Error::invalid_value(
-            serde::de::Unexpected::Float(f64::from(num)),
-            &"0 or a positive floating point number",
-        ));
+    #[test]
+    fn replace_server_keeps_previous_listener() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+
+        runtime.block_on(async {
+            let exe_dir = std::env::temp_dir();
+            let previous_config = ListenerConfig {
+                address: unused_address(),
+                ..Default::default()
+            };
+
+            let mut servers = Vec::new();
+            replace_server(&mut servers, &previous_config, &exe_dir, start_test_server)
+                .await
+                .unwrap();
+
+            // the certificate cannot be loaded, so the previous listener is not stopped
+            let changed_config = ListenerConfig {
+                tls: Some(TlsConfig {
+                    cert: PathBuf::from("jonitor-missing/cert.pem"),
+                    key: PathBuf::from("jonitor-missing/key.pem"),
+                    self_signed: false,
+                }),
+                ..previous_config.clone()
+            };
+
+            let result =
+                replace_server(&mut servers, &changed_config, &exe_dir, start_test_server).await;
+            assert!(result.is_err());
+
+            assert_eq!(servers.len(), 1);
+            assert!(servers[0].0 == previous_config);
+            assert!(!servers[0].1.handle.is_finished());
+            assert!(std::net::TcpStream::connect(&previous_config.address).is_ok());
+
+            // a valid change replaces the listener
+            let changed_config = ListenerConfig {
+                redirect_https_port: Some(443),
+                ..previous_config.clone()
+            };
+
+            replace_server(&mut servers, &changed_config, &exe_dir, start_test_server)
+                .await
+                .unwrap();
+
+            assert_eq!(servers.len(), 1);
+            assert!(servers[0].0 == changed_config);
+            assert!(std::net::TcpStream::connect(&changed_config.address).is_ok());
+
+            for (_, server) in servers {
+                let _ = server.stop_tx.send(());
+            }
+        });
     }
-
-    Ok(num)
 }
@@ -0,0 +1,224 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::hwinfo::ReadError;
+
+const MAGIC: &[u8; 8] = b"JONITORR";
+const VERSION: u32 = 1;
+
+const ENTRY_KIND_SENSORS: u8 = 0;
+const ENTRY_KIND_DATA: u8 = 1;
+
+/// Size of the header of an entry: kind (u8), elapsed milliseconds (u64) and length (u32)
+const ENTRY_HEADER_SIZE: usize = 13;
+
+/// Writes the sensor information and data frames read from HWiNFO to a file.
+///
+/// The file begins with `MAGIC` and an `u32` integer (version), followed by entries. Each entry
+/// has an `u8` integer (kind), an `u64` integer (milliseconds since the recording started), an
+/// `u32` integer (length) and the content. The content of a sensors entry is the JSON encoded
+/// sensor information, and the content of a data entry is a full data frame.
+pub(crate) struct Recorder {
+    file: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> std::io::Result<Recorder> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.flush()?;
+
+        Ok(Recorder {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    /// Writes the values returned by `SharedMemory::read()`. The file is flushed so the
+    /// recording is usable even if Jonitor is closed abruptly.
+    pub(crate) fn write(&mut self, data: &[u8], sensors_json: Option<&str>) -> std::io::Result<()> {
+        let elapsed = self.start.elapsed().as_millis() as u64;
+
+        if let Some(json) = sensors_json {
+            self.write_entry(ENTRY_KIND_SENSORS, elapsed, json.as_bytes())?;
+        }
+
+        self.write_entry(ENTRY_KIND_DATA, elapsed, data)?;
+
+        self.file.flush()
+    }
+
+    fn write_entry(&mut self, kind: u8, elapsed: u64, content: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(&[kind])?;
+        self.file.write_all(&elapsed.to_le_bytes())?;
+        self.file.write_all(&(content.len() as u32).to_le_bytes())?;
+        self.file.write_all(content)
+    }
+}
+
+struct Entry {
+    // milliseconds since the first entry
+    elapsed: u64,
+    // data frame, or the JSON encoded sensor information preceding it
+    data: Vec<u8>,
+    sensors_json: Option<String>,
+}
+
+/// Replays a file written by `Recorder`, as a replacement of `SharedMemory`
+pub(crate) struct Player {
+    entries: Vec<Entry>,
+    // index of the next entry to be returned
+    position: usize,
+    // when the current pass of the recording started
+    start: Instant,
+    speed: f64,
+    is_looping: bool,
+    // added to last_poll_time of every frame so the poll time keeps increasing between passes
+    poll_time_offset: i64,
+    has_ended: bool,
+}
+
+impl Player {
+    pub(crate) fn open(path: &Path, speed: f64, is_looping: bool) -> Result<Player, String> {
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Failed to read the recording: {e}"))?;
+
+        if bytes.len() < 12 || &bytes[0..8] != MAGIC {
+            return Err(String::from("The file is not a Jonitor recording"));
+        }
+
+        let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(format!("Unsupported recording version `{version}`"));
+        }
+
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut sensors_json = None;
+        let mut rest = &bytes[12..];
+
+        while !rest.is_empty() {
+            if rest.len() < ENTRY_HEADER_SIZE {
+                // the last entry was not completely written
+                break;
+            }
+
+            let kind = rest[0];
+            let elapsed = u64::from_le_bytes(rest[1..9].try_into().unwrap());
+            let length = u32::from_le_bytes(rest[9..13].try_into().unwrap()) as usize;
+
+            if rest.len() < ENTRY_HEADER_SIZE + length {
+                break;
+            }
+
+            let content = &rest[ENTRY_HEADER_SIZE..ENTRY_HEADER_SIZE + length];
+            rest = &rest[ENTRY_HEADER_SIZE + length..];
+
+            match kind {
+                ENTRY_KIND_SENSORS => match String::from_utf8(content.to_vec()) {
+                    Ok(x) => sensors_json = Some(x),
+                    Err(_) => return Err(String::from("The recording contains invalid sensors")),
+                },
+                ENTRY_KIND_DATA => {
+                    if jonitor_client::frame::decode_full(content).is_err() {
+                        return Err(String::from("The recording contains an invalid data frame"));
+                    }
+
+                    if entries.is_empty() && sensors_json.is_none() {
+                        return Err(String::from(
+                            "The recording does not begin with the list of sensors",
+                        ));
+                    }
+
+                    entries.push(Entry {
+                        elapsed,
+                        data: content.to_vec(),
+                        sensors_json: sensors_json.take(),
+                    });
+                }
+                _ => return Err(format!("Unknown recording entry kind `{kind}`")),
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(String::from("The recording is empty"));
+        }
+
+        // start replaying from the first entry immediately
+        let first_elapsed = entries[0].elapsed;
+        for entry in entries.iter_mut() {
+            entry.elapsed -= first_elapsed;
+        }
+
+        Ok(Player {
+            entries,
+            position: 0,
+            start: Instant::now(),
+            speed,
+            is_looping,
+            poll_time_offset: 0,
+            has_ended: false,
+        })
+    }
+
+    /// Returns the latest data frame due since the last call, with the same meaning of the
+    /// returned values as `SharedMemory::read()`
+    pub(crate) fn read(&mut self) -> Result<(Vec<u8>, Option<String>), ReadError> {
+        if self.has_ended {
+            return Ok((Vec::with_capacity(0), None));
+        }
+
+        let elapsed = (self.start.elapsed().as_millis() as f64 * self.speed) as u64;
+
+        let mut latest: Option<&Entry> = None;
+        let mut sensors_json = None;
+
+        while let Some(entry) = self.entries.get(self.position) {
+            if entry.elapsed > elapsed {
+                break;
+            }
+
+            if entry.sensors_json.is_some() {
+                sensors_json.clone_from(&entry.sensors_json);
+            }
+
+            latest = Some(entry);
+            self.position += 1;
+        }
+
+        let mut data = match latest {
+            Some(entry) => entry.data.clone(),
+            None => Vec::with_capacity(0),
+        };
+
+        if !data.is_empty() {
+            let poll_time = i64::from_le_bytes(data[0..8].try_into().unwrap());
+            data[0..8].copy_from_slice(&(poll_time + self.poll_time_offset).to_le_bytes());
+        }
+
+        if self.position == self.entries.len() {
+            if self.is_looping {
+                let first = i64::from_le_bytes(self.entries[0].data[0..8].try_into().unwrap());
+                let last = i64::from_le_bytes(
+                    self.entries[self.entries.len() - 1].data[0..8]
+                        .try_into()
+                        .unwrap(),
+                );
+
+                self.poll_time_offset += last - first + 1;
+                self.position = 0;
+                self.start = Instant::now();
+            } else {
+                crate::log("The end of the recording is reached");
+
+                self.has_ended = true;
+            }
+        }
+
+        Ok((data, sensors_json))
+    }
+}
@@ -0,0 +1,449 @@
+//! JSON Schema documents of the dashboard files and the configuration file, served at
+//! `/schemas/<name>.json` for editors to validate and autocomplete the files. They describe the
+//! structs in `dashboard.rs` and `config.rs`, including the rules checked by their custom
+//! deserializers and `validate()` methods, and should be changed together with them.
+
+use axum::extract::Path;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use jonitor_client::ReadingType;
+use serde_json::{json, Value};
+
+use crate::config::{
+    default_session_hours, CONFIG_VERSION, DEFAULT_POLLING_INTERVAL, MINIMUM_POLLING_INTERVAL,
+};
+use crate::dashboard::{CHART_TYPES, MAX_DECIMALS};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns a router serving `GET /schemas/<name>.json`
+pub(crate) fn router<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    axum::Router::new().route("/schemas/:name", axum::routing::get(schema_route))
+}
+
+async fn schema_route(Path(name): Path<String>) -> Response {
+    let schema = match name.as_str() {
+        "dashboard.json" => dashboard(),
+        "config.json" => config(),
+        _ => return (StatusCode::NOT_FOUND, "Not Found").into_response(),
+    };
+
+    (
+        [(header::CONTENT_TYPE, "application/schema+json")],
+        schema.to_string(),
+    )
+        .into_response()
+}
+
+fn integer(maximum: u64) -> Value {
+    json!({ "type": "integer", "minimum": 0, "maximum": maximum })
+}
+
+/// A string which is not empty or only whitespace, checked by `no_empty_string`
+fn non_empty_string() -> Value {
+    json!({ "type": "string", "pattern": "\\S" })
+}
+
+/// Decimal places of values, checked by `no_invalid_decimals`
+fn decimals() -> Value {
+    json!({ "type": "integer", "minimum": 0, "maximum": MAX_DECIMALS })
+}
+
+/// Rules of the fields only supported by one chart type, checked by `ChartConfig::try_from`
+fn chart_type_fields(
+    chart_type: &str,
+    fields: &[&str],
+    required: &[&str],
+    dataset_count: Option<usize>,
+) -> Value {
+    let mut then = json!({ "required": required });
+
+    if let Some(count) = dataset_count {
+        then["properties"] = json!({
+            "datasets": { "minItems": count, "maxItems": count }
+        });
+    }
+
+    json!({
+        "if": {
+            "properties": { "chartType": { "const": chart_type } },
+            "required": ["chartType"]
+        },
+        "then": then,
+        "else": {
+            "not": { "anyOf": fields.iter().map(|x| json!({ "required": [x] })).collect::<Vec<_>>() }
+        }
+    })
+}
+
+/// Schema of a dashboard file, `Vec<ChartGroupConfig>`
+pub(crate) fn dashboard() -> Value {
+    let u16_max = u64::from(u16::MAX);
+    let u32_max = u64::from(u32::MAX);
+    let reading_types: Vec<&str> = ReadingType::ALL.iter().map(|x| x.as_str()).collect();
+
+    json!({
+        "$schema": DRAFT,
+        "$id": "/schemas/dashboard.json",
+        "title": "Jonitor dashboard",
+        "oneOf": [
+            {
+                "type": "array",
+                "items": {
+                    "$ref": "#/$defs/ChartGroupConfig",
+                    "not": { "required": ["repeat"] }
+                }
+            },
+            { "$ref": "#/$defs/DashboardTemplate" }
+        ],
+        "$defs": {
+            "DashboardTemplate": {
+                "description": "Groups with `$name` variables replaced by the matching sensors",
+                "type": "object",
+                "required": ["groups"],
+                "properties": {
+                    "variables": {
+                        "type": "object",
+                        "propertyNames": { "pattern": "^[A-Za-z0-9_]+$" },
+                        "additionalProperties": { "$ref": "#/$defs/VariableConfig" }
+                    },
+                    "groups": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/ChartGroupConfig" }
+                    }
+                }
+            },
+            "VariableConfig": {
+                "type": "object",
+                "required": ["sensor"],
+                "properties": {
+                    "sensor": {
+                        "description": "Sensor name pattern, `*` matches any text",
+                        "type": "string",
+                        "minLength": 1
+                    }
+                }
+            },
+            "ChartGroupConfig": {
+                "type": "object",
+                "required": ["gridBreakpoints", "chartConfigs"],
+                "properties": {
+                    "repeat": {
+                        "description": "Variable of a template, the group is repeated for every matching sensor",
+                        "type": "string"
+                    },
+                    "gridBreakpoints": { "$ref": "#/$defs/ChartGridBreakpoints" },
+                    "chartConfigs": {
+                        "type": "array",
+                        "minItems": 1,
+                        "items": { "$ref": "#/$defs/ChartConfig" }
+                    }
+                }
+            },
+            "ChartGridBreakpoints": {
+                "description": "Number of columns of charts when the page is at least as wide as each width",
+                "type": "object",
+                "required": [
+                    "smallColumns", "smallWidth", "mediumColumns", "mediumWidth",
+                    "largeColumns", "largeWidth", "extraLargeColumns", "extraLargeWidth"
+                ],
+                "properties": {
+                    "smallColumns": integer(u16_max),
+                    "smallWidth": integer(u16_max),
+                    "mediumColumns": integer(u16_max),
+                    "mediumWidth": integer(u16_max),
+                    "largeColumns": integer(u16_max),
+                    "largeWidth": integer(u16_max),
+                    "extraLargeColumns": integer(u16_max),
+                    "extraLargeWidth": integer(u16_max)
+                }
+            },
+            "ChartConfig": {
+                "type": "object",
+                "required": [
+                    "title", "chartType", "dataCount", "maximumValue", "height",
+                    "animationDuration", "showLegend", "showLabels", "autoColors", "datasets"
+                ],
+                "properties": {
+                    "title": non_empty_string(),
+                    "chartType": { "enum": CHART_TYPES },
+                    "dataCount": integer(u32_max),
+                    "maximumValue": { "type": "number", "minimum": 0 },
+                    "height": integer(u16_max),
+                    "animationDuration": integer(u16_max),
+                    "showLegend": { "type": "boolean" },
+                    "showLabels": { "type": "boolean" },
+                    "autoColors": { "type": "boolean" },
+                    "datasets": {
+                        "type": "array",
+                        "minItems": 1,
+                        "items": { "$ref": "#/$defs/ChartDataset" }
+                    },
+                    "minimumValue": {
+                        "description": "Lower end of the axis or gauge, 0 if not set. Has to be less than maximumValue",
+                        "type": "number"
+                    },
+                    "thresholds": {
+                        "description": "Colors of value ranges, sorted by value",
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/Threshold" }
+                    },
+                    "decimals": decimals(),
+                    "trendCount": {
+                        "description": "\"stat\" charts, number of previous values the trend is calculated from",
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u32_max
+                    },
+                    "fill": {
+                        "description": "\"sparkline\" charts, whether the area under the line is filled",
+                        "type": "boolean"
+                    },
+                    "bucketCount": {
+                        "description": "\"heatmap\" charts, number of ranges from minimumValue to maximumValue the values are counted in",
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u16_max
+                    },
+                    "xMaximumValue": {
+                        "description": "\"scatter\" charts, maximum of the x axis, which shows the first dataset",
+                        "type": "number",
+                        "minimum": 0
+                    }
+                },
+                "allOf": [
+                    chart_type_fields("stat", &["trendCount"], &[], Some(1)),
+                    chart_type_fields("sparkline", &["fill"], &[], None),
+                    chart_type_fields("heatmap", &["bucketCount"], &["bucketCount"], Some(1)),
+                    chart_type_fields("scatter", &["xMaximumValue"], &[], Some(2))
+                ]
+            },
+            "ChartDataset": {
+                "description": "A reading shown by a chart, given by its ids or a selector",
+                "type": "object",
+                "required": ["label", "unit", "color"],
+                "anyOf": [
+                    { "required": ["sensorId", "sensorInstance", "readingId"] },
+                    { "required": ["selector"] }
+                ],
+                "properties": {
+                    "sensorId": integer(u32_max),
+                    "sensorInstance": integer(u32_max),
+                    "readingId": integer(u32_max),
+                    "selector": { "$ref": "#/$defs/DatasetSelector" },
+                    "label": { "type": "string" },
+                    "unit": non_empty_string(),
+                    "color": non_empty_string(),
+                    "scale": {
+                        "description": "The value is shown as value * scale + offset in unit, e.g. 1.8 and 32 for °C to °F",
+                        "type": "number",
+                        "not": { "const": 0 }
+                    },
+                    "offset": { "type": "number" },
+                    "decimals": decimals()
+                }
+            },
+            "Threshold": {
+                "description": "Values from value up to the value of the next threshold are shown in color",
+                "type": "object",
+                "required": ["value", "color"],
+                "properties": {
+                    "value": { "type": "number" },
+                    "color": non_empty_string(),
+                    "label": { "type": "string" }
+                }
+            },
+            "DatasetSelector": {
+                "description": "Selects the first reading matching every condition. The ids are used if no reading matches",
+                "type": "object",
+                "minProperties": 1,
+                "properties": {
+                    "sensor": {
+                        "description": "Case-insensitive pattern of the sensor name, `*` matches any text and `?` any character",
+                        "type": "string"
+                    },
+                    "reading": {
+                        "description": "Case-insensitive pattern of the reading name",
+                        "type": "string"
+                    },
+                    "type": { "enum": reading_types },
+                    "unit": { "type": "string" }
+                }
+            }
+        }
+    })
+}
+
+/// Schema of the configuration file, `Config`
+pub(crate) fn config() -> Value {
+    let role = json!({ "enum": ["viewer", "editor", "admin"], "default": "viewer" });
+
+    json!({
+        "$schema": DRAFT,
+        "$id": "/schemas/config.json",
+        "title": "Jonitor configuration file",
+        "type": "object",
+        "properties": {
+            "$schema": { "type": "string" },
+            "version": {
+                "description": "Older versions are migrated when Jonitor starts",
+                "type": "integer",
+                "minimum": 1,
+                "maximum": CONFIG_VERSION,
+                "default": CONFIG_VERSION
+            },
+            "listeners": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "$ref": "#/$defs/ListenerConfig" }
+            },
+            "polling_interval": {
+                "description": "How often HWiNFO is read, in milliseconds",
+                "type": "integer",
+                "minimum": MINIMUM_POLLING_INTERVAL,
+                "maximum": u32::MAX,
+                "default": DEFAULT_POLLING_INTERVAL
+            },
+            "public_dir": {
+                "description": "Path to the web application, relative to the folder of jonitor.exe",
+                "type": "string"
+            },
+            "data_dir": {
+                "description": "Path to the dashboards and recordings, relative to the folder of jonitor.exe. %LOCALAPPDATA%\\Jonitor by default",
+                "type": "string"
+            },
+            "base_path": {
+                "description": "URL path Jonitor is served under behind a reverse proxy, e.g. /monitor",
+                "type": "string",
+                "pattern": "^/[A-Za-z0-9._~/-]*$"
+            },
+            "auth": { "$ref": "#/$defs/AuthConfig" },
+            "access": { "$ref": "#/$defs/AccessConfig" }
+        },
+        "$defs": {
+            "ListenerConfig": {
+                "type": "object",
+                "required": ["address"],
+                "properties": {
+                    "address": {
+                        "description": "`ip:port`, `[ipv6]:port` or `unix:<path>`",
+                        "type": "string"
+                    },
+                    "dual_stack": {
+                        "description": "Also accept IPv4 connections on an IPv6 address",
+                        "type": "boolean",
+                        "default": false
+                    },
+                    "tls": { "$ref": "#/$defs/TlsConfig" },
+                    "redirect_https_port": {
+                        "description": "Redirect every request to the HTTPS listener on this port",
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u16::MAX
+                    },
+                    "auth": {
+                        "description": "Users and API tokens of this listener instead of the top-level auth",
+                        "$ref": "#/$defs/AuthConfig"
+                    }
+                },
+                "allOf": [
+                    { "not": { "required": ["tls", "redirect_https_port"] } },
+                    { "not": { "required": ["auth", "redirect_https_port"] } }
+                ]
+            },
+            "TlsConfig": {
+                "type": "object",
+                "required": ["cert", "key"],
+                "properties": {
+                    "cert": { "description": "PEM encoded certificate chain", "type": "string" },
+                    "key": { "description": "PEM encoded private key", "type": "string" },
+                    "self_signed": {
+                        "description": "Create a self-signed certificate if the files do not exist",
+                        "type": "boolean",
+                        "default": false
+                    }
+                }
+            },
+            "AuthConfig": {
+                "type": "object",
+                "anyOf": [
+                    { "required": ["users"], "properties": { "users": { "minItems": 1 } } },
+                    { "required": ["tokens"], "properties": { "tokens": { "minItems": 1 } } }
+                ],
+                "properties": {
+                    "users": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["username", "password_hash"],
+                            "properties": {
+                                "username": { "type": "string", "minLength": 1 },
+                                "password_hash": {
+                                    "description": "Created by `jonitor hash-password`",
+                                    "type": "string",
+                                    "pattern": "^\\$"
+                                },
+                                "role": role
+                            }
+                        }
+                    },
+                    "tokens": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["name", "hash"],
+                            "properties": {
+                                "name": { "type": "string" },
+                                "hash": {
+                                    "description": "Created by `jonitor generate-token`",
+                                    "type": "string",
+                                    "pattern": "^[0-9a-fA-F]{64}$"
+                                },
+                                "role": role
+                            }
+                        }
+                    },
+                    "session_hours": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u32::MAX,
+                        "default": default_session_hours()
+                    }
+                }
+            },
+            "AccessConfig": {
+                "type": "object",
+                "properties": {
+                    "allow": {
+                        "description": "IP ranges in CIDR notation. Every address is allowed when empty",
+                        "type": "array",
+                        "items": { "type": "string" }
+                    },
+                    "deny": {
+                        "description": "IP ranges in CIDR notation, rejected even if they are allowed",
+                        "type": "array",
+                        "items": { "type": "string" }
+                    },
+                    "trusted_proxies": {
+                        "description": "IP ranges in CIDR notation of reverse proxies, whose X-Forwarded-For header is used to log the address of clients",
+                        "type": "array",
+                        "items": { "type": "string" }
+                    },
+                    "max_websocket_connections": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u32::MAX
+                    },
+                    "max_connections_per_ip": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": u32::MAX
+                    }
+                }
+            }
+        }
+    })
+}
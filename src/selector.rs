@@ -0,0 +1,472 @@
+use jonitor_client::{ReadingType, SensorInfo};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::dashboard::{self, ValidationWarning};
+
+/// Selects a reading by its names, type and unit instead of the ids, which change when the
+/// hardware is replaced or HWiNFO is reinstalled. Every condition which is set has to match.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DatasetSelector {
+    // case-insensitive pattern of the sensor name, `*` matches any text and `?` any character
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sensor: Option<String>,
+    // case-insensitive pattern of the reading name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reading: Option<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    reading_type: Option<ReadingType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+}
+
+impl DatasetSelector {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sensor.is_none()
+            && self.reading.is_none()
+            && self.reading_type.is_none()
+            && self.unit.is_none()
+    }
+
+    /// Returns whether a condition contains a template variable like `$gpu`
+    pub(crate) fn uses_variables(&self) -> bool {
+        [&self.sensor, &self.reading, &self.unit]
+            .into_iter()
+            .flatten()
+            .any(|x| x.contains('$'))
+    }
+}
+
+/// A reading matched by a selector
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SelectorMatch {
+    pub(crate) sensor_id: u32,
+    pub(crate) sensor_instance: u32,
+    pub(crate) reading_id: u32,
+    pub(crate) sensor_name: String,
+    pub(crate) reading_name: String,
+}
+
+/// The readings matched by the selector of a dataset. The dataset shows the first one.
+#[derive(serde::Serialize)]
+pub(crate) struct Resolution {
+    // JSON path of the dataset
+    pub(crate) path: String,
+    pub(crate) selector: DatasetSelector,
+    pub(crate) matches: Vec<SelectorMatch>,
+}
+
+/// Returns the readings matched by a selector, in the order of `sensor_info`
+pub(crate) fn resolve(selector: &DatasetSelector, sensor_info: &SensorInfo) -> Vec<SelectorMatch> {
+    let mut matches = Vec::new();
+
+    for sensor in sensor_info.sensors.iter() {
+        if selector
+            .sensor
+            .as_ref()
+            .is_some_and(|x| !matches_pattern(x, &sensor.name))
+        {
+            continue;
+        }
+
+        for reading in sensor.readings.iter() {
+            let is_match = selector
+                .reading
+                .as_ref()
+                .is_none_or(|x| matches_pattern(x, &reading.name))
+                && selector
+                    .reading_type
+                    .is_none_or(|x| x == reading.reading_type)
+                && selector.unit.as_ref().is_none_or(|x| *x == reading.unit);
+
+            if is_match {
+                matches.push(SelectorMatch {
+                    sensor_id: sensor.id,
+                    sensor_instance: sensor.instance,
+                    reading_id: reading.id,
+                    sensor_name: sensor.name.clone(),
+                    reading_name: reading.name.clone(),
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+/// A dashboard with the selectors resolved
+#[derive(serde::Serialize)]
+pub(crate) struct ResolvedDashboard {
+    #[serde(skip)]
+    pub(crate) content: String,
+    pub(crate) resolutions: Vec<Resolution>,
+    // the charts and groups removed because too few of their datasets have a reading
+    pub(crate) warnings: Vec<ValidationWarning>,
+}
+
+/// Returns the content of a valid dashboard with the ids of every dataset with a selector
+/// replaced by the first reading it matches, and the readings matched by each selector. Datasets
+/// without a match keep their ids, or are removed if they have none. A chart left with fewer
+/// datasets than its type requires is removed, and so is a group left without charts. The
+/// content is returned unchanged if no dataset has a selector.
+///
+/// `group_paths` are the JSON paths of the template groups the groups are created from, the
+/// paths are `$[i]` if it is empty.
+pub(crate) fn resolve_dashboard(
+    content: &str,
+    group_paths: &[String],
+    sensor_info: Option<&SensorInfo>,
+) -> ResolvedDashboard {
+    let mut resolved = ResolvedDashboard {
+        content: content.to_owned(),
+        resolutions: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let mut value = match serde_json::from_str::<Value>(content) {
+        Ok(x) => x,
+        Err(_) => return resolved,
+    };
+
+    let groups = match value.as_array_mut() {
+        Some(x) => x,
+        None => return resolved,
+    };
+
+    for (i, group) in groups.iter_mut().enumerate() {
+        let group_path = group_paths
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| format!("$[{i}]"));
+
+        let charts = match group.get_mut("chartConfigs").and_then(|x| x.as_array_mut()) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        // indexes in the file, before removing charts
+        let mut j = 0;
+
+        charts.retain_mut(|chart| {
+            let chart_path = format!("{group_path}.chartConfigs[{j}]");
+            j += 1;
+
+            let minimum = dashboard::minimum_datasets(
+                chart
+                    .get("chartType")
+                    .and_then(|x| x.as_str())
+                    .unwrap_or_default(),
+            );
+
+            let datasets = match chart.get_mut("datasets").and_then(|x| x.as_array_mut()) {
+                Some(x) => x,
+                None => return true,
+            };
+
+            // indexes in the file, before removing datasets
+            let mut k = 0;
+            let mut removed = 0;
+
+            datasets.retain_mut(|dataset| {
+                let index = k;
+                k += 1;
+
+                let selector = match dataset
+                    .get("selector")
+                    .and_then(|x| DatasetSelector::deserialize(x).ok())
+                {
+                    Some(x) => x,
+                    None => return true,
+                };
+
+                let matches = match sensor_info {
+                    Some(x) => resolve(&selector, x),
+                    None => Vec::with_capacity(0),
+                };
+
+                let is_kept = match (matches.first(), dataset.as_object_mut()) {
+                    (Some(first), Some(object)) => {
+                        object.insert(String::from("sensorId"), first.sensor_id.into());
+                        object.insert(String::from("sensorInstance"), first.sensor_instance.into());
+                        object.insert(String::from("readingId"), first.reading_id.into());
+
+                        true
+                    }
+                    _ => dataset.get("sensorId").is_some(),
+                };
+
+                resolved.resolutions.push(Resolution {
+                    path: format!("{chart_path}.datasets[{index}]"),
+                    selector,
+                    matches,
+                });
+
+                if !is_kept {
+                    removed += 1;
+                }
+
+                is_kept
+            });
+
+            if removed == 0 || datasets.len() >= minimum {
+                return true;
+            }
+
+            push_warning(
+                &mut resolved.warnings,
+                dashboard::removed_chart_warning(chart_path, removed, minimum),
+            );
+
+            false
+        });
+    }
+
+    // the charts of a group cannot be empty
+    let mut i = 0;
+
+    groups.retain(|group| {
+        let group_path = group_paths
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| format!("$[{i}]"));
+        i += 1;
+
+        let is_empty = group
+            .get("chartConfigs")
+            .and_then(|x| x.as_array())
+            .is_some_and(|x| x.is_empty());
+
+        if is_empty {
+            push_warning(
+                &mut resolved.warnings,
+                ValidationWarning {
+                    path: group_path,
+                    message: String::from("the group is removed, none of its charts are shown"),
+                },
+            );
+        }
+
+        !is_empty
+    });
+
+    if !resolved.resolutions.is_empty() {
+        resolved.content = value.to_string();
+    }
+
+    resolved
+}
+
+/// Adds a warning unless it is already found, e.g. for another instance of a repeated group
+fn push_warning(warnings: &mut Vec<ValidationWarning>, warning: ValidationWarning) {
+    let is_found = warnings
+        .iter()
+        .any(|x| x.path == warning.path && x.message == warning.message);
+
+    if !is_found {
+        warnings.push(warning);
+    }
+}
+
+/// Case-insensitive wildcard matching, `*` matches any text and `?` any character
+pub(crate) fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // position after the last `*`, and the text position it is matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if let Some((star_p, star_t)) = backtrack {
+            // let the last `*` match one more character
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|x| *x == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sensor_info() -> SensorInfo {
+        serde_json::from_value(serde_json::json!({
+            "updatedCount": 1,
+            "sensors": [
+                {
+                    "id": 1, "instance": 0, "name": "CPU [#0]: AMD Ryzen 7", "offset": 0,
+                    "readings": [
+                        { "id": 10, "name": "CPU (Tctl/Tdie)", "unit": "°C", "type": "temperature" },
+                        { "id": 11, "name": "Core Clocks (avg)", "unit": "MHz", "type": "clock" }
+                    ]
+                },
+                {
+                    "id": 2, "instance": 0, "name": "GPU [#0]: NVIDIA GeForce", "offset": 4,
+                    "readings": [
+                        { "id": 20, "name": "GPU Temperature", "unit": "°C", "type": "temperature" }
+                    ]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn chart(chart_type: &str, datasets: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "title": "Chart", "chartType": chart_type, "datasets": datasets })
+    }
+
+    fn dataset(selector: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "selector": selector, "label": "A", "unit": "°C", "color": "red" })
+    }
+
+    fn resolved(groups: serde_json::Value, group_paths: &[String]) -> ResolvedDashboard {
+        resolve_dashboard(&groups.to_string(), group_paths, Some(&sensor_info()))
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(matches_pattern("cpu*", "CPU [#0]: AMD Ryzen 7"));
+        assert!(matches_pattern("*ryzen*", "CPU [#0]: AMD Ryzen 7"));
+        assert!(matches_pattern("GPU [#?]*", "GPU [#0]: NVIDIA GeForce"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("a*b*c", "aXXbYYc"));
+        assert!(matches_pattern("a*c", "abcbc"));
+        assert!(matches_pattern("ÄÖ*", "äö ü"));
+
+        assert!(!matches_pattern("cpu", "CPU [#0]"));
+        assert!(!matches_pattern("?", ""));
+        assert!(!matches_pattern("a*b", "aXXc"));
+        assert!(!matches_pattern("", "a"));
+    }
+
+    #[test]
+    fn selector_conditions() {
+        let selector: DatasetSelector =
+            serde_json::from_value(serde_json::json!({ "type": "temperature", "unit": "°C" }))
+                .unwrap();
+
+        let matches = resolve(&selector, &sensor_info());
+        let ids: Vec<u32> = matches.iter().map(|x| x.reading_id).collect();
+        assert_eq!(ids, [10, 20]);
+    }
+
+    #[test]
+    fn ids_are_replaced_by_the_first_match() {
+        let groups = serde_json::json!([{
+            "chartConfigs": [chart("line", serde_json::json!([dataset(serde_json::json!({ "reading": "*temp*" }))]))]
+        }]);
+
+        let resolved = resolved(groups, &[]);
+        let value: serde_json::Value = serde_json::from_str(&resolved.content).unwrap();
+        let dataset = &value[0]["chartConfigs"][0]["datasets"][0];
+
+        assert_eq!(dataset["sensorId"], 2);
+        assert_eq!(dataset["readingId"], 20);
+        assert_eq!(
+            resolved.resolutions[0].path,
+            "$[0].chartConfigs[0].datasets[0]"
+        );
+        assert!(resolved.warnings.is_empty());
+    }
+
+    #[test]
+    fn charts_with_too_few_datasets_are_removed() {
+        let matched = dataset(serde_json::json!({ "sensor": "CPU*", "reading": "CPU*" }));
+        let unmatched = dataset(serde_json::json!({ "sensor": "Drive*" }));
+
+        let groups = serde_json::json!([
+            {
+                "chartConfigs": [
+                    chart("scatter", serde_json::json!([matched.clone(), unmatched.clone()])),
+                    chart("line", serde_json::json!([matched.clone(), unmatched.clone()])),
+                    chart("stat", serde_json::json!([unmatched.clone()]))
+                ]
+            },
+            { "chartConfigs": [chart("gauge", serde_json::json!([unmatched.clone()]))] }
+        ]);
+
+        let resolved = resolved(groups, &[]);
+        let value: serde_json::Value = serde_json::from_str(&resolved.content).unwrap();
+
+        // only the line chart is left, with its matched dataset
+        assert_eq!(value.as_array().unwrap().len(), 1);
+        let charts = value[0]["chartConfigs"].as_array().unwrap();
+        assert_eq!(charts.len(), 1);
+        assert_eq!(charts[0]["chartType"], "line");
+        assert_eq!(charts[0]["datasets"].as_array().unwrap().len(), 1);
+
+        let paths: Vec<&str> = resolved.warnings.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "$[0].chartConfigs[0]",
+                "$[0].chartConfigs[2]",
+                "$[1].chartConfigs[0]",
+                "$[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_refer_to_the_template_groups() {
+        let unmatched = dataset(serde_json::json!({ "sensor": "Drive*" }));
+
+        // two instances of the second template group
+        let groups = serde_json::json!([
+            { "chartConfigs": [chart("line", serde_json::json!([unmatched.clone()]))] },
+            { "chartConfigs": [chart("line", serde_json::json!([unmatched.clone()]))] },
+            { "chartConfigs": [chart("line", serde_json::json!([unmatched.clone()]))] }
+        ]);
+        let group_paths = ["$.groups[0]", "$.groups[1]", "$.groups[1]"].map(String::from);
+
+        let resolved = resolved(groups, &group_paths);
+
+        let paths: Vec<&str> = resolved
+            .resolutions
+            .iter()
+            .map(|x| x.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "$.groups[0].chartConfigs[0].datasets[0]",
+                "$.groups[1].chartConfigs[0].datasets[0]",
+                "$.groups[1].chartConfigs[0].datasets[0]"
+            ]
+        );
+
+        // the warnings of the instances are not repeated
+        let paths: Vec<&str> = resolved.warnings.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "$.groups[0].chartConfigs[0]",
+                "$.groups[1].chartConfigs[0]",
+                "$.groups[0]",
+                "$.groups[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn dashboards_without_selectors_are_unchanged() {
+        let content = r#"[{"chartConfigs":[{"datasets":[{"sensorId":9}]}]}]"#;
+        let resolved = resolve_dashboard(content, &[], None);
+
+        assert_eq!(resolved.content, content);
+        assert!(resolved.resolutions.is_empty());
+    }
+}
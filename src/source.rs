@@ -0,0 +1,18 @@
+use crate::hwinfo::{ReadError, SharedMemory};
+use crate::recording::Player;
+
+/// Where the sensor information and data frames served by Jonitor come from
+pub(crate) enum Source {
+    Hwinfo(SharedMemory),
+    Replay(Player),
+}
+
+impl Source {
+    /// Reads data from the source, see `SharedMemory::read()` for the returned values
+    pub(crate) fn read(&mut self) -> Result<(Vec<u8>, Option<String>), ReadError> {
+        match self {
+            Source::Hwinfo(x) => x.read(),
+            Source::Replay(x) => x.read(),
+        }
+    }
+}
@@ -0,0 +1,834 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use jonitor_client::{DashboardInfo, RevisionInfo};
+use sha2::{Digest, Sha256};
+
+use crate::dashboard::{self, Validation, ValidationError};
+use crate::diff;
+use crate::log;
+
+/// Dashboard files larger than this are rejected, hardcoded to 5 megabytes
+pub(crate) const MAX_DASHBOARD_SIZE: usize = 5000000;
+
+/// Older revisions of a dashboard are deleted when it has more than this
+const MAX_REVISIONS: usize = 50;
+
+/// Folder in the configs folder with the revisions of every dashboard, `<name>/<id>.json`
+const HISTORY_DIR: &str = ".history";
+
+/// Names of the routes next to `/api/dashboards/:name`, and the generated dashboard of
+/// `/configs/auto`, which a dashboard cannot have. Names starting with `.` are also reserved for
+/// files like `HISTORY_DIR`.
+pub(crate) const RESERVED_NAMES: [&str; 6] = [
+    "export",
+    "generate",
+    "import",
+    "instantiate",
+    "validate",
+    "auto",
+];
+
+/// Returns whether a dashboard cannot have a name. File names are case-insensitive on Windows.
+fn is_reserved(name: &str) -> bool {
+    name.starts_with('.') || RESERVED_NAMES.iter().any(|x| x.eq_ignore_ascii_case(name))
+}
+
+/// Returns whether Windows opens a device instead of a file with this name, e.g. `CON` or
+/// `com1.txt`. Device names are case-insensitive and ignore the extension.
+fn is_device_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+
+    if ["CON", "PRN", "AUX", "NUL"]
+        .iter()
+        .any(|x| x.eq_ignore_ascii_case(stem))
+    {
+        return true;
+    }
+
+    match stem.as_bytes() {
+        [a, b, c, b'1'..=b'9'] => {
+            let prefix = [*a, *b, *c];
+
+            prefix.eq_ignore_ascii_case(b"COM") || prefix.eq_ignore_ascii_case(b"LPT")
+        }
+        _ => false,
+    }
+}
+
+pub(crate) enum StoreError {
+    NotFound,
+    // the name is not a valid file name
+    InvalidName,
+    // the name is one of `RESERVED_NAMES` or starts with `.`
+    ReservedName,
+    TooLarge,
+    // the content is not a valid charts config
+    Invalid(ValidationError),
+    AlreadyExists,
+    // If-Match is required to change an existing dashboard
+    PreconditionRequired,
+    // the dashboard has been changed since the given ETag
+    PreconditionFailed,
+    Io(String),
+}
+
+impl IntoResponse for StoreError {
+    fn into_response(self) -> Response {
+        match self {
+            StoreError::NotFound | StoreError::InvalidName => {
+                (StatusCode::NOT_FOUND, "Not Found").into_response()
+            }
+            StoreError::ReservedName => (
+                StatusCode::BAD_REQUEST,
+                "The name is reserved and cannot be used by a dashboard",
+            )
+                .into_response(),
+            StoreError::TooLarge => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "The dashboard exceeds 5 megabytes",
+            )
+                .into_response(),
+            StoreError::Invalid(e) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(Validation::from(e))).into_response()
+            }
+            StoreError::AlreadyExists => {
+                (StatusCode::CONFLICT, "The dashboard already exists").into_response()
+            }
+            StoreError::PreconditionRequired => (
+                StatusCode::PRECONDITION_REQUIRED,
+                "If-Match is required to change an existing dashboard",
+            )
+                .into_response(),
+            StoreError::PreconditionFailed => (
+                StatusCode::PRECONDITION_FAILED,
+                "The dashboard has been changed by someone else",
+            )
+                .into_response(),
+            StoreError::Io(e) => {
+                log(e);
+
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+            }
+        }
+    }
+}
+
+pub(crate) struct Dashboard {
+    pub(crate) content: String,
+    pub(crate) etag: String,
+}
+
+/// A saved version of a dashboard, stored in `.history/<name>/<id>.json`
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RevisionFile {
+    // milliseconds since the Unix epoch
+    timestamp: u64,
+    // the user who saved the revision, `None` when authentication is disabled
+    author: Option<String>,
+    content: String,
+}
+
+/// Dashboard files, `<name>.json` in the configs folder. Every method does blocking file IO.
+pub(crate) struct DashboardStore {
+    dir: PathBuf,
+    // held while a dashboard is changed, so the ETag cannot change between checking and writing
+    write_lock: Mutex<()>,
+}
+
+impl DashboardStore {
+    pub(crate) fn new(dir: PathBuf) -> DashboardStore {
+        DashboardStore {
+            dir,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the path of a dashboard, or an error if the name is not a single file name or is
+    /// reserved
+    fn path(&self, name: &str) -> Result<PathBuf, StoreError> {
+        if is_reserved(name) {
+            return Err(StoreError::ReservedName);
+        }
+
+        // Windows removes trailing dots and spaces from file names, so `main.` would be `main`
+        if name.ends_with(['.', ' ']) || is_device_name(name) {
+            return Err(StoreError::InvalidName);
+        }
+
+        let name_with_ext = format!("{name}.json");
+        let path = Path::new(&name_with_ext);
+
+        let mut components = path.components();
+
+        match components.next() {
+            Some(Component::Normal(comp)) => {
+                // check for paths like `/foo/c:/bar/baz` (see https://github.com/tower-rs/tower-http/pull/204)
+                if !Path::new(&comp)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+                {
+                    return Err(StoreError::InvalidName);
+                }
+            }
+            // the path component should not be a directory
+            _ => return Err(StoreError::InvalidName),
+        }
+
+        // the path should only have 1 component
+        if components.next().is_some() {
+            return Err(StoreError::InvalidName);
+        }
+
+        Ok(self.dir.join(path))
+    }
+
+    pub(crate) fn list(&self) -> Result<Vec<DashboardInfo>, StoreError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::with_capacity(0)),
+            Err(e) => {
+                return Err(StoreError::Io(format!(
+                    "Failed to read the configs folder: {e}"
+                )))
+            }
+        };
+
+        let mut dashboards = Vec::new();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let name = match (path.file_stem(), path.extension()) {
+                (Some(stem), Some(ext)) if ext == "json" => stem.to_string_lossy().into_owned(),
+                _ => continue,
+            };
+
+            // e.g. `auto.json` saved by an older version, which cannot be read by its name
+            if is_reserved(&name) {
+                continue;
+            }
+
+            let dashboard = match self.read_unchecked(&path) {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+
+            let modified = entry
+                .metadata()
+                .and_then(|x| x.modified())
+                .ok()
+                .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+                .map(|x| x.as_millis() as u64);
+
+            let groups = dashboard::parse(&dashboard.content).ok().map(|x| x.groups);
+
+            dashboards.push(DashboardInfo {
+                name,
+                size: dashboard.content.len() as u64,
+                modified,
+                etag: dashboard.etag,
+                group_count: groups.as_ref().map(|x| x.len()),
+                chart_count: groups
+                    .as_ref()
+                    .map(|x| x.iter().map(|group| group.chart_count()).sum()),
+            });
+        }
+
+        dashboards.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(dashboards)
+    }
+
+    /// Reads a dashboard. The content is validated.
+    pub(crate) fn read(&self, name: &str) -> Result<Dashboard, StoreError> {
+        let path = self.path(name)?;
+
+        let dashboard = self.read_unchecked(&path)?;
+
+        dashboard::parse(&dashboard.content).map_err(StoreError::Invalid)?;
+
+        Ok(dashboard)
+    }
+
+    fn read_unchecked(&self, path: &Path) -> Result<Dashboard, StoreError> {
+        if !path.is_file() {
+            return Err(StoreError::NotFound);
+        }
+
+        let metadata = path
+            .metadata()
+            .map_err(|e| StoreError::Io(format!("Failed to get metadata of a config file: {e}")))?;
+
+        if metadata.len() > MAX_DASHBOARD_SIZE as u64 {
+            return Err(StoreError::TooLarge);
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            StoreError::Io(format!(
+                "Failed to read the charts config file `{}`: {e}",
+                path.to_string_lossy()
+            ))
+        })?;
+
+        Ok(Dashboard {
+            etag: etag(&content),
+            content,
+        })
+    }
+
+    /// Creates or replaces a dashboard, and returns the new ETag and whether it is created.
+    /// Replacing requires `if_match` to be the current ETag, and `create_only` rejects replacing.
+    /// The content is also saved as a revision by `author`.
+    pub(crate) fn write(
+        &self,
+        name: &str,
+        content: &str,
+        if_match: Option<&str>,
+        create_only: bool,
+        author: Option<&str>,
+    ) -> Result<(String, bool), StoreError> {
+        if content.len() > MAX_DASHBOARD_SIZE {
+            return Err(StoreError::TooLarge);
+        }
+
+        dashboard::parse(content).map_err(StoreError::Invalid)?;
+
+        let _lock = self.write_lock.lock().unwrap();
+
+        self.write_locked(name, content, if_match, create_only, author)
+    }
+
+    fn write_locked(
+        &self,
+        name: &str,
+        content: &str,
+        if_match: Option<&str>,
+        create_only: bool,
+        author: Option<&str>,
+    ) -> Result<(String, bool), StoreError> {
+        let path = self.path(name)?;
+
+        let is_created = !path.exists();
+        if !is_created && create_only {
+            return Err(StoreError::PreconditionFailed);
+        } else if !is_created {
+            self.check_etag(&path, if_match)?;
+        } else if if_match.is_some_and(|x| x != "*") {
+            // the client expects an existing dashboard
+            return Err(StoreError::PreconditionFailed);
+        }
+
+        let mut revision_ids = self.revision_ids(name)?;
+
+        if !is_created {
+            self.save_disk_content(name, &path, &mut revision_ids)?;
+        }
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| StoreError::Io(format!("Failed to create the configs folder: {e}")))?;
+
+        write_atomic(&path, content.as_bytes())?;
+
+        revision_ids.push(self.add_revision(name, &revision_ids, content, author)?);
+        self.prune_revisions(name, &revision_ids);
+
+        Ok((etag(content), is_created))
+    }
+
+    /// Deletes a dashboard. Its revisions are kept, so it can be restored.
+    pub(crate) fn delete(&self, name: &str, if_match: Option<&str>) -> Result<(), StoreError> {
+        let path = self.path(name)?;
+
+        let _lock = self.write_lock.lock().unwrap();
+
+        if !path.is_file() {
+            return Err(StoreError::NotFound);
+        }
+
+        self.check_etag(&path, if_match)?;
+
+        let mut revision_ids = self.revision_ids(name)?;
+        self.save_disk_content(name, &path, &mut revision_ids)?;
+
+        std::fs::remove_file(&path)
+            .map_err(|e| StoreError::Io(format!("Failed to delete the charts config file: {e}")))?;
+
+        self.prune_revisions(name, &revision_ids);
+
+        Ok(())
+    }
+
+    /// Adds the content of an existing dashboard as a revision unless it is the last revision,
+    /// so dashboards created before the history was kept, or edited on disk, are not lost when
+    /// they are replaced or deleted
+    fn save_disk_content(
+        &self,
+        name: &str,
+        path: &Path,
+        revision_ids: &mut Vec<u64>,
+    ) -> Result<(), StoreError> {
+        let current = self.read_unchecked(path)?;
+
+        let is_saved = match revision_ids.last() {
+            Some(id) => self.read_revision(name, *id)?.content == current.content,
+            None => false,
+        };
+
+        if !is_saved {
+            revision_ids.push(self.add_revision(name, revision_ids, &current.content, None)?);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn rename(
+        &self,
+        name: &str,
+        new_name: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), StoreError> {
+        let path = self.path(name)?;
+        let new_path = self.path(new_name)?;
+
+        let _lock = self.write_lock.lock().unwrap();
+
+        if !path.is_file() {
+            return Err(StoreError::NotFound);
+        }
+
+        self.check_etag(&path, if_match)?;
+
+        if new_path.exists() {
+            return Err(StoreError::AlreadyExists);
+        }
+
+        std::fs::rename(&path, &new_path)
+            .map_err(|e| StoreError::Io(format!("Failed to rename the charts config file: {e}")))?;
+
+        // the revisions are added after the revisions of a deleted dashboard with the new name
+        let mut new_ids = self.revision_ids(new_name)?;
+
+        for id in self.revision_ids(name)? {
+            let next_id = new_ids.last().map_or(1, |x| x + 1);
+
+            std::fs::create_dir_all(self.history_dir(new_name))
+                .and_then(|_| {
+                    std::fs::rename(
+                        self.revision_path(name, id),
+                        self.revision_path(new_name, next_id),
+                    )
+                })
+                .map_err(|e| StoreError::Io(format!("Failed to move a dashboard revision: {e}")))?;
+
+            new_ids.push(next_id);
+        }
+
+        let _ = std::fs::remove_dir(self.history_dir(name));
+        self.prune_revisions(new_name, &new_ids);
+
+        Ok(())
+    }
+
+    /// Returns the revisions of a dashboard, newest first
+    pub(crate) fn revisions(&self, name: &str) -> Result<Vec<RevisionInfo>, StoreError> {
+        let path = self.path(name)?;
+
+        let ids = self.revision_ids(name)?;
+
+        if ids.is_empty() && !path.is_file() {
+            return Err(StoreError::NotFound);
+        }
+
+        let mut revisions = Vec::with_capacity(ids.len());
+
+        for id in ids.into_iter().rev() {
+            let revision = self.read_revision(name, id)?;
+
+            revisions.push(RevisionInfo {
+                id,
+                timestamp: revision.timestamp,
+                author: revision.author,
+                size: revision.content.len() as u64,
+                etag: etag(&revision.content),
+            });
+        }
+
+        Ok(revisions)
+    }
+
+    pub(crate) fn revision(&self, name: &str, id: u64) -> Result<Dashboard, StoreError> {
+        self.path(name)?;
+
+        let revision = self.read_revision(name, id)?;
+
+        Ok(Dashboard {
+            etag: etag(&revision.content),
+            content: revision.content,
+        })
+    }
+
+    /// Returns the differences between two revisions in the unified diff format. The current
+    /// content is compared when `to` is `None`.
+    pub(crate) fn diff(
+        &self,
+        name: &str,
+        from: u64,
+        to: Option<u64>,
+    ) -> Result<String, StoreError> {
+        let path = self.path(name)?;
+
+        let old = self.read_revision(name, from)?.content;
+
+        let (new, new_label) = match to {
+            Some(id) => (
+                self.read_revision(name, id)?.content,
+                format!("{name}@{id}"),
+            ),
+            None => (
+                self.read_unchecked(&path)?.content,
+                format!("{name}@current"),
+            ),
+        };
+
+        Ok(diff::unified(
+            &old,
+            &new,
+            &format!("{name}@{from}"),
+            &new_label,
+        ))
+    }
+
+    /// Saves the content of a revision as the current content, which is also a new revision.
+    /// Replacing an existing dashboard requires `if_match` to be the current ETag.
+    pub(crate) fn restore(
+        &self,
+        name: &str,
+        id: u64,
+        if_match: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<(String, bool), StoreError> {
+        self.path(name)?;
+
+        let _lock = self.write_lock.lock().unwrap();
+
+        let revision = self.read_revision(name, id)?;
+
+        // revisions of dashboards edited on disk may be invalid
+        dashboard::parse(&revision.content).map_err(StoreError::Invalid)?;
+
+        self.write_locked(name, &revision.content, if_match, false, author)
+    }
+
+    fn history_dir(&self, name: &str) -> PathBuf {
+        self.dir.join(HISTORY_DIR).join(name)
+    }
+
+    fn revision_path(&self, name: &str, id: u64) -> PathBuf {
+        self.history_dir(name).join(format!("{id}.json"))
+    }
+
+    /// Returns the ids of the revisions of a dashboard in ascending order
+    fn revision_ids(&self, name: &str) -> Result<Vec<u64>, StoreError> {
+        let entries = match std::fs::read_dir(self.history_dir(name)) {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::with_capacity(0)),
+            Err(e) => {
+                return Err(StoreError::Io(format!(
+                    "Failed to read the revisions of `{name}`: {e}"
+                )))
+            }
+        };
+
+        let mut ids: Vec<u64> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+
+                match path.extension() {
+                    Some(ext) if ext == "json" => path.file_stem()?.to_str()?.parse().ok(),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        ids.sort_unstable();
+
+        Ok(ids)
+    }
+
+    fn read_revision(&self, name: &str, id: u64) -> Result<RevisionFile, StoreError> {
+        let path = self.revision_path(name, id);
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(StoreError::NotFound),
+            Err(e) => {
+                return Err(StoreError::Io(format!(
+                    "Failed to read the revision `{}`: {e}",
+                    path.to_string_lossy()
+                )))
+            }
+        };
+
+        serde_json::from_str(&content).map_err(|e| {
+            StoreError::Io(format!(
+                "Failed to parse the revision `{}`: {e}",
+                path.to_string_lossy()
+            ))
+        })
+    }
+
+    /// Saves a revision after the revisions in `ids`, and returns its id
+    fn add_revision(
+        &self,
+        name: &str,
+        ids: &[u64],
+        content: &str,
+        author: Option<&str>,
+    ) -> Result<u64, StoreError> {
+        let id = ids.last().map_or(1, |x| x + 1);
+
+        let revision = RevisionFile {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |x| x.as_millis() as u64),
+            author: author.map(str::to_owned),
+            content: content.to_owned(),
+        };
+
+        let json = serde_json::to_vec(&revision)
+            .map_err(|e| StoreError::Io(format!("Failed to serialize a revision: {e}")))?;
+
+        std::fs::create_dir_all(self.history_dir(name))
+            .map_err(|e| StoreError::Io(format!("Failed to create the revisions folder: {e}")))?;
+
+        write_atomic(&self.revision_path(name, id), &json)?;
+
+        Ok(id)
+    }
+
+    /// Deletes the oldest revisions over `MAX_REVISIONS`
+    fn prune_revisions(&self, name: &str, ids: &[u64]) {
+        if ids.len() <= MAX_REVISIONS {
+            return;
+        }
+
+        for id in ids[..ids.len() - MAX_REVISIONS].iter() {
+            if let Err(e) = std::fs::remove_file(self.revision_path(name, *id)) {
+                log(format!("Failed to delete an old revision of `{name}`: {e}"));
+            }
+        }
+    }
+
+    fn check_etag(&self, path: &Path, if_match: Option<&str>) -> Result<(), StoreError> {
+        let if_match = if_match.ok_or(StoreError::PreconditionRequired)?;
+
+        if if_match == "*" {
+            return Ok(());
+        }
+
+        let current = self.read_unchecked(path)?;
+
+        if !if_match.split(',').any(|x| x.trim() == current.etag) {
+            return Err(StoreError::PreconditionFailed);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns a strong ETag, the quoted hash of the content
+pub(crate) fn etag(content: impl AsRef<[u8]>) -> String {
+    let hash = Sha256::digest(content.as_ref());
+
+    let hex: String = hash[..16].iter().map(|x| format!("{x:02x}")).collect();
+
+    format!("\"{hex}\"")
+}
+
+/// Moves the dashboards and their revisions from `old_dir`, the configs folder in `public_dir`
+/// used by older versions, to `new_dir` if it does not exist yet. Returns whether the folder was
+/// moved.
+pub(crate) fn migrate(old_dir: &Path, new_dir: &Path) -> Result<bool, String> {
+    if !old_dir.is_dir() || new_dir.exists() {
+        return Ok(false);
+    }
+
+    let error = |e: std::io::Error| {
+        format!(
+            "Failed to move the dashboards from {} to {}: {e}",
+            old_dir.to_string_lossy(),
+            new_dir.to_string_lossy()
+        )
+    };
+
+    if let Some(parent) = new_dir.parent() {
+        std::fs::create_dir_all(parent).map_err(error)?;
+    }
+
+    // renaming fails if the folders are on different drives
+    if std::fs::rename(old_dir, new_dir).is_err() {
+        if let Err(e) = copy_dir(old_dir, new_dir) {
+            let _ = std::fs::remove_dir_all(new_dir);
+
+            return Err(error(e));
+        }
+
+        // the copy is used even if the old folder cannot be removed
+        if let Err(e) = std::fs::remove_dir_all(old_dir) {
+            log(format!(
+                "Failed to remove {}: {e}",
+                old_dir.to_string_lossy()
+            ));
+        }
+    }
+
+    Ok(true)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &path)?;
+        } else {
+            std::fs::copy(entry.path(), path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a file through a temporary file, so the existing file is not corrupted if writing
+/// fails
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), StoreError> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+
+        return Err(StoreError::Io(format!(
+            "Failed to write `{}`: {e}",
+            path.to_string_lossy()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> DashboardStore {
+        let dir = std::env::temp_dir().join(format!("jonitor-store-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        DashboardStore::new(dir)
+    }
+
+    #[test]
+    fn reserved_names_are_rejected() {
+        let store = temp_store("reserved");
+
+        for name in ["import", "Validate", "auto", ".history", ".hidden"] {
+            assert!(matches!(
+                store.write(name, "[]", None, false, None),
+                Err(StoreError::ReservedName)
+            ));
+            assert!(matches!(store.read(name), Err(StoreError::ReservedName)));
+        }
+
+        assert!(store.write("automatic", "[]", None, false, None).is_ok());
+
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn invalid_file_names_are_rejected() {
+        let store = temp_store("invalid");
+
+        for name in [
+            "CON",
+            "con",
+            "Nul",
+            "aux.txt",
+            "prn.json",
+            "COM1",
+            "lpt9.log",
+            "com3 .txt",
+            "main.",
+            "main ",
+            "main. .",
+            "a/../main",
+            "a/b",
+        ] {
+            assert!(matches!(
+                store.write(name, "[]", None, false, None),
+                Err(StoreError::InvalidName)
+            ));
+            assert!(matches!(store.read(name), Err(StoreError::InvalidName)));
+        }
+
+        for name in [
+            "console",
+            "com10",
+            "lpt",
+            "com0",
+            "nul-values",
+            "main.backup",
+        ] {
+            assert!(!is_device_name(name), "{name}");
+        }
+
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn delete_keeps_content_edited_on_disk() {
+        let store = temp_store("delete");
+
+        let Ok((etag, _)) = store.write("main", "[]", None, false, None) else {
+            panic!("failed to save the dashboard");
+        };
+        std::fs::write(store.dir.join("main.json"), "[ ]").unwrap();
+
+        // the ETag of the edited file is required
+        assert!(matches!(
+            store.delete("main", Some(&etag)),
+            Err(StoreError::PreconditionFailed)
+        ));
+        assert!(store.delete("main", Some(&super::etag("[ ]"))).is_ok());
+
+        let Ok(revisions) = store.revisions("main") else {
+            panic!("failed to list the revisions");
+        };
+        assert_eq!(revisions.len(), 2);
+        assert!(matches!(
+            store.revision("main", revisions[0].id),
+            Ok(x) if x.content == "[ ]"
+        ));
+
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+use jonitor_client::SensorInfo;
+use serde_json::Value;
+
+use crate::dashboard::{self, ValidationWarning};
+use crate::selector::matches_pattern;
+
+/// A dashboard with the template instantiated
+pub(crate) struct Instance {
+    pub(crate) content: String,
+    // JSON path of the template group each group of `content` is created from, e.g.
+    // `$.groups[1]`, empty if the dashboard is not a template
+    pub(crate) group_paths: Vec<String>,
+    // the variables which match no sensor, and the groups repeated for no sensor
+    pub(crate) warnings: Vec<ValidationWarning>,
+}
+
+/// Returns the groups of a valid template for the current sensors. Every variable like `$gpu`
+/// is replaced by the name of the first sensor it matches, and a group with `repeat` is repeated
+/// for every sensor its variable matches. Variables without a match are not replaced, and are
+/// reported in the warnings with the groups they repeat. Dashboards which are not templates are
+/// returned unchanged.
+pub(crate) fn instantiate(content: &str, sensor_info: Option<&SensorInfo>) -> Instance {
+    let unchanged = || Instance {
+        content: content.to_owned(),
+        group_paths: Vec::with_capacity(0),
+        warnings: Vec::with_capacity(0),
+    };
+
+    if !dashboard::is_template(content) {
+        return unchanged();
+    }
+
+    let mut template = match serde_json::from_str::<Value>(content) {
+        Ok(x) => x,
+        Err(_) => return unchanged(),
+    };
+
+    // names of the sensors matched by each variable
+    let mut matches: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    if let Some(variables) = template.get("variables").and_then(|x| x.as_object()) {
+        if sensor_info.is_none() && !variables.is_empty() {
+            warnings.push(dashboard::no_sensors_warning());
+        }
+
+        for (name, variable) in variables.iter() {
+            let pattern = variable
+                .get("sensor")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default();
+
+            let mut names: Vec<&str> = Vec::new();
+
+            for sensor in sensor_info.iter().flat_map(|x| x.sensors.iter()) {
+                // sensors with the same name cannot be told apart by selectors
+                if matches_pattern(pattern, &sensor.name) && !names.contains(&sensor.name.as_str())
+                {
+                    names.push(&sensor.name);
+                }
+            }
+
+            if sensor_info.is_some() && names.is_empty() {
+                warnings.push(ValidationWarning {
+                    path: format!("$.variables.{name}"),
+                    message: String::from("the variable matches no sensor"),
+                });
+            }
+
+            matches.insert(name.clone(), names);
+        }
+    }
+
+    let first_matches: BTreeMap<&str, &str> = matches
+        .iter()
+        .filter_map(|(name, names)| Some((name.as_str(), *names.first()?)))
+        .collect();
+
+    let groups = match template.get_mut("groups").and_then(|x| x.as_array_mut()) {
+        Some(x) => std::mem::take(x),
+        None => Vec::with_capacity(0),
+    };
+
+    let mut instances = Vec::with_capacity(groups.len());
+    let mut group_paths = Vec::with_capacity(groups.len());
+
+    for (i, mut group) in groups.into_iter().enumerate() {
+        let repeat = group
+            .as_object_mut()
+            .and_then(|x| x.remove("repeat"))
+            .and_then(|x| x.as_str().map(str::to_owned));
+
+        match repeat {
+            Some(name) if matches.get(&name).is_none_or(|x| x.is_empty()) => {
+                warnings.push(ValidationWarning {
+                    path: format!("$.groups[{i}]"),
+                    message: format!("the group is not shown, `{name}` matches no sensor"),
+                });
+            }
+            Some(name) => {
+                for sensor_name in matches.get(&name).into_iter().flatten() {
+                    let mut values = first_matches.clone();
+                    values.insert(&name, sensor_name);
+
+                    let mut instance = group.clone();
+                    replace_in_value(&mut instance, &values);
+                    instances.push(instance);
+                    group_paths.push(format!("$.groups[{i}]"));
+                }
+            }
+            None => {
+                replace_in_value(&mut group, &first_matches);
+                instances.push(group);
+                group_paths.push(format!("$.groups[{i}]"));
+            }
+        }
+    }
+
+    Instance {
+        content: Value::Array(instances).to_string(),
+        group_paths,
+        warnings,
+    }
+}
+
+/// Replaces the variables in every string of a JSON value
+fn replace_in_value(value: &mut Value, values: &BTreeMap<&str, &str>) {
+    match value {
+        Value::String(text) if text.contains('$') => {
+            *text = replace_variables(text, values);
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                replace_in_value(item, values);
+            }
+        }
+        Value::Object(object) => {
+            for (_, item) in object.iter_mut() {
+                replace_in_value(item, values);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces every `$name` in a text with the value of the variable, unless it is not found
+fn replace_variables(text: &str, values: &BTreeMap<&str, &str>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        let name_length = rest
+            .find(|x: char| !x.is_ascii_alphanumeric() && x != '_')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_length];
+
+        match values.get(name) {
+            Some(value) => result.push_str(value),
+            None => {
+                result.push('$');
+                result.push_str(name);
+            }
+        }
+
+        rest = &rest[name_length..];
+    }
+
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sensor_info() -> SensorInfo {
+        let sensor = |id: u32, name: &str| serde_json::json!({ "id": id, "instance": 0, "name": name, "offset": 0, "readings": [] });
+
+        serde_json::from_value(serde_json::json!({
+            "updatedCount": 1,
+            "sensors": [
+                sensor(1, "CPU [#0]: AMD Ryzen 7"),
+                sensor(2, "GPU [#0]: NVIDIA GeForce"),
+                sensor(3, "GPU [#1]: AMD Radeon"),
+                // the same name as another sensor
+                sensor(4, "GPU [#1]: AMD Radeon")
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn template(variables: Value, groups: Value) -> String {
+        serde_json::json!({ "variables": variables, "groups": groups }).to_string()
+    }
+
+    fn groups(instance: &Instance) -> Vec<Value> {
+        serde_json::from_str(&instance.content).unwrap()
+    }
+
+    #[test]
+    fn variables_are_replaced() {
+        let values = BTreeMap::from([("gpu", "GPU [#0]"), ("cpu_0", "CPU")]);
+
+        assert_eq!(replace_variables("$gpu", &values), "GPU [#0]");
+        assert_eq!(
+            replace_variables("$cpu_0 and $gpu: usage", &values),
+            "CPU and GPU [#0]: usage"
+        );
+        assert_eq!(replace_variables("$gpus", &values), "$gpus");
+        assert_eq!(replace_variables("$unknown $", &values), "$unknown $");
+        assert_eq!(replace_variables("10$", &values), "10$");
+        assert_eq!(replace_variables("$$gpu", &values), "$GPU [#0]");
+    }
+
+    #[test]
+    fn groups_are_repeated_for_every_sensor() {
+        let content = template(
+            serde_json::json!({ "gpu": { "sensor": "GPU*" }, "cpu": { "sensor": "CPU*" } }),
+            serde_json::json!([
+                { "title": "$cpu" },
+                { "repeat": "gpu", "title": "$gpu with $cpu" }
+            ]),
+        );
+
+        let instance = instantiate(&content, Some(&sensor_info()));
+        let groups = groups(&instance);
+
+        let titles: Vec<&str> = groups
+            .iter()
+            .map(|x| x["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "CPU [#0]: AMD Ryzen 7",
+                "GPU [#0]: NVIDIA GeForce with CPU [#0]: AMD Ryzen 7",
+                "GPU [#1]: AMD Radeon with CPU [#0]: AMD Ryzen 7"
+            ]
+        );
+        assert!(groups.iter().all(|x| x.get("repeat").is_none()));
+        assert_eq!(
+            instance.group_paths,
+            ["$.groups[0]", "$.groups[1]", "$.groups[1]"]
+        );
+        assert!(instance.warnings.is_empty());
+    }
+
+    #[test]
+    fn unmatched_variables_are_reported() {
+        let content = template(
+            serde_json::json!({ "drive": { "sensor": "S.M.A.R.T.*" } }),
+            serde_json::json!([{ "title": "$drive" }, { "repeat": "drive" }]),
+        );
+
+        let instance = instantiate(&content, Some(&sensor_info()));
+        let groups = groups(&instance);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["title"], "$drive");
+
+        let paths: Vec<&str> = instance.warnings.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(paths, ["$.variables.drive", "$.groups[1]"]);
+    }
+
+    #[test]
+    fn missing_sensors_are_reported() {
+        let content = template(
+            serde_json::json!({ "gpu": { "sensor": "GPU*" } }),
+            serde_json::json!([{ "repeat": "gpu" }]),
+        );
+
+        let instance = instantiate(&content, None);
+
+        assert!(groups(&instance).is_empty());
+
+        let paths: Vec<&str> = instance.warnings.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(paths, ["$.variables", "$.groups[0]"]);
+    }
+
+    #[test]
+    fn dashboards_are_unchanged() {
+        let content = r#"[{"title":"$gpu"}]"#;
+        let instance = instantiate(content, Some(&sensor_info()));
+
+        assert_eq!(instance.content, content);
+        assert!(instance.group_paths.is_empty() && instance.warnings.is_empty());
+    }
+}
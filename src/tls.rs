@@ -0,0 +1,308 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+use std::io::Write;
+
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Redirect};
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, FALSE, HANDLE},
+    Security::{
+        AddAccessAllowedAce,
+        Authorization::{SetNamedSecurityInfoW, SE_FILE_OBJECT},
+        GetLengthSid, GetTokenInformation, InitializeAcl, TokenUser, ACCESS_ALLOWED_ACE, ACL,
+        ACL_REVISION, DACL_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION, TOKEN_QUERY,
+        TOKEN_USER,
+    },
+    Storage::FileSystem::FILE_ALL_ACCESS,
+    System::Threading::{GetCurrentProcess, OpenProcessToken},
+};
+
+use crate::config::TlsConfig;
+use crate::listener::ListenAddress;
+use crate::log;
+
+/// Returns the certificate used by every TLS connection of a listener, which is replaced when
+/// the certificate or key file changes
+#[derive(Debug)]
+pub(crate) struct CertificateResolver {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    certified_key: RwLock<Arc<CertifiedKey>>,
+    // modified time of the certificate and key files when they were last loaded
+    modified_times: Mutex<(Option<SystemTime>, Option<SystemTime>)>,
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.read().unwrap().clone())
+    }
+}
+
+impl CertificateResolver {
+    fn load(cert_path: PathBuf, key_path: PathBuf) -> Result<CertificateResolver, String> {
+        let modified_times = (modified_time(&cert_path), modified_time(&key_path));
+        let certified_key = load_certified_key(&cert_path, &key_path)?;
+
+        Ok(CertificateResolver {
+            cert_path,
+            key_path,
+            certified_key: RwLock::new(Arc::new(certified_key)),
+            modified_times: Mutex::new(modified_times),
+        })
+    }
+
+    /// Loads the certificate again if the certificate or key file has changed. The old
+    /// certificate is kept if the new one is invalid, e.g. when only one of the files has been
+    /// replaced so far.
+    pub(crate) fn reload_if_changed(&self) {
+        let modified = (
+            modified_time(&self.cert_path),
+            modified_time(&self.key_path),
+        );
+
+        let mut last_modified = self.modified_times.lock().unwrap();
+        if modified.0.is_none() || modified.1.is_none() || modified == *last_modified {
+            return;
+        }
+
+        *last_modified = modified;
+
+        match load_certified_key(&self.cert_path, &self.key_path) {
+            Ok(x) => {
+                *self.certified_key.write().unwrap() = Arc::new(x);
+
+                log(format!(
+                    "The TLS certificate {} is reloaded",
+                    self.cert_path.to_string_lossy()
+                ));
+            }
+            Err(e) => log(format!("{e}. The old certificate is still used")),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|x| x.modified()).ok()
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|x| x.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            format!(
+                "Failed to read the TLS certificate {}: {e}",
+                cert_path.to_string_lossy()
+            )
+        })?;
+
+    if certs.is_empty() {
+        return Err(format!(
+            "Failed to read the TLS certificate {}: no certificate is found",
+            cert_path.to_string_lossy()
+        ));
+    }
+
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        format!(
+            "Failed to read the TLS key {}: {e}",
+            key_path.to_string_lossy()
+        )
+    })?;
+
+    let signing_key = ring::sign::any_supported_type(&key).map_err(|e| {
+        format!(
+            "Failed to read the TLS key {}: {e}",
+            key_path.to_string_lossy()
+        )
+    })?;
+
+    Ok(CertifiedKey::new(certs, signing_key))
+}
+
+/// Creates the acceptor of a HTTPS listener. A self-signed certificate is created first if
+/// enabled and the certificate does not exist.
+pub(crate) fn acceptor(
+    config: &TlsConfig,
+    base_dir: &Path,
+    address: &ListenAddress,
+) -> Result<(TlsAcceptor, Arc<CertificateResolver>), String> {
+    let cert_path = base_dir.join(&config.cert);
+    let key_path = base_dir.join(&config.key);
+
+    if config.self_signed && !cert_path.exists() && !key_path.exists() {
+        create_self_signed(&cert_path, &key_path, address)?;
+    }
+
+    let resolver = Arc::new(CertificateResolver::load(cert_path, key_path)?);
+
+    let mut server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {e}"))?
+        .with_no_client_auth()
+        .with_cert_resolver(resolver.clone());
+
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok((TlsAcceptor::from(Arc::new(server_config)), resolver))
+}
+
+fn create_self_signed(
+    cert_path: &Path,
+    key_path: &Path,
+    address: &ListenAddress,
+) -> Result<(), String> {
+    let mut names = vec![String::from("localhost")];
+
+    if let ListenAddress::Tcp(addr) = address {
+        if !addr.ip().is_unspecified() {
+            names.push(addr.ip().to_string());
+        }
+    }
+
+    let certified_key = rcgen::generate_simple_self_signed(names)
+        .map_err(|e| format!("Failed to create a self-signed certificate: {e}"))?;
+
+    std::fs::write(cert_path, certified_key.cert.pem()).map_err(|e| {
+        format!(
+            "Failed to save the TLS certificate {}: {e}",
+            cert_path.to_string_lossy()
+        )
+    })?;
+
+    write_private_file(key_path, certified_key.key_pair.serialize_pem().as_bytes()).map_err(
+        |e| {
+            format!(
+                "Failed to save the TLS key {}: {e}",
+                key_path.to_string_lossy()
+            )
+        },
+    )?;
+
+    log(format!(
+        "A self-signed certificate is created at {}. Browsers show a warning until it is trusted",
+        cert_path.to_string_lossy()
+    ));
+
+    Ok(())
+}
+
+/// Creates a file which only the current user can access, e.g. a private key. The permissions
+/// inherited from the folder are removed before anything is written.
+fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::File::create_new(path)?;
+
+    if let Err(e) = restrict_to_current_user(path) {
+        drop(file);
+        let _ = std::fs::remove_file(path);
+
+        return Err(e);
+    }
+
+    file.write_all(contents)
+}
+
+/// Replaces the permissions of a file with full access for the current user
+fn restrict_to_current_user(path: &Path) -> std::io::Result<()> {
+    let mut token: HANDLE = std::ptr::null_mut();
+
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) } == FALSE {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    // the first call returns the size of the user and its SID
+    let mut length = 0;
+    unsafe { GetTokenInformation(token, TokenUser, std::ptr::null_mut(), 0, &mut length) };
+
+    // u64 for the alignment of TOKEN_USER
+    let mut token_user = vec![0u64; (length as usize).div_ceil(8)];
+    let result = unsafe {
+        GetTokenInformation(
+            token,
+            TokenUser,
+            token_user.as_mut_ptr().cast(),
+            length,
+            &mut length,
+        )
+    };
+    let error = std::io::Error::last_os_error();
+    unsafe { CloseHandle(token) };
+
+    if result == FALSE {
+        return Err(error);
+    }
+
+    let sid = unsafe { &*(token_user.as_ptr() as *const TOKEN_USER) }
+        .User
+        .Sid;
+
+    // an ACL with a single ACE, which ends with the SID instead of `SidStart`
+    let acl_length = std::mem::size_of::<ACL>() + std::mem::size_of::<ACCESS_ALLOWED_ACE>()
+        - std::mem::size_of::<u32>()
+        + unsafe { GetLengthSid(sid) } as usize;
+    let mut acl = vec![0u64; acl_length.div_ceil(8)];
+    let acl = acl.as_mut_ptr() as *mut ACL;
+
+    if unsafe { InitializeAcl(acl, acl_length as u32, ACL_REVISION) } == FALSE
+        || unsafe { AddAccessAllowedAce(acl, ACL_REVISION, FILE_ALL_ACCESS, sid) } == FALSE
+    {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let w_path = path
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+
+    let error = unsafe {
+        SetNamedSecurityInfoW(
+            w_path.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            acl,
+            std::ptr::null(),
+        )
+    };
+
+    if error != 0 {
+        return Err(std::io::Error::from_raw_os_error(error as i32));
+    }
+
+    Ok(())
+}
+
+/// Returns a router which redirects every request to the HTTPS listener on `port` of the same
+/// host. The host is taken from the `Host` header, or the URI of an absolute-form request, and
+/// never from `X-Forwarded-Host`, which any client can set.
+pub(crate) fn redirect_router(port: u16) -> axum::Router {
+    axum::Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+        let authority = match headers.get(header::HOST) {
+            Some(host) => host
+                .to_str()
+                .ok()
+                .and_then(|x| x.parse::<axum::http::uri::Authority>().ok()),
+            None => uri.authority().cloned(),
+        };
+
+        // replace the port, IPv6 addresses keep their brackets
+        let host = match authority {
+            Some(x) => x.host().to_owned(),
+            None => return (StatusCode::BAD_REQUEST, "Bad Request").into_response(),
+        };
+
+        let path = uri.path_and_query().map(|x| x.as_str()).unwrap_or("/");
+
+        Redirect::permanent(&format!("https://{host}:{port}{path}")).into_response()
+    })
+}
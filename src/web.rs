@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Redirect, Response};
+use tower_service::Service;
+
+use crate::config::Config;
+use crate::store;
+
+/// A file of the web application embedded into jonitor.exe by the `embed-web` feature
+struct EmbeddedFile {
+    // URL path, e.g. `/index.html`
+    path: &'static str,
+    content: &'static [u8],
+    // precompressed by the web build, `None` if compressing does not make the file smaller
+    gzip: Option<&'static [u8]>,
+    brotli: Option<&'static [u8]>,
+}
+
+#[cfg(feature = "embed-web")]
+static FILES: &[EmbeddedFile] = include!(concat!(env!("OUT_DIR"), "/web_assets.rs"));
+
+#[cfg(not(feature = "embed-web"))]
+static FILES: &[EmbeddedFile] = &[];
+
+/// Files in this folder have a hash in their name, so they never change
+const IMMUTABLE_DIR: &str = "/assets/";
+
+/// Where the web application is served from
+pub(crate) enum WebFiles {
+    Embedded,
+    Dir(PathBuf),
+    // the folder does not exist, only the API is served
+    Missing(PathBuf),
+}
+
+/// Returns where the web application is served from. A `public_dir` set in the configuration
+/// file overrides the embedded files, and `public` in the folder of jonitor.exe is used when the
+/// files are not embedded.
+pub(crate) fn web_files(config: &Config, exe_dir: &Path) -> WebFiles {
+    if config.public_dir.is_none() && !FILES.is_empty() {
+        return WebFiles::Embedded;
+    }
+
+    let path = config.public_dir_path(exe_dir);
+
+    if path.is_dir() {
+        WebFiles::Dir(path)
+    } else {
+        WebFiles::Missing(path)
+    }
+}
+
+/// Returns a router serving `router` under `base_path`, e.g. `/monitor`, for a reverse proxy
+/// which forwards the path unchanged. The base path is removed before routing, so the routes and
+/// the web application are unchanged. Requests outside of it receive 404 Not Found.
+pub(crate) fn with_base_path(router: axum::Router, base_path: &str) -> axum::Router {
+    if base_path.is_empty() {
+        return router;
+    }
+
+    let base_path: Arc<str> = Arc::from(base_path);
+
+    axum::Router::new().fallback(move |mut request: Request| {
+        let base_path = base_path.clone();
+        let mut router = router.clone();
+
+        async move {
+            let query = match request.uri().query() {
+                Some(x) => format!("?{x}"),
+                None => String::with_capacity(0),
+            };
+
+            let path = match request.uri().path().strip_prefix(&*base_path) {
+                Some(x) if x.starts_with('/') => x.to_owned(),
+                // the URLs of the web application are relative to the base path with a slash
+                Some("") => {
+                    return Redirect::permanent(&format!("{base_path}/{query}")).into_response()
+                }
+                _ => return (StatusCode::NOT_FOUND, "Not Found").into_response(),
+            };
+
+            let mut parts = request.uri().clone().into_parts();
+            parts.path_and_query = format!("{path}{query}").parse().ok();
+
+            match Uri::from_parts(parts) {
+                Ok(x) => *request.uri_mut() = x,
+                Err(_) => return (StatusCode::BAD_REQUEST, "Bad Request").into_response(),
+            }
+
+            // routers never fail
+            router.call(request).await.unwrap_or_else(|e| match e {})
+        }
+    })
+}
+
+/// Serves the embedded files. The brotli or gzip variant is sent if the client accepts it.
+pub(crate) async fn embedded_route(method: Method, uri: Uri, headers: HeaderMap) -> Response {
+    if method != Method::GET && method != Method::HEAD {
+        return (StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed").into_response();
+    }
+
+    let path = match uri.path() {
+        x if x.ends_with('/') => format!("{x}index.html"),
+        x => x.to_owned(),
+    };
+
+    let (i, file) = match FILES.iter().enumerate().find(|(_, x)| x.path == path) {
+        Some(x) => x,
+        None => return (StatusCode::NOT_FOUND, "Not Found").into_response(),
+    };
+
+    let accept_encoding = headers
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|x| x.to_str().ok())
+        .unwrap_or_default();
+
+    let (content, encoding) = match (file.brotli, file.gzip) {
+        (Some(x), _) if accepts(accept_encoding, "br") => (x, Some("br")),
+        (_, Some(x)) if accepts(accept_encoding, "gzip") => (x, Some("gzip")),
+        _ => (file.content, None),
+    };
+
+    // each variant has its own ETag, as required for strong ETags
+    let etag = match encoding {
+        Some(x) => format!("{}-{x}\"", etag(i).trim_end_matches('"')),
+        None => etag(i).to_owned(),
+    };
+
+    let cache_control = if file.path.starts_with(IMMUTABLE_DIR) {
+        "public, max-age=31536000, immutable"
+    } else {
+        // revalidated with the ETag, so an updated jonitor.exe is used immediately
+        "no-cache"
+    };
+
+    let mime = mime_guess::from_path(file.path).first_or_octet_stream();
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    response_headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    if let Ok(x) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, x);
+    }
+
+    let is_not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.split(',').any(|x| x.trim() == etag));
+
+    if is_not_modified {
+        return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+    }
+
+    if let Ok(x) = HeaderValue::from_str(mime.as_ref()) {
+        response_headers.insert(header::CONTENT_TYPE, x);
+    }
+
+    if let Some(x) = encoding {
+        response_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(x));
+    }
+
+    (response_headers, content).into_response()
+}
+
+/// Returns the ETag of an embedded file, calculated when it is first requested
+fn etag(index: usize) -> &'static str {
+    static ETAGS: OnceLock<Vec<String>> = OnceLock::new();
+
+    ETAGS.get_or_init(|| FILES.iter().map(|x| store::etag(x.content)).collect())[index].as_str()
+}
+
+/// Returns whether `Accept-Encoding` contains an encoding which is not disabled by `q=0`
+fn accepts(accept_encoding: &str, encoding: &str) -> bool {
+    accept_encoding.split(',').any(|x| {
+        let mut parts = x.split(';').map(str::trim);
+
+        parts.next() == Some(encoding)
+            && !parts.any(|x| matches!(x, "q=0" | "q=0.0" | "q=0.00" | "q=0.000"))
+    })
+}
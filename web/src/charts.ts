@@ -26,6 +26,8 @@ import {
     TimeSeriesScale,
     DoughnutController,
     ArcElement,
+    ScatterController,
+    Filler,
 } from "chart.js";
 
 import imgAddUrl from "./assets/add.svg";
@@ -43,6 +45,7 @@ import type {
     ChartType,
     LegendItem,
     Point,
+    ScriptableContext,
 } from "chart.js";
 
 let state: "configure" | "configureEmpty" | "active" = "configure";
@@ -72,12 +75,43 @@ let configureGroupConfigs: ChartGroupConfig[] = [];
 // sensors for the current configure session to allow consistency
 let currentSensors: SensorInfo | null = null;
 
+// the dashboard on the server the charts were loaded from or saved as, replaced and deleted with
+// its ETag so changes made by someone else are not overwritten
+let savedDashboard: { name: string; etag: string } | null = null;
+
 // latest data to be used when reconfiguring charts, and creating charts right after configuring
 let latestSensors: SensorInfo;
 let latestValues: Float64Array;
 let latestPollTime: number;
 
 const hintSensorText = "Every sensor in a chart must have the same unit";
+const hintScatterSensorText =
+    "The first sensor is shown on the x axis, and the second sensor on the y axis";
+
+// chart types using the number of data points
+const dataCountChartTypes: ChartConfig["chartType"][] = [
+    "line",
+    "sparkline",
+    "heatmap",
+    "scatter",
+];
+// chart types using the maximum value
+const maximumValueChartTypes: ChartConfig["chartType"][] = [
+    "gauge",
+    "heatmap",
+    "scatter",
+];
+// chart types with an axis or a range starting at the minimum value
+const minimumValueChartTypes: ChartConfig["chartType"][] = [
+    "bar",
+    "line",
+    "gauge",
+    "sparkline",
+    "heatmap",
+    "scatter",
+];
+// chart types with readings of different units
+const mixedUnitChartTypes: ChartConfig["chartType"][] = ["table", "scatter"];
 
 let preferReducedMotion = window.matchMedia(
     "(prefers-reduced-motion: reduce)",
@@ -168,6 +202,8 @@ Chart.register(
     TimeSeriesScale,
     DoughnutController,
     ArcElement,
+    ScatterController,
+    Filler,
 );
 
 let cachedTimestamp = 0;
@@ -209,17 +245,100 @@ Tooltip.positioners.middle = function (items, _) {
     };
 };
 
-const show = (
+const show = async (
     sensors: SensorInfo,
     values: Float64Array,
     lastPollTime: number,
 ) => {
-    currentSensors = sensors;
     latestSensors = sensors;
     latestValues = values;
     latestPollTime = lastPollTime;
 
-    configure(sensors, []);
+    const groupConfigs =
+        sensors.sensors.length === 0 ? [] : await fetchGeneratedGroupConfigs();
+
+    // the sensors may have been updated while loading
+    currentSensors = latestSensors;
+
+    configure(latestSensors, groupConfigs, groupConfigs.length > 0);
+};
+
+/**
+ * Returns the groups of the dashboard generated from the detected hardware if no dashboard has
+ * been saved, so the first launch shows charts instead of an empty page. Returns an empty array
+ * otherwise, or if the dashboards cannot be listed, e.g. without the viewer role.
+ */
+const fetchGeneratedGroupConfigs = async (): Promise<ChartGroupConfig[]> => {
+    try {
+        const response = await fetch("api/dashboards");
+
+        if (!response.ok || ((await response.json()) as unknown[]).length > 0) {
+            return [];
+        }
+
+        const generatedResponse = await fetch("configs/auto");
+
+        if (!generatedResponse.ok) {
+            return [];
+        }
+
+        return (await generatedResponse.json()) as ChartGroupConfig[];
+    } catch (e) {
+        console.error(e);
+
+        return [];
+    }
+};
+
+/**
+ * Returns the ETag of a dashboard saved on the server, which is required to replace or delete it.
+ * Returns `null` if the dashboard cannot be read.
+ */
+const fetchDashboardEtag = async (name: string): Promise<string | null> => {
+    try {
+        const response = await fetch(
+            `api/dashboards/${encodeURIComponent(name)}`,
+            { method: "HEAD" },
+        );
+
+        return response.ok ? response.headers.get("ETag") : null;
+    } catch (e) {
+        console.error(e);
+
+        return null;
+    }
+};
+
+/**
+ * Returns the text shown when saving or deleting a dashboard is rejected by the server
+ */
+const dashboardErrorText = (
+    status: number,
+    name: string,
+    action: "save" | "delete",
+): string => {
+    const change = action === "save" ? "replace" : "delete";
+
+    switch (status) {
+        case 400:
+            return "The name is reserved and cannot be used by a dashboard";
+        case 401:
+        case 403:
+            return "Saving and deleting dashboards requires the editor role";
+        case 404:
+            return `The name \`${name}\` is not a valid file name`;
+        case 409:
+            return `The dashboard \`${name}\` already exists. Load it to replace it`;
+        case 412:
+        case 428:
+            return `The dashboard \`${name}\` has been changed by someone else. Load it again to ${change} it`;
+        case 413:
+            return "The dashboard exceeds 5 megabytes";
+        case 422:
+            return "The charts are not a valid dashboard";
+        default:
+            return `Failed to ${action} the dashboard`;
+    }
 };
 
 const update = (
@@ -270,18 +389,27 @@ const update = (
                 const unit = chart.datasets[0].unit;
 
                 const chartData = chart.chart.data.datasets[0].data;
+                const barColors = chart.chart.data.datasets[0]
+                    .backgroundColor as string[];
                 for (let k = 0; k < datasetCount; k++) {
                     const index = chart.indices[k];
+                    const dataset = chart.datasets[k];
 
                     if (index === -1) {
                         chartData[k].y = NaN;
+                        barColors[k] = dataset.color;
 
                         continue;
                     }
 
-                    chartData[k].y = values[index];
+                    const currentValue = convertValue(dataset, values[index]);
+
+                    chartData[k].y = currentValue;
+                    barColors[k] =
+                        getThreshold(chart, currentValue)?.color ??
+                        dataset.color;
 
-                    const maxValue = values[index + 1];
+                    const maxValue = convertValue(dataset, values[index + 1]);
                     if (yAxisMax < maxValue) {
                         yAxisMax = maxValue;
                     }
@@ -292,7 +420,9 @@ const update = (
                 } else if (unit === "°C" && yAxisMax <= 100) {
                     chart.chart.options.scales!.y!.max = 100;
                 } else if (unit === "°F") {
-                    chart.chart.options.scales!.y!.min = 32;
+                    if (chart.minimumValue === undefined) {
+                        chart.chart.options.scales!.y!.min = 32;
+                    }
                     if (yAxisMax <= 212) {
                         chart.chart.options.scales!.y!.max = 212;
                     } else {
@@ -311,7 +441,10 @@ const update = (
                 }
 
                 chart.chart.update();
-            } else if (chart.chartType === "line") {
+            } else if (
+                chart.chartType === "line" ||
+                chart.chartType === "sparkline"
+            ) {
                 let yAxisMax = chart.yAxisMax;
                 const unit = chart.datasets[0].unit;
 
@@ -331,12 +464,14 @@ const update = (
                         continue;
                     }
 
+                    const dataset = chart.datasets[k];
+
                     chartData.push({
                         x: lastPollTime,
-                        y: values[index],
+                        y: convertValue(dataset, values[index]),
                     });
 
-                    const maxValue = values[index + 1];
+                    const maxValue = convertValue(dataset, values[index + 1]);
                     if (yAxisMax < maxValue) {
                         yAxisMax = maxValue;
                     }
@@ -347,7 +482,9 @@ const update = (
                 } else if (unit === "°C" && yAxisMax <= 100) {
                     chart.chart.options.scales!.y!.max = 100;
                 } else if (unit === "°F") {
-                    chart.chart.options.scales!.y!.min = 32;
+                    if (chart.minimumValue === undefined) {
+                        chart.chart.options.scales!.y!.min = 32;
+                    }
                     if (yAxisMax <= 212) {
                         chart.chart.options.scales!.y!.max = 212;
                     } else {
@@ -368,22 +505,31 @@ const update = (
                 chart.chart.update();
             } else if (chart.chartType === "gauge") {
                 const index = chart.indices[0];
+                const dataset = chart.datasets[0];
                 const chartData = chart.chart.data.datasets[0].data;
+                const arcColors = chart.chart.data.datasets[0]
+                    .backgroundColor as string[];
+                // the arc starts at the minimum value
+                const minimumValue = chart.minimumValue ?? 0;
 
                 if (index === -1) {
                     chartData[0] = NaN;
-                    chartData[1] = chart.maximumValue;
+                    chartData[1] = chart.maximumValue - minimumValue;
+                    arcColors[0] = dataset.color;
                 } else {
-                    const currentValue = values[index];
+                    const currentValue = convertValue(dataset, values[index]);
 
                     // use max value from HWiNFO if max value from user is wrong
                     let maxValue = chart.maximumValue - currentValue;
                     if (maxValue <= 0) {
-                        maxValue = values[index + 1];
+                        maxValue = convertValue(dataset, values[index + 1]);
                     }
 
-                    chartData[0] = currentValue;
+                    chartData[0] = currentValue - minimumValue;
                     chartData[1] = maxValue;
+                    arcColors[0] =
+                        getThreshold(chart, currentValue)?.color ??
+                        dataset.color;
                 }
 
                 chart.chart.update();
@@ -394,37 +540,135 @@ const update = (
                     if (index === -1) {
                         // only update the current value cell
                         chart.cells[k * 2].textContent = "-";
+                        chart.cells[k * 2].style.color = "";
                         chart.lastValues[k * 2] = -1;
 
                         continue;
                     }
 
-                    const unit = chart.datasets[k].unit;
-                    const currentValue = values[index];
-                    const maxValue = values[index + 1];
+                    const dataset = chart.datasets[k];
+                    const decimals = getDecimals(chart, k);
+                    const currentValue = convertValue(dataset, values[index]);
+                    const maxValue = convertValue(dataset, values[index + 1]);
 
                     if (chart.lastValues[k * 2] !== currentValue) {
-                        chart.cells[k * 2].textContent = formatValue(
+                        const cell = chart.cells[k * 2];
+                        const threshold = getThreshold(chart, currentValue);
+
+                        cell.textContent = formatValue(
                             currentValue,
-                            unit,
+                            dataset.unit,
+                            decimals,
                         );
+                        cell.style.color = threshold?.color ?? "";
+
+                        if (threshold?.label) {
+                            cell.textContent += ` ${threshold.label}`;
+                        }
+
                         chart.lastValues[k * 2] = currentValue;
                     }
 
                     if (chart.lastValues[k * 2 + 1] !== maxValue) {
                         chart.cells[k * 2 + 1].textContent = formatValue(
                             maxValue,
-                            unit,
+                            dataset.unit,
+                            decimals,
                         );
                         chart.lastValues[k * 2 + 1] = maxValue;
                     }
                 }
+            } else if (chart.chartType === "stat") {
+                const index = chart.indices[0];
+                const dataset = chart.datasets[0];
+                const unit = dataset.unit;
+                const decimals = getDecimals(chart, 0);
+                const currentValue =
+                    index === -1 ? NaN : convertValue(dataset, values[index]);
+                const threshold = getThreshold(chart, currentValue);
+
+                chart.value.textContent = formatValue(
+                    currentValue,
+                    unit,
+                    decimals,
+                );
+                chart.value.style.color = threshold?.color ?? dataset.color;
+
+                if (threshold?.label) {
+                    chart.value.textContent += ` ${threshold.label}`;
+                }
+
+                if (chart.trendCount) {
+                    const previousValues = chart.previousValues;
+                    let difference = NaN;
+
+                    if (previousValues.length > 0) {
+                        let sum = 0;
+
+                        for (const value of previousValues) {
+                            sum += value;
+                        }
+
+                        difference = currentValue - sum / previousValues.length;
+                    }
+
+                    chart.trend.textContent = formatTrend(
+                        difference,
+                        unit,
+                        decimals,
+                    );
+
+                    // values of readings which are not found are not used for the trend
+                    if (!Number.isNaN(currentValue)) {
+                        previousValues.push(currentValue);
+
+                        if (previousValues.length > chart.trendCount) {
+                            previousValues.shift();
+                        }
+                    }
+                }
+            } else if (chart.chartType === "heatmap") {
+                const index = chart.indices[0];
+
+                addHeatmapValue(
+                    chart.heatmap,
+                    index === -1
+                        ? NaN
+                        : convertValue(chart.datasets[0], values[index]),
+                );
+                drawHeatmap(chart.heatmap);
+            } else if (chart.chartType === "scatter") {
+                const xIndex = chart.indices[0];
+                const yIndex = chart.indices[1];
+                const chartData = chart.chart.data.datasets[0].data;
+
+                if (xIndex !== -1 && yIndex !== -1) {
+                    chartData.push({
+                        x: convertValue(chart.datasets[0], values[xIndex]),
+                        y: convertValue(chart.datasets[1], values[yIndex]),
+                    });
+
+                    // remove the oldest point
+                    if (chartData.length > chart.dataCount) {
+                        chartData.shift();
+                    }
+                }
+
+                chart.chart.update();
             }
         }
     }
 };
 
-const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
+/**
+ * Shows the configure page with the groups, or the charts of the groups right away if
+ * `isApplied` is true
+ */
+const configure = (
+    sensors: SensorInfo,
+    groupConfigs: ChartGroupConfig[],
+    isApplied = false,
+) => {
     const container = document.createElement("main");
     container.id = "container";
 
@@ -565,6 +809,16 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                 updateChartColors();
             });
 
+            const getGroupConfigs = (): ChartGroupConfig[] =>
+                chartGroupInfo.map((group) => {
+                    return {
+                        gridBreakpoints: group.gridBreakpoints,
+                        chartConfigs: group.charts.map((value) =>
+                            copyChartConfig(value),
+                        ),
+                    };
+                });
+
             const buttonDownload = document.createElement("button");
             buttonDownload.id = "download-button";
             buttonDownload.textContent = "Download config";
@@ -572,27 +826,7 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
             buttonDownload.addEventListener("click", (ev) => {
                 ev.stopPropagation();
 
-                const groupConfigs: ChartGroupConfig[] = chartGroupInfo.map(
-                    (group) => {
-                        return {
-                            gridBreakpoints: group.gridBreakpoints,
-                            chartConfigs: group.charts.map((value) => {
-                                return {
-                                    title: value.title,
-                                    chartType: value.chartType,
-                                    dataCount: value.dataCount,
-                                    maximumValue: value.maximumValue,
-                                    height: value.height,
-                                    animationDuration: value.animationDuration,
-                                    showLegend: value.showLegend,
-                                    showLabels: value.showLabels,
-                                    autoColors: value.autoColors,
-                                    datasets: value.datasets,
-                                };
-                            }),
-                        };
-                    },
-                );
+                const groupConfigs = getGroupConfigs();
 
                 // create an anchor element with data URI, the browser will save the content
                 // as a file when the element is clicked
@@ -622,13 +856,177 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                     Place the config file in \`\\public\\configs\`.`,
             );
 
+            const labelDashboard = createLabel(
+                "Dashboard name on the server",
+                "dashboard-input",
+                false,
+            );
+
+            const inputDashboard = document.createElement("input");
+            inputDashboard.id = "dashboard-input";
+            inputDashboard.type = "text";
+            inputDashboard.value = savedDashboard?.name ?? "";
+            inputDashboard.setAttribute("aria-describedby", "dashboard-hint");
+
+            const hintDashboard = createHint(
+                "dashboard-hint",
+                "Saves the charts to the configs folder of the server, which requires the editor role.",
+            );
+
+            const showDashboardResult = (text: string, isError: boolean) => {
+                inputDashboard.classList.toggle("error", isError);
+                hintDashboard.classList.toggle("error", isError);
+                hintDashboard.textContent = text;
+            };
+
+            const buttonSaveDashboard = document.createElement("button");
+            buttonSaveDashboard.textContent = "Save to server";
+            buttonSaveDashboard.setAttribute(
+                "aria-describedby",
+                "dashboard-hint",
+            );
+
+            const buttonDeleteDashboard = document.createElement("button");
+            buttonDeleteDashboard.textContent = "Delete from server";
+            buttonDeleteDashboard.setAttribute(
+                "aria-describedby",
+                "dashboard-hint",
+            );
+            buttonDeleteDashboard.disabled = savedDashboard === null;
+
+            buttonSaveDashboard.addEventListener("click", async (ev) => {
+                ev.stopPropagation();
+
+                const name = inputDashboard.value.trim();
+
+                if (!name) {
+                    showDashboardResult(
+                        "Enter the name of the dashboard",
+                        true,
+                    );
+
+                    return;
+                }
+
+                // test for \ / : * ? " < > | % &
+                if (new RegExp(/[\\/:%*?"<>|%&]/).test(name)) {
+                    showDashboardResult(
+                        "The name must not contain reserved characters",
+                        true,
+                    );
+
+                    return;
+                }
+
+                // an existing dashboard is only replaced if it has not changed since it was
+                // loaded or saved, otherwise a new dashboard is created
+                const etag =
+                    savedDashboard?.name === name ? savedDashboard.etag : null;
+
+                buttonSaveDashboard.disabled = true;
+
+                try {
+                    const response = await fetch(
+                        `api/dashboards/${encodeURIComponent(name)}`,
+                        {
+                            method: "PUT",
+                            headers:
+                                etag === null
+                                    ? { "If-None-Match": "*" }
+                                    : { "If-Match": etag },
+                            body: JSON.stringify(getGroupConfigs(), null, 4),
+                        },
+                    );
+
+                    if (response.ok) {
+                        const newEtag = response.headers.get("ETag");
+                        savedDashboard =
+                            newEtag === null ? null : { name, etag: newEtag };
+
+                        showDashboardResult(
+                            `The charts are saved as the dashboard \`${name}\``,
+                            false,
+                        );
+                    } else {
+                        console.error(await response.text());
+
+                        showDashboardResult(
+                            dashboardErrorText(response.status, name, "save"),
+                            true,
+                        );
+                    }
+                } catch (e) {
+                    console.error(e);
+
+                    showDashboardResult("Failed to save the dashboard", true);
+                }
+
+                buttonSaveDashboard.disabled = false;
+                buttonDeleteDashboard.disabled = savedDashboard === null;
+            });
+
+            buttonDeleteDashboard.addEventListener("click", async (ev) => {
+                ev.stopPropagation();
+
+                if (savedDashboard === null) {
+                    return;
+                }
+
+                const { name, etag } = savedDashboard;
+
+                buttonDeleteDashboard.disabled = true;
+
+                try {
+                    const response = await fetch(
+                        `api/dashboards/${encodeURIComponent(name)}`,
+                        { method: "DELETE", headers: { "If-Match": etag } },
+                    );
+
+                    if (response.ok || response.status === 404) {
+                        savedDashboard = null;
+
+                        showDashboardResult(
+                            `The dashboard \`${name}\` is deleted. It can be restored from its revisions.`,
+                            false,
+                        );
+                    } else {
+                        console.error(await response.text());
+
+                        showDashboardResult(
+                            dashboardErrorText(response.status, name, "delete"),
+                            true,
+                        );
+                    }
+                } catch (e) {
+                    console.error(e);
+
+                    showDashboardResult("Failed to delete the dashboard", true);
+                }
+
+                buttonDeleteDashboard.disabled = savedDashboard === null;
+            });
+
+            const divDashboardButtons = document.createElement("div");
+            divDashboardButtons.id = "dashboard-buttons";
+            divDashboardButtons.appendChild(buttonSaveDashboard);
+            divDashboardButtons.appendChild(buttonDeleteDashboard);
+
             createDialog(
                 "charts-dialog",
                 "Settings",
                 false,
                 true,
                 true,
-                [labelTheme, selectTheme, buttonDownload, hintDownload],
+                [
+                    labelTheme,
+                    selectTheme,
+                    buttonDownload,
+                    hintDownload,
+                    labelDashboard,
+                    inputDashboard,
+                    divDashboardButtons,
+                    hintDashboard,
+                ],
                 [
                     {
                         id: null,
@@ -640,10 +1038,11 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
 
                             for (const group of chartGroupInfo) {
                                 for (const chart of group.charts) {
-                                    if (
-                                        chart.chartType === "bar" ||
-                                        chart.chartType === "line" ||
-                                        chart.chartType === "gauge"
+                                    if (chart.chartType === "heatmap") {
+                                        chart.resizeObserver.disconnect();
+                                    } else if (
+                                        chart.chartType !== "table" &&
+                                        chart.chartType !== "stat"
                                     ) {
                                         chart.chart.destroy();
                                     }
@@ -651,21 +1050,9 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
 
                                 groupConfigs.push({
                                     gridBreakpoints: group.gridBreakpoints,
-                                    chartConfigs: group.charts.map((value) => {
-                                        return {
-                                            title: value.title,
-                                            chartType: value.chartType,
-                                            dataCount: value.dataCount,
-                                            maximumValue: value.maximumValue,
-                                            height: value.height,
-                                            animationDuration:
-                                                value.animationDuration,
-                                            showLegend: value.showLegend,
-                                            showLabels: value.showLabels,
-                                            autoColors: value.autoColors,
-                                            datasets: value.datasets,
-                                        };
-                                    }),
+                                    chartConfigs: group.charts.map((value) =>
+                                        copyChartConfig(value),
+                                    ),
                                 });
                             }
 
@@ -822,8 +1209,12 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                         }
 
                         try {
+                            // the ETag is read first, so a change made while loading is
+                            // detected when saving
+                            const etag = await fetchDashboardEtag(fileName);
+
                             const response = await fetch(
-                                `/configs/${fileName}`,
+                                `configs/${fileName}`,
                             );
 
                             if (!response.ok) {
@@ -838,6 +1229,11 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                                 const groupConfigs =
                                     (await response.json()) as ChartGroupConfig[];
 
+                                savedDashboard =
+                                    etag === null
+                                        ? null
+                                        : { name: fileName, etag };
+
                                 buttonDone.disabled = false;
                                 divBigButtonGroup.remove();
                                 buttonAddGroup.classList.remove("display-none");
@@ -1446,7 +1842,10 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
         currentConfig.chartType =
             selectType.value as typeof currentConfig.chartType;
 
-        if (fromType === "table") {
+        if (
+            mixedUnitChartTypes.includes(fromType) &&
+            !mixedUnitChartTypes.includes(currentConfig.chartType)
+        ) {
             // other charts cannot have readings with different units
 
             let deleteTables = false;
@@ -1471,6 +1870,21 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
             }
         }
 
+        // remove the readings which cannot be shown by the chart type
+        const maximumDatasetCount = getMaximumDatasetCount(
+            currentConfig.chartType,
+        );
+
+        while (currentConfig.datasets.length > maximumDatasetCount) {
+            currentConfig.datasets.pop();
+            addedSensorButtonGroups.pop();
+
+            listSensors.children[currentConfig.datasets.length].remove();
+        }
+
+        updateAddedSensorButtonGroups();
+        updateListSensorsLabel();
+
         updateConfigureChartDialog(
             currentConfig.chartType,
             labelDataCount,
@@ -1479,6 +1893,9 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
             labelMaximumValue,
             inputMaximumValue,
             hintMaximumValue,
+            labelMinimumValue,
+            inputMinimumValue,
+            hintMinimumValue,
             labelAnimation,
             inputAnimation,
             hintAnimation,
@@ -1513,11 +1930,39 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
 
             currentConfig.autoColors = false;
             inputAutoColors.checked = false;
+        } else if (currentConfig.chartType === "stat") {
+            inputTrendCount.value = "";
+            inputShowLegend.checked = false;
+            inputShowLabels.checked = false;
+
+            currentConfig.autoColors = false;
+            inputAutoColors.checked = false;
+        } else if (currentConfig.chartType === "sparkline") {
+            inputDataCount.value = "30";
+            inputFill.checked = false;
+            inputShowLegend.checked = false;
+            inputShowLabels.checked = false;
+
+            currentConfig.autoColors = true;
+            inputAutoColors.checked = true;
+        } else if (currentConfig.chartType === "heatmap") {
+            inputDataCount.value = "60";
+            inputMaximumValue.value = "";
+            inputBucketCount.value = "10";
+            inputShowLegend.checked = false;
+            inputShowLabels.checked = false;
 
-            currentConfig.datasets = [];
-            addedSensorButtonGroups = [];
+            currentConfig.autoColors = false;
+            inputAutoColors.checked = false;
+        } else if (currentConfig.chartType === "scatter") {
+            inputDataCount.value = "60";
+            inputMaximumValue.value = "";
+            inputXMaximumValue.value = "";
+            inputShowLegend.checked = false;
+            inputShowLabels.checked = false;
 
-            listSensors.replaceChildren(listSensors.lastChild!);
+            currentConfig.autoColors = false;
+            inputAutoColors.checked = false;
         } else {
             inputShowLegend.checked = false;
             inputShowLabels.checked = false;
@@ -1563,8 +2008,20 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
     selectType.appendChild(
         createSelectOption("Table", "table", false, false, false),
     );
+    selectType.appendChild(
+        createSelectOption("Stat", "stat", false, false, false),
+    );
+    selectType.appendChild(
+        createSelectOption("Sparkline", "sparkline", false, false, false),
+    );
+    selectType.appendChild(
+        createSelectOption("Heatmap", "heatmap", false, false, false),
+    );
+    selectType.appendChild(
+        createSelectOption("Scatter", "scatter", false, false, false),
+    );
 
-    // only shown for line charts
+    // only shown for line, sparkline, heatmap and scatter charts
     const labelDataCount = createLabel(
         "Number of data points",
         "chart-data-count-input",
@@ -1583,7 +2040,7 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
         "Minimum number of data points must be 5. Chart rendering performance may degrade if the number is set too high.",
     );
 
-    // only shown for gauge charts
+    // only shown for gauge, heatmap and scatter charts
     const labelMaximumValue = createLabel(
         "Maximum value",
         "chart-maximum-value-input",
@@ -1599,8 +2056,89 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
 
     const hintMaximumValue = createHint(
         "chart-maximum-value-hint",
-        "The maximum value of the added sensor. The gauge chart may not work correctly if the maximum value is wrong.",
+        "The maximum value of the added sensor. Gauge and heatmap charts may not work correctly if the maximum value is wrong. Optional for scatter charts, where it is the maximum of the y axis.",
+    );
+
+    // not shown for tables and stat charts
+    const labelMinimumValue = createLabel(
+        "Minimum value",
+        "chart-minimum-value-input",
+        false,
+    );
+    labelMinimumValue.id = "chart-minimum-value-label";
+
+    const inputMinimumValue = createNumberInput(
+        "chart-minimum-value-input",
+        "",
+        "chart-minimum-value-hint",
+    );
+
+    const hintMinimumValue = createHint(
+        "chart-minimum-value-hint",
+        "Optional. The lower end of the y axis, the gauge or the ranges of a heatmap, 0 if it is not set. Must be less than the maximum value.",
+    );
+
+    // the inputs below are only shown for the chart type in `data-chart-type`
+
+    const labelXMaximumValue = createLabel(
+        "Maximum value of the x axis",
+        "chart-x-maximum-value-input",
+        false,
+    );
+    labelXMaximumValue.dataset.chartType = "scatter";
+
+    const inputXMaximumValue = createNumberInput(
+        "chart-x-maximum-value-input",
+        "",
+        "chart-x-maximum-value-hint",
+    );
+    inputXMaximumValue.dataset.chartType = "scatter";
+
+    const hintXMaximumValue = createHint(
+        "chart-x-maximum-value-hint",
+        "Optional. The maximum value of the first sensor, the x axis is scaled to the values if it is not set.",
+    );
+    hintXMaximumValue.dataset.chartType = "scatter";
+
+    const labelBucketCount = createLabel(
+        "Number of ranges",
+        "chart-bucket-count-input",
+        false,
+    );
+    labelBucketCount.dataset.chartType = "heatmap";
+
+    const inputBucketCount = createNumberInput(
+        "chart-bucket-count-input",
+        "",
+        "chart-bucket-count-hint",
+    );
+    inputBucketCount.dataset.chartType = "heatmap";
+
+    const hintBucketCount = createHint(
+        "chart-bucket-count-hint",
+        "Between 1 and 100 inclusive. The values from the minimum value to the maximum value are counted in this number of ranges.",
+    );
+    hintBucketCount.dataset.chartType = "heatmap";
+
+    const labelTrendCount = createLabel(
+        "Number of values for the trend",
+        "chart-trend-count-input",
+        false,
+    );
+    labelTrendCount.dataset.chartType = "stat";
+
+    const inputTrendCount = createNumberInput(
+        "chart-trend-count-input",
+        "",
+        "chart-trend-count-hint",
+    );
+    inputTrendCount.dataset.chartType = "stat";
+
+    const hintTrendCount = createHint(
+        "chart-trend-count-hint",
+        "Optional. The trend shows the difference between the current value and the average of this number of previous values.",
     );
+    hintTrendCount.dataset.chartType = "stat";
 
     const labelHeight = createLabel(
         "Height (pixels)",
@@ -1638,6 +2176,40 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
         "Between 0 and 1000 inclusive. Set to 0 to disable animation. Lower the value to improve chart rendering performance.",
     );
 
+    const labelDecimals = createLabel(
+        "Decimal places",
+        "chart-decimals-input",
+        false,
+    );
+
+    const inputDecimals = createNumberInput(
+        "chart-decimals-input",
+        "",
+        "chart-decimals-hint",
+    );
+
+    const hintDecimals = createHint(
+        "chart-decimals-hint",
+        "Optional. Between 0 and 6 inclusive. The number of decimal places depends on the unit if it is not set.",
+    );
+
+    const labelThresholds = createLabel(
+        "Thresholds",
+        "chart-thresholds-input",
+        false,
+    );
+
+    const inputThresholds = document.createElement("input");
+    inputThresholds.id = "chart-thresholds-input";
+    inputThresholds.type = "text";
+    inputThresholds.placeholder = "0 #4cb140, 80 #c9190b Hot";
+    inputThresholds.setAttribute("aria-describedby", "chart-thresholds-hint");
+
+    const hintThresholds = createHint(
+        "chart-thresholds-hint",
+        "Optional. Values from low to high separated by commas, each followed by a color and an optional label. A value and the values above it up to the next threshold are shown in its color.",
+    );
+
     // only shown for bar and line charts
     const divShowLegend = document.createElement("div");
 
@@ -1674,7 +2246,24 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
     divShowLabels.appendChild(inputShowLabels);
     divShowLabels.appendChild(labelShowLabels);
 
-    // only shown for bar and line charts
+    const divFill = document.createElement("div");
+    divFill.dataset.chartType = "sparkline";
+
+    const inputFill = document.createElement("input");
+    inputFill.id = "chart-fill-input";
+    inputFill.type = "checkbox";
+
+    const labelFill = createLabel(
+        "Fill the area under the line",
+        "chart-fill-input",
+        false,
+    );
+    labelFill.classList.add("label-checkbox", "label-checkbox-margin");
+
+    divFill.appendChild(inputFill);
+    divFill.appendChild(labelFill);
+
+    // only shown for bar, line and sparkline charts
     const divAutoColors = document.createElement("div");
 
     const inputAutoColors = document.createElement("input");
@@ -1765,7 +2354,7 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
 
             let add = true;
             if (
-                currentConfig.chartType !== "table" &&
+                !mixedUnitChartTypes.includes(currentConfig.chartType) &&
                 currentConfig.datasets.length > 0
             ) {
                 add = currentConfig.datasets[0].unit === unit;
@@ -1791,7 +2380,7 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                     inputLabel.value = "";
                     inputLabel.disabled = false;
 
-                    hintSensor.textContent = hintSensorText;
+                    hintSensor.textContent = getHintSensorText();
 
                     buttonAddSensor.disabled = false;
                 }
@@ -1926,14 +2515,33 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
             labelMaximumValue,
             inputMaximumValue,
             hintMaximumValue,
+            labelMinimumValue,
+            inputMinimumValue,
+            hintMinimumValue,
+            labelXMaximumValue,
+            inputXMaximumValue,
+            hintXMaximumValue,
+            labelBucketCount,
+            inputBucketCount,
+            hintBucketCount,
+            labelTrendCount,
+            inputTrendCount,
+            hintTrendCount,
             labelHeight,
             inputHeight,
             hintHeight,
             labelAnimation,
             inputAnimation,
             hintAnimation,
+            labelDecimals,
+            inputDecimals,
+            hintDecimals,
+            labelThresholds,
+            inputThresholds,
+            hintThresholds,
             divShowLegend,
             divShowLabels,
+            divFill,
             divAutoColors,
             labelSensors,
             listSensors,
@@ -1972,10 +2580,11 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                     // a default value is used if an input is not required, to pass the validation
 
                     let dataCountNumber: number;
-                    const dataCountString =
-                        currentConfig.chartType === "line"
-                            ? inputDataCount.value.trim()
-                            : "5";
+                    const dataCountString = dataCountChartTypes.includes(
+                        currentConfig.chartType,
+                    )
+                        ? inputDataCount.value.trim()
+                        : "5";
 
                     if (dataCountString) {
                         dataCountNumber = Number(dataCountString);
@@ -2001,10 +2610,11 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                     }
 
                     let maximumValueNumber: number;
-                    const maximumValueString =
-                        currentConfig.chartType === "gauge"
-                            ? inputMaximumValue.value.trim()
-                            : "1";
+                    const maximumValueString = maximumValueChartTypes.includes(
+                        currentConfig.chartType,
+                    )
+                        ? inputMaximumValue.value.trim()
+                        : "1";
 
                     if (maximumValueString) {
                         maximumValueNumber = Number(maximumValueString);
@@ -2021,6 +2631,12 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                             inputMaximumValue.classList.remove("error");
                             hintMaximumValue.classList.remove("error");
                         }
+                    } else if (currentConfig.chartType === "scatter") {
+                        // the y axis is scaled to the values
+                        maximumValueNumber = 0;
+
+                        inputMaximumValue.classList.remove("error");
+                        hintMaximumValue.classList.remove("error");
                     } else {
                         inputMaximumValue.classList.add("error");
                         hintMaximumValue.classList.add("error");
@@ -2054,51 +2670,230 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
                         hasError = true;
                     }
 
-                    let animationNumber: number;
-                    const animationString =
-                        currentConfig.chartType === "table"
-                            ? "0"
-                            : inputAnimation.value.trim();
+                    // optional, only used by scatter charts
+                    let xMaximumValueNumber: number | undefined;
+                    const xMaximumValueString =
+                        currentConfig.chartType === "scatter"
+                            ? inputXMaximumValue.value.trim()
+                            : "";
 
-                    if (animationString) {
-                        animationNumber = Number(animationString);
+                    if (xMaximumValueString) {
+                        xMaximumValueNumber = Number(xMaximumValueString);
 
                         if (
-                            Number.isNaN(animationNumber) ||
-                            !Number.isInteger(animationNumber) ||
-                            animationNumber < 0 ||
-                            animationNumber > 1000
+                            Number.isNaN(xMaximumValueNumber) ||
+                            xMaximumValueNumber <= 0
                         ) {
-                            inputAnimation.classList.add("error");
-                            hintAnimation.classList.add("error");
+                            inputXMaximumValue.classList.add("error");
+                            hintXMaximumValue.classList.add("error");
 
                             hasError = true;
                         } else {
-                            inputAnimation.classList.remove("error");
-                            hintAnimation.classList.remove("error");
+                            inputXMaximumValue.classList.remove("error");
+                            hintXMaximumValue.classList.remove("error");
                         }
                     } else {
-                        inputAnimation.classList.add("error");
-                        hintAnimation.classList.add("error");
-
-                        hasError = true;
+                        inputXMaximumValue.classList.remove("error");
+                        hintXMaximumValue.classList.remove("error");
                     }
 
-                    if (!hasError) {
-                        currentConfig.title = title;
-                        currentConfig.dataCount =
-                            currentConfig.chartType === "line"
-                                ? dataCountNumber!
-                                : 0;
-                        currentConfig.maximumValue =
-                            currentConfig.chartType === "gauge"
-                                ? maximumValueNumber!
-                                : 0;
+                    let bucketCountNumber: number;
+                    const bucketCountString =
+                        currentConfig.chartType === "heatmap"
+                            ? inputBucketCount.value.trim()
+                            : "1";
+
+                    if (bucketCountString) {
+                        bucketCountNumber = Number(bucketCountString);
+
+                        if (
+                            Number.isNaN(bucketCountNumber) ||
+                            !Number.isInteger(bucketCountNumber) ||
+                            bucketCountNumber < 1 ||
+                            bucketCountNumber > 100
+                        ) {
+                            inputBucketCount.classList.add("error");
+                            hintBucketCount.classList.add("error");
+
+                            hasError = true;
+                        } else {
+                            inputBucketCount.classList.remove("error");
+                            hintBucketCount.classList.remove("error");
+                        }
+                    } else {
+                        inputBucketCount.classList.add("error");
+                        hintBucketCount.classList.add("error");
+
+                        hasError = true;
+                    }
+
+                    // optional, only used by stat charts
+                    let trendCountNumber: number | undefined;
+                    const trendCountString =
+                        currentConfig.chartType === "stat"
+                            ? inputTrendCount.value.trim()
+                            : "";
+
+                    if (trendCountString) {
+                        trendCountNumber = Number(trendCountString);
+
+                        if (
+                            Number.isNaN(trendCountNumber) ||
+                            !Number.isInteger(trendCountNumber) ||
+                            trendCountNumber < 1
+                        ) {
+                            inputTrendCount.classList.add("error");
+                            hintTrendCount.classList.add("error");
+
+                            hasError = true;
+                        } else {
+                            inputTrendCount.classList.remove("error");
+                            hintTrendCount.classList.remove("error");
+                        }
+                    } else {
+                        inputTrendCount.classList.remove("error");
+                        hintTrendCount.classList.remove("error");
+                    }
+
+                    // optional, the server also checks it against the maximum value
+                    let minimumValueNumber: number | undefined;
+                    const minimumValueString = minimumValueChartTypes.includes(
+                        currentConfig.chartType,
+                    )
+                        ? inputMinimumValue.value.trim()
+                        : "";
+
+                    if (minimumValueString) {
+                        minimumValueNumber = Number(minimumValueString);
+
+                        // the maximum value is 0 for scatter charts scaled to the values
+                        const hasMaximumValue =
+                            maximumValueChartTypes.includes(
+                                currentConfig.chartType,
+                            ) && maximumValueNumber! > 0;
+
+                        if (
+                            Number.isNaN(minimumValueNumber) ||
+                            (hasMaximumValue &&
+                                minimumValueNumber >= maximumValueNumber!)
+                        ) {
+                            inputMinimumValue.classList.add("error");
+                            hintMinimumValue.classList.add("error");
+
+                            hasError = true;
+                        } else {
+                            inputMinimumValue.classList.remove("error");
+                            hintMinimumValue.classList.remove("error");
+                        }
+                    } else {
+                        inputMinimumValue.classList.remove("error");
+                        hintMinimumValue.classList.remove("error");
+                    }
+
+                    // optional
+                    let decimalsNumber: number | undefined;
+                    const decimalsString = inputDecimals.value.trim();
+
+                    if (decimalsString) {
+                        decimalsNumber = Number(decimalsString);
+
+                        if (
+                            Number.isNaN(decimalsNumber) ||
+                            !Number.isInteger(decimalsNumber) ||
+                            decimalsNumber < 0 ||
+                            decimalsNumber > 6
+                        ) {
+                            inputDecimals.classList.add("error");
+                            hintDecimals.classList.add("error");
+
+                            hasError = true;
+                        } else {
+                            inputDecimals.classList.remove("error");
+                            hintDecimals.classList.remove("error");
+                        }
+                    } else {
+                        inputDecimals.classList.remove("error");
+                        hintDecimals.classList.remove("error");
+                    }
+
+                    // optional, an empty array if the input is empty
+                    const thresholds = parseThresholds(inputThresholds.value);
+
+                    if (thresholds === null) {
+                        inputThresholds.classList.add("error");
+                        hintThresholds.classList.add("error");
+
+                        hasError = true;
+                    } else {
+                        inputThresholds.classList.remove("error");
+                        hintThresholds.classList.remove("error");
+                    }
+
+                    let animationNumber: number;
+                    const animationString =
+                        currentConfig.chartType === "table" ||
+                        currentConfig.chartType === "stat" ||
+                        currentConfig.chartType === "heatmap"
+                            ? "0"
+                            : inputAnimation.value.trim();
+
+                    if (animationString) {
+                        animationNumber = Number(animationString);
+
+                        if (
+                            Number.isNaN(animationNumber) ||
+                            !Number.isInteger(animationNumber) ||
+                            animationNumber < 0 ||
+                            animationNumber > 1000
+                        ) {
+                            inputAnimation.classList.add("error");
+                            hintAnimation.classList.add("error");
+
+                            hasError = true;
+                        } else {
+                            inputAnimation.classList.remove("error");
+                            hintAnimation.classList.remove("error");
+                        }
+                    } else {
+                        inputAnimation.classList.add("error");
+                        hintAnimation.classList.add("error");
+
+                        hasError = true;
+                    }
+
+                    if (!hasError) {
+                        const chartType = currentConfig.chartType;
+
+                        currentConfig.title = title;
+                        currentConfig.dataCount = dataCountChartTypes.includes(
+                            chartType,
+                        )
+                            ? dataCountNumber!
+                            : 0;
+                        currentConfig.maximumValue =
+                            maximumValueChartTypes.includes(chartType)
+                                ? maximumValueNumber!
+                                : 0;
                         currentConfig.height = heightNumber!;
                         currentConfig.animationDuration = animationNumber!;
                         currentConfig.showLegend = inputShowLegend.checked;
                         currentConfig.showLabels = inputShowLabels.checked;
                         currentConfig.autoColors = inputAutoColors.checked;
+                        // the fields of other chart types are rejected by the server
+                        currentConfig.trendCount = trendCountNumber;
+                        currentConfig.fill =
+                            chartType === "sparkline"
+                                ? inputFill.checked
+                                : undefined;
+                        currentConfig.bucketCount =
+                            chartType === "heatmap"
+                                ? bucketCountNumber!
+                                : undefined;
+                        currentConfig.xMaximumValue = xMaximumValueNumber;
+                        currentConfig.minimumValue = minimumValueNumber;
+                        currentConfig.decimals = decimalsNumber;
+                        currentConfig.thresholds =
+                            thresholds!.length > 0 ? thresholds! : undefined;
 
                         const groupConfig =
                             chartGroupConfigs[currentGroupConfigIndex];
@@ -2157,6 +2952,12 @@ const configure = (sensors: SensorInfo, groupConfigs: ChartGroupConfig[]) => {
     if (groupConfigs.length > 0) {
         // skip the add load buttons selection
         buttonAddChart.click();
+
+        if (isApplied) {
+            buttonDone.click();
+
+            return;
+        }
     }
 
     requestAnimationFrame(() => {
@@ -2488,6 +3289,55 @@ const onClickAddEditChart = (ev: MouseEvent) => {
         "chart-auto-color-label",
     ) as HTMLLabelElement;
 
+    const inputXMaximumValue = document.getElementById(
+        "chart-x-maximum-value-input",
+    ) as HTMLInputElement;
+    const hintXMaximumValue = document.getElementById(
+        "chart-x-maximum-value-hint",
+    ) as HTMLElement;
+
+    const inputBucketCount = document.getElementById(
+        "chart-bucket-count-input",
+    ) as HTMLInputElement;
+    const hintBucketCount = document.getElementById(
+        "chart-bucket-count-hint",
+    ) as HTMLElement;
+
+    const inputTrendCount = document.getElementById(
+        "chart-trend-count-input",
+    ) as HTMLInputElement;
+    const hintTrendCount = document.getElementById(
+        "chart-trend-count-hint",
+    ) as HTMLElement;
+
+    const inputFill = document.getElementById(
+        "chart-fill-input",
+    ) as HTMLInputElement;
+
+    const labelMinimumValue = document.getElementById(
+        "chart-minimum-value-label",
+    ) as HTMLLabelElement;
+    const inputMinimumValue = document.getElementById(
+        "chart-minimum-value-input",
+    ) as HTMLInputElement;
+    const hintMinimumValue = document.getElementById(
+        "chart-minimum-value-hint",
+    ) as HTMLElement;
+
+    const inputDecimals = document.getElementById(
+        "chart-decimals-input",
+    ) as HTMLInputElement;
+    const hintDecimals = document.getElementById(
+        "chart-decimals-hint",
+    ) as HTMLElement;
+
+    const inputThresholds = document.getElementById(
+        "chart-thresholds-input",
+    ) as HTMLInputElement;
+    const hintThresholds = document.getElementById(
+        "chart-thresholds-hint",
+    ) as HTMLElement;
+
     const listSensors = document.getElementById(
         "sensors-list",
     ) as HTMLOListElement;
@@ -2510,6 +3360,24 @@ const onClickAddEditChart = (ev: MouseEvent) => {
     inputAnimation.classList.remove("error");
     hintAnimation.classList.remove("error");
 
+    inputXMaximumValue.classList.remove("error");
+    hintXMaximumValue.classList.remove("error");
+
+    inputBucketCount.classList.remove("error");
+    hintBucketCount.classList.remove("error");
+
+    inputTrendCount.classList.remove("error");
+    hintTrendCount.classList.remove("error");
+
+    inputMinimumValue.classList.remove("error");
+    hintMinimumValue.classList.remove("error");
+
+    inputDecimals.classList.remove("error");
+    hintDecimals.classList.remove("error");
+
+    inputThresholds.classList.remove("error");
+    hintThresholds.classList.remove("error");
+
     listSensors.replaceChildren(listSensors.lastChild!);
 
     if (action === "add") {
@@ -2543,18 +3411,7 @@ const onClickAddEditChart = (ev: MouseEvent) => {
                 currentConfigIndex
             ];
 
-        currentConfig = {
-            title: config.title,
-            chartType: config.chartType,
-            dataCount: config.dataCount,
-            maximumValue: config.maximumValue,
-            height: config.height,
-            animationDuration: config.animationDuration,
-            showLegend: config.showLegend,
-            showLabels: config.showLabels,
-            autoColors: config.autoColors,
-            datasets: config.datasets,
-        };
+        currentConfig = copyChartConfig(config);
 
         for (let i = 0; i < currentConfig.datasets.length; i++) {
             const dataset = currentConfig.datasets[i];
@@ -2608,6 +3465,13 @@ const onClickAddEditChart = (ev: MouseEvent) => {
     inputShowLegend.checked = currentConfig.showLegend;
     inputShowLabels.checked = currentConfig.showLabels;
     inputAutoColors.checked = currentConfig.autoColors;
+    inputXMaximumValue.value = currentConfig.xMaximumValue?.toString() ?? "";
+    inputBucketCount.value = currentConfig.bucketCount?.toString() ?? "";
+    inputTrendCount.value = currentConfig.trendCount?.toString() ?? "";
+    inputFill.checked = currentConfig.fill === true;
+    inputMinimumValue.value = currentConfig.minimumValue?.toString() ?? "";
+    inputDecimals.value = currentConfig.decimals?.toString() ?? "";
+    inputThresholds.value = formatThresholds(currentConfig.thresholds ?? []);
 
     updateConfigureChartDialog(
         currentConfig.chartType,
@@ -2617,6 +3481,9 @@ const onClickAddEditChart = (ev: MouseEvent) => {
         labelMaximumValue,
         inputMaximumValue,
         hintMaximumValue,
+        labelMinimumValue,
+        inputMinimumValue,
+        hintMinimumValue,
         labelAnimation,
         inputAnimation,
         hintAnimation,
@@ -2780,6 +3647,9 @@ const updateConfigureChartDialog = (
     labelMaximumValue: HTMLLabelElement,
     inputMaximumValue: HTMLInputElement,
     hintMaximumValue: HTMLElement,
+    labelMinimumValue: HTMLLabelElement,
+    inputMinimumValue: HTMLInputElement,
+    hintMinimumValue: HTMLElement,
     labelAnimation: HTMLLabelElement,
     inputAnimation: HTMLInputElement,
     hintAnimation: HTMLElement,
@@ -2793,71 +3663,51 @@ const updateConfigureChartDialog = (
 ) => {
     // hide or show elements depending on the chart type
 
-    if (chartType === "bar" || chartType === "line") {
-        if (currentConfig.chartType === "bar") {
-            labelDataCount.classList.add("display-none");
-            inputDataCount.classList.add("display-none");
-            hintDataCount.classList.add("display-none");
-
-            labelShowLabels.classList.remove("display-none");
-            inputShowLabels.classList.remove("display-none");
-        } else {
-            labelDataCount.classList.remove("display-none");
-            inputDataCount.classList.remove("display-none");
-            hintDataCount.classList.remove("display-none");
-
-            labelShowLabels.classList.add("display-none");
-            inputShowLabels.classList.add("display-none");
-        }
-
-        labelMaximumValue.classList.add("display-none");
-        inputMaximumValue.classList.add("display-none");
-        hintMaximumValue.classList.add("display-none");
-
-        labelAnimation.classList.remove("display-none");
-        inputAnimation.classList.remove("display-none");
-        hintAnimation.classList.remove("display-none");
-
-        labelShowLegend.classList.remove("display-none");
-        inputShowLegend.classList.remove("display-none");
-
-        inputAutoColors.classList.remove("display-none");
-        labelAutoColors.classList.remove("display-none");
-
-        hintSensor.classList.remove("display-none");
-    } else {
-        if (chartType === "gauge") {
-            labelMaximumValue.classList.remove("display-none");
-            inputMaximumValue.classList.remove("display-none");
-            hintMaximumValue.classList.remove("display-none");
-
-            labelAnimation.classList.remove("display-none");
-            inputAnimation.classList.remove("display-none");
-            hintAnimation.classList.remove("display-none");
-        } else {
-            labelMaximumValue.classList.add("display-none");
-            inputMaximumValue.classList.add("display-none");
-            hintMaximumValue.classList.add("display-none");
-
-            labelAnimation.classList.add("display-none");
-            inputAnimation.classList.add("display-none");
-            hintAnimation.classList.add("display-none");
-        }
-
-        labelDataCount.classList.add("display-none");
-        inputDataCount.classList.add("display-none");
-        hintDataCount.classList.add("display-none");
-
-        labelShowLegend.classList.add("display-none");
-        inputShowLegend.classList.add("display-none");
-
-        labelShowLabels.classList.add("display-none");
-        inputShowLabels.classList.add("display-none");
-
-        inputAutoColors.classList.add("display-none");
-        labelAutoColors.classList.add("display-none");
-
-        hintSensor.classList.add("display-none");
+    const isDataCountShown = dataCountChartTypes.includes(chartType);
+    const isMaximumValueShown = maximumValueChartTypes.includes(chartType);
+    const isMinimumValueShown = minimumValueChartTypes.includes(chartType);
+    const isAnimationShown =
+        chartType !== "table" &&
+        chartType !== "stat" &&
+        chartType !== "heatmap";
+    const isShowLegendShown = chartType === "bar" || chartType === "line";
+    const isAutoColorsShown = isShowLegendShown || chartType === "sparkline";
+
+    labelDataCount.classList.toggle("display-none", !isDataCountShown);
+    inputDataCount.classList.toggle("display-none", !isDataCountShown);
+    hintDataCount.classList.toggle("display-none", !isDataCountShown);
+
+    labelMaximumValue.classList.toggle("display-none", !isMaximumValueShown);
+    inputMaximumValue.classList.toggle("display-none", !isMaximumValueShown);
+    hintMaximumValue.classList.toggle("display-none", !isMaximumValueShown);
+
+    labelMinimumValue.classList.toggle("display-none", !isMinimumValueShown);
+    inputMinimumValue.classList.toggle("display-none", !isMinimumValueShown);
+    hintMinimumValue.classList.toggle("display-none", !isMinimumValueShown);
+
+    labelAnimation.classList.toggle("display-none", !isAnimationShown);
+    inputAnimation.classList.toggle("display-none", !isAnimationShown);
+    hintAnimation.classList.toggle("display-none", !isAnimationShown);
+
+    labelShowLegend.classList.toggle("display-none", !isShowLegendShown);
+    inputShowLegend.classList.toggle("display-none", !isShowLegendShown);
+
+    labelShowLabels.classList.toggle("display-none", chartType !== "bar");
+    inputShowLabels.classList.toggle("display-none", chartType !== "bar");
+
+    inputAutoColors.classList.toggle("display-none", !isAutoColorsShown);
+    labelAutoColors.classList.toggle("display-none", !isAutoColorsShown);
+
+    hintSensor.classList.toggle("display-none", chartType === "table");
+
+    // the inputs of the fields supported by a single chart type
+    for (const element of document.querySelectorAll<HTMLElement>(
+        "#configure-chart-dialog [data-chart-type]",
+    )) {
+        element.classList.toggle(
+            "display-none",
+            element.dataset.chartType !== chartType,
+        );
     }
 };
 
@@ -3099,15 +3949,89 @@ const updateListSensorsLabel = () => {
 
 const getColor = (index: number): string => colors[index % colors.length];
 
+/**
+ * Returns the fields of a chart config, without the elements and the chart of a preview or an
+ * active chart
+ */
+const copyChartConfig = (config: ChartConfig): ChartConfig => {
+    return {
+        title: config.title,
+        chartType: config.chartType,
+        dataCount: config.dataCount,
+        maximumValue: config.maximumValue,
+        height: config.height,
+        animationDuration: config.animationDuration,
+        showLegend: config.showLegend,
+        showLabels: config.showLabels,
+        autoColors: config.autoColors,
+        datasets: config.datasets,
+        trendCount: config.trendCount,
+        fill: config.fill,
+        bucketCount: config.bucketCount,
+        xMaximumValue: config.xMaximumValue,
+        minimumValue: config.minimumValue,
+        thresholds: config.thresholds,
+        decimals: config.decimals,
+    };
+};
+
+/**
+ * Returns the thresholds in the text of the thresholds input, e.g. "0 #4cb140, 80 #c9190b Hot",
+ * or null if the text is not valid or the values are not in ascending order
+ */
+const parseThresholds = (text: string): Threshold[] | null => {
+    const thresholds: Threshold[] = [];
+
+    for (const item of text.split(",")) {
+        const parts = item.trim().split(/\s+/);
+
+        // skip empty items, e.g. after a trailing comma
+        if (parts.length === 1 && parts[0] === "") {
+            continue;
+        }
+
+        const value = Number(parts[0]);
+
+        if (parts.length < 2 || Number.isNaN(value)) {
+            return null;
+        }
+
+        const last = thresholds[thresholds.length - 1];
+
+        if (last && last.value >= value) {
+            return null;
+        }
+
+        thresholds.push({
+            value,
+            color: parts[1],
+            label: parts.length > 2 ? parts.slice(2).join(" ") : undefined,
+        });
+    }
+
+    return thresholds;
+};
+
+/**
+ * Returns the text of the thresholds input for the thresholds
+ */
+const formatThresholds = (thresholds: Threshold[]): string =>
+    thresholds
+        .map((x) =>
+            x.label
+                ? `${x.value} ${x.color} ${x.label}`
+                : `${x.value} ${x.color}`,
+        )
+        .join(", ");
+
 const resetAddSensorListItem = () => {
     const selectSensorGroup = document.getElementById(
         "dataset-sensor-group-select",
     ) as HTMLSelectElement;
     selectSensorGroup.selectedIndex = 0;
-    // only one reading can be added to gauge charts
     selectSensorGroup.disabled =
-        currentConfig.chartType === "gauge" &&
-        currentConfig.datasets.length > 0;
+        currentConfig.datasets.length >=
+        getMaximumDatasetCount(currentConfig.chartType);
 
     const selectSensor = document.getElementById(
         "dataset-sensor-select",
@@ -3118,7 +4042,7 @@ const resetAddSensorListItem = () => {
     const hintSensor = document.getElementById(
         "dataset-sensor-hint",
     ) as HTMLElement;
-    hintSensor.textContent = hintSensorText;
+    hintSensor.textContent = getHintSensorText();
 
     const inputLabel = document.getElementById(
         "dataset-label-input",
@@ -3136,9 +4060,35 @@ const resetAddSensorListItem = () => {
         document.getElementById(
             "configure-chart-add-button",
         ) as HTMLButtonElement
-    ).disabled = currentConfig.datasets.length === 0;
+    ).disabled =
+        currentConfig.datasets.length <
+        (currentConfig.chartType === "scatter" ? 2 : 1);
+};
+
+/**
+ * Returns the number of readings which can be added to a chart of the type
+ */
+const getMaximumDatasetCount = (
+    chartType: ChartConfig["chartType"],
+): number => {
+    switch (chartType) {
+        case "gauge":
+        case "stat":
+        case "heatmap":
+            return 1;
+        case "scatter":
+            // the x and y axes
+            return 2;
+        default:
+            return Infinity;
+    }
 };
 
+const getHintSensorText = (): string =>
+    currentConfig.chartType === "scatter"
+        ? hintScatterSensorText
+        : hintSensorText;
+
 const createChartContainer = (
     chartConfig: ChartConfig,
     groupIndex: number,
@@ -3219,16 +4169,7 @@ const createChartContainer = (
         container.appendChild(dropdownDiv);
 
         chartGroupConfigs[groupIndex].chartConfigs[chartConfigIndex] = {
-            title: chartConfig.title,
-            chartType: chartConfig.chartType,
-            dataCount: chartConfig.dataCount,
-            maximumValue: chartConfig.maximumValue,
-            height: chartConfig.height,
-            animationDuration: chartConfig.animationDuration,
-            showLegend: chartConfig.showLegend,
-            showLabels: chartConfig.showLabels,
-            autoColors: chartConfig.autoColors,
-            datasets: chartConfig.datasets,
+            ...copyChartConfig(chartConfig),
             dropdownButton: dropdownButton,
             dropdownMenu: dropdownMenu,
             dropdownItems: {
@@ -3276,7 +4217,8 @@ const createChartContainer = (
         const cells = [];
         const lastValues = [];
 
-        for (const dataset of chartConfig.datasets) {
+        for (let i = 0; i < chartConfig.datasets.length; i++) {
+            const dataset = chartConfig.datasets[i];
             const tr = document.createElement("tr");
 
             const th = document.createElement("th");
@@ -3286,7 +4228,11 @@ const createChartContainer = (
             const tdCurrent = document.createElement("td");
             const tdMaximum = document.createElement("td");
 
-            const value = formatValue(getRandomNumber(), dataset.unit);
+            const value = formatValue(
+                getRandomNumber(),
+                dataset.unit,
+                getDecimals(chartConfig, i),
+            );
 
             tdCurrent.textContent = value;
             tdMaximum.textContent = value;
@@ -3308,21 +4254,51 @@ const createChartContainer = (
 
         if (!isPreview) {
             chartGroupInfo[groupIndex].charts.push({
-                title: chartConfig.title,
+                ...copyChartConfig(chartConfig),
                 chartType: "table",
-                dataCount: chartConfig.dataCount,
-                maximumValue: chartConfig.maximumValue,
-                height: chartConfig.height,
-                animationDuration: chartConfig.animationDuration,
-                showLegend: chartConfig.showLegend,
-                showLabels: chartConfig.showLabels,
-                autoColors: chartConfig.autoColors,
-                datasets: chartConfig.datasets,
                 indices: new Array(chartConfig.datasets.length).fill(0),
                 cells,
                 lastValues,
             });
         }
+    } else if (chartConfig.chartType === "stat") {
+        innerContainer.classList.add("chart-inner-container", "stat-container");
+
+        const dataset = chartConfig.datasets[0];
+
+        const value = document.createElement("p");
+        value.classList.add("stat-value");
+        value.style.color = dataset.color;
+        value.textContent = formatValue(
+            isPreview ? getRandomNumber() : NaN,
+            dataset.unit,
+            getDecimals(chartConfig, 0),
+        );
+
+        const trend = document.createElement("p");
+        trend.classList.add("stat-trend");
+
+        if (isPreview && chartConfig.trendCount) {
+            trend.textContent = formatTrend(
+                getRandomOffset(),
+                dataset.unit,
+                getDecimals(chartConfig, 0),
+            );
+        }
+
+        innerContainer.appendChild(value);
+        innerContainer.appendChild(trend);
+
+        if (!isPreview) {
+            chartGroupInfo[groupIndex].charts.push({
+                ...copyChartConfig(chartConfig),
+                chartType: "stat",
+                indices: new Array(chartConfig.datasets.length).fill(0),
+                value,
+                trend,
+                previousValues: [],
+            });
+        }
     } else {
         innerContainer.classList.add("chart-inner-container");
 
@@ -3378,7 +4354,10 @@ const createChartContainer = (
                             },
                         },
                         y: {
-                            min: 0,
+                            // the preview data is between 0 and 10
+                            min: isPreview
+                                ? 0
+                                : (chartConfig.minimumValue ?? 0),
                             ticks: {
                                 includeBounds: false,
                                 color: chartTextColor,
@@ -3403,7 +4382,7 @@ const createChartContainer = (
                             callbacks: {
                                 title: (_) => "",
                                 label: (item) =>
-                                    `${item.label}: ${formatValue((item.raw as Point).y, unit)}`,
+                                    `${item.label}: ${formatValue((item.raw as Point).y, unit, getDecimals(chartConfig, item.dataIndex))}`,
                             },
                         },
                         legend: {
@@ -3433,7 +4412,7 @@ const createChartContainer = (
                                             dataset.backgroundColor as string[]
                                         )[i];
                                         labels.push({
-                                            text: `${chart.data.labels![i]}: ${formatValue((dataset.data[i] as Point).y, unit)}`,
+                                            text: `${chart.data.labels![i]}: ${formatValue((dataset.data[i] as Point).y, unit, getDecimals(chartConfig, i))}`,
                                             fillStyle: color,
                                             fontColor: chartTextColor,
                                             hidden: false,
@@ -3465,31 +4444,36 @@ const createChartContainer = (
                 ].chart = chart;
             } else {
                 chartGroupInfo[groupIndex].charts.push({
-                    title: chartConfig.title,
+                    ...copyChartConfig(chartConfig),
                     chartType: "bar",
-                    dataCount: chartConfig.dataCount,
-                    maximumValue: chartConfig.maximumValue,
-                    height: chartConfig.height,
-                    animationDuration: chartConfig.animationDuration,
-                    showLegend: chartConfig.showLegend,
-                    showLabels: chartConfig.showLabels,
-                    autoColors: chartConfig.autoColors,
-                    datasets: chartConfig.datasets,
                     indices: new Array(chartConfig.datasets.length).fill(0),
                     chart,
                     yAxisMax: 0,
                 });
             }
-        } else if (chartConfig.chartType === "line") {
+        } else if (
+            chartConfig.chartType === "line" ||
+            chartConfig.chartType === "sparkline"
+        ) {
+            // sparklines are line charts without axes and legend
+            const isSparkline = chartConfig.chartType === "sparkline";
+            const isFilled = isSparkline && chartConfig.fill === true;
+
             const datasets = [];
             const unit = chartConfig.datasets[0].unit;
 
             for (const dataset of chartConfig.datasets) {
+                // the filled area is translucent so the lines behind it are still visible
+                const backgroundColor = isFilled
+                    ? `${dataset.color}40`
+                    : dataset.color;
+
                 datasets.push({
                     label: dataset.label,
                     data: [] as Point[],
-                    backgroundColor: dataset.color,
-                    hoverBackgroundColor: dataset.color,
+                    fill: isFilled,
+                    backgroundColor,
+                    hoverBackgroundColor: backgroundColor,
                     borderColor: dataset.color,
                     hoverBorderColor: dataset.color,
                 });
@@ -3538,7 +4522,7 @@ const createChartContainer = (
                 }
             }
 
-            const showLegend = chartConfig.showLegend;
+            const showLegend = chartConfig.showLegend && !isSparkline;
 
             const chart = new Chart(canvas, {
                 type: "line",
@@ -3548,6 +4532,7 @@ const createChartContainer = (
                 options: {
                     scales: {
                         x: {
+                            display: !isSparkline,
                             type: "timeseries",
                             time: {
                                 tooltipFormat: "HH:mm:ss",
@@ -3570,7 +4555,11 @@ const createChartContainer = (
                             },
                         },
                         y: {
-                            min: 0,
+                            display: !isSparkline,
+                            // the preview data is between 0 and 10
+                            min: isPreview
+                                ? 0
+                                : (chartConfig.minimumValue ?? 0),
                             ticks: {
                                 includeBounds: false,
                                 color: chartTextColor,
@@ -3609,7 +4598,7 @@ const createChartContainer = (
                             },
                             callbacks: {
                                 label: (item) =>
-                                    `${item.dataset.label}: ${formatValue((item.raw as Point).y, unit)}`,
+                                    `${item.dataset.label}: ${formatValue((item.raw as Point).y, unit, getDecimals(chartConfig, item.datasetIndex))}`,
                             },
                         },
                         legend: {
@@ -3635,7 +4624,7 @@ const createChartContainer = (
                                             dataset.backgroundColor!.toString();
 
                                         labels.push({
-                                            text: `${dataset.label!}: ${formatValue((dataset.data[lastDataIndex] as Point).y, unit)}`,
+                                            text: `${dataset.label!}: ${formatValue((dataset.data[lastDataIndex] as Point).y, unit, getDecimals(chartConfig, i))}`,
                                             fillStyle: color,
                                             fontColor: chartTextColor,
                                             hidden: !chart.isDatasetVisible(i),
@@ -3692,6 +4681,51 @@ const createChartContainer = (
                             ctx.lineTo(x, chart.chartArea.bottom);
                             ctx.stroke();
 
+                            ctx.restore();
+                        },
+                    },
+                    {
+                        id: "thresholdLines",
+                        beforeDatasetsDraw: (chart, _, __) => {
+                            // draw a dashed line across the chart at the value of each threshold,
+                            // with its label above the line
+
+                            const thresholds = chartConfig.thresholds ?? [];
+                            const area = chart.chartArea;
+                            const ctx = chart.ctx;
+
+                            ctx.save();
+
+                            ctx.lineWidth = 1;
+                            ctx.setLineDash([4, 4]);
+                            ctx.font = `0.75rem ${Chart.defaults.font.family}`;
+                            ctx.textBaseline = "bottom";
+
+                            for (const threshold of thresholds) {
+                                const y = chart.scales.y.getPixelForValue(
+                                    threshold.value,
+                                );
+
+                                if (y < area.top || y > area.bottom) {
+                                    continue;
+                                }
+
+                                ctx.strokeStyle = threshold.color;
+                                ctx.beginPath();
+                                ctx.moveTo(area.left, y);
+                                ctx.lineTo(area.right, y);
+                                ctx.stroke();
+
+                                if (threshold.label) {
+                                    ctx.fillStyle = threshold.color;
+                                    ctx.fillText(
+                                        threshold.label,
+                                        area.left + 4,
+                                        y - 2,
+                                    );
+                                }
+                            }
+
                             ctx.restore();
                         },
                     },
@@ -3706,24 +4740,220 @@ const createChartContainer = (
                 ].chart = chart;
             } else {
                 chartGroupInfo[groupIndex].charts.push({
-                    title: chartConfig.title,
-                    chartType: "line",
-                    dataCount: chartConfig.dataCount,
-                    maximumValue: chartConfig.maximumValue,
-                    height: chartConfig.height,
-                    animationDuration: chartConfig.animationDuration,
-                    showLegend: chartConfig.showLegend,
-                    showLabels: chartConfig.showLabels,
-                    autoColors: chartConfig.autoColors,
-                    datasets: chartConfig.datasets,
+                    ...copyChartConfig(chartConfig),
+                    chartType: isSparkline ? "sparkline" : "line",
                     indices: new Array(chartConfig.datasets.length).fill(0),
                     chart,
                     yAxisMax: 0,
                 });
             }
+        } else if (chartConfig.chartType === "heatmap") {
+            const dataset = chartConfig.datasets[0];
+
+            canvas.classList.add("heatmap-canvas");
+
+            const minimumValue = chartConfig.minimumValue ?? 0;
+            const bucketCount = chartConfig.bucketCount ?? 1;
+            const range = chartConfig.maximumValue - minimumValue;
+            const bucketSize = range / bucketCount;
+            const bucketColors = [];
+
+            // each range has the color of the threshold of its middle value
+            for (let i = 0; i < bucketCount; i++) {
+                const middleValue = minimumValue + (i + 0.5) * bucketSize;
+
+                bucketColors.push(
+                    getThreshold(chartConfig, middleValue)?.color ??
+                        dataset.color,
+                );
+            }
+
+            const heatmap: Heatmap = {
+                canvas,
+                bucketColors,
+                unit: dataset.unit,
+                decimals: getDecimals(chartConfig, 0),
+                minimumValue,
+                maximumValue: chartConfig.maximumValue,
+                bucketCount,
+                dataCount: chartConfig.dataCount,
+                buckets: [],
+                columns: [],
+            };
+
+            if (isPreview) {
+                for (let i = 0; i < chartConfig.dataCount; i++) {
+                    // values around the middle of the range
+                    addHeatmapValue(
+                        heatmap,
+                        minimumValue + range * (0.5 + getRandomOffset() / 4),
+                    );
+                }
+            }
+
+            // the size of the canvas is only known after the chart is added to the page
+            const resizeObserver = new ResizeObserver((_) =>
+                drawHeatmap(heatmap),
+            );
+            resizeObserver.observe(canvas);
+
+            if (!isPreview) {
+                chartGroupInfo[groupIndex].charts.push({
+                    ...copyChartConfig(chartConfig),
+                    chartType: "heatmap",
+                    indices: new Array(chartConfig.datasets.length).fill(0),
+                    heatmap,
+                    resizeObserver,
+                });
+            }
+        } else if (chartConfig.chartType === "scatter") {
+            // the first dataset is shown on the x axis, and the second on the y axis
+            const [xDataset, yDataset] = chartConfig.datasets;
+            const data: Point[] = [];
+
+            if (isPreview) {
+                for (let i = 0; i < chartConfig.dataCount; i++) {
+                    // y rises with x, like a fan speed with a temperature
+                    const x = getRandomNumber();
+
+                    data.push({
+                        x,
+                        y: x / 2 + 2 + getRandomOffset(),
+                    });
+                }
+            }
+
+            // each point has the color of the threshold of its y value
+            const pointColor = (context: ScriptableContext<"scatter">) => {
+                const point = context.raw as Point | undefined;
+
+                return point
+                    ? (getThreshold(chartConfig, point.y)?.color ??
+                          xDataset.color)
+                    : xDataset.color;
+            };
+
+            const chart = new Chart(canvas, {
+                type: "scatter",
+                data: {
+                    datasets: [
+                        {
+                            data,
+                            backgroundColor: pointColor,
+                            hoverBackgroundColor: pointColor,
+                            borderColor: pointColor,
+                            hoverBorderColor: pointColor,
+                        },
+                    ],
+                },
+                options: {
+                    scales: {
+                        x: {
+                            type: "linear",
+                            min: 0,
+                            max: chartConfig.xMaximumValue,
+                            title: {
+                                display: true,
+                                text: xDataset.label,
+                                color: chartTextColor,
+                            },
+                            ticks: {
+                                includeBounds: false,
+                                color: chartTextColor,
+                            },
+                            grid: {
+                                color: chartBackgroundColor,
+                            },
+                            border: {
+                                color: chartBorderColor,
+                            },
+                        },
+                        y: {
+                            // the preview data is between 0 and 10
+                            min: isPreview
+                                ? 0
+                                : (chartConfig.minimumValue ?? 0),
+                            // the axis is scaled to the values if the maximum value is not set
+                            max:
+                                chartConfig.maximumValue > 0
+                                    ? chartConfig.maximumValue
+                                    : undefined,
+                            title: {
+                                display: true,
+                                text: yDataset.label,
+                                color: chartTextColor,
+                            },
+                            ticks: {
+                                includeBounds: false,
+                                color: chartTextColor,
+                            },
+                            grid: {
+                                color: chartBackgroundColor,
+                            },
+                            border: {
+                                color: chartBorderColor,
+                            },
+                        },
+                    },
+                    elements: {
+                        point: {
+                            radius: 2,
+                            hoverRadius: 4,
+                        },
+                    },
+                    plugins: {
+                        tooltip: {
+                            displayColors: false,
+                            backgroundColor: chartTooltipBackgroundColor,
+                            titleColor: chartTooltipTextColor,
+                            bodyColor: chartTooltipTextColor,
+                            animation: {
+                                duration: chartConfig.animationDuration,
+                            },
+                            callbacks: {
+                                label: (item) => [
+                                    `${xDataset.label}: ${formatValue((item.raw as Point).x, xDataset.unit, getDecimals(chartConfig, 0))}`,
+                                    `${yDataset.label}: ${formatValue((item.raw as Point).y, yDataset.unit, getDecimals(chartConfig, 1))}`,
+                                ],
+                            },
+                        },
+                        legend: {
+                            display: false,
+                        },
+                    },
+                    animation: preferReducedMotion
+                        ? false
+                        : {
+                              duration: chartConfig.animationDuration,
+                          },
+                    parsing: false,
+                    maintainAspectRatio: false,
+                },
+            });
+
+            if (isPreview) {
+                chart.options.scales!.x!.max = 10;
+                chart.options.scales!.y!.max = 10;
+
+                chartGroupConfigs[groupIndex].chartConfigs[
+                    chartConfigIndex
+                ].chart = chart;
+            } else {
+                chartGroupInfo[groupIndex].charts.push({
+                    ...copyChartConfig(chartConfig),
+                    chartType: "scatter",
+                    indices: new Array(chartConfig.datasets.length).fill(0),
+                    chart,
+                });
+            }
         } else {
             const unit = chartConfig.datasets[0].unit;
+            const decimals = getDecimals(chartConfig, 0);
             const randomValue = getRandomNumber();
+            // the data of the arc starts at the minimum value, the preview data starts at 0
+            const minimumValue = isPreview
+                ? 0
+                : (chartConfig.minimumValue ?? 0);
 
             const chart = new Chart(canvas, {
                 type: "doughnut",
@@ -3790,8 +5020,10 @@ const createChartContainer = (
                             ctx.textAlign = "center";
                             ctx.fillText(
                                 formatValue(
-                                    chart.data.datasets[0].data[0],
+                                    chart.data.datasets[0].data[0] +
+                                        minimumValue,
                                     unit,
+                                    decimals,
                                 ),
                                 x,
                                 y,
@@ -3809,16 +5041,8 @@ const createChartContainer = (
                 ].chart = chart;
             } else {
                 chartGroupInfo[groupIndex].charts.push({
-                    title: chartConfig.title,
+                    ...copyChartConfig(chartConfig),
                     chartType: "gauge",
-                    dataCount: chartConfig.dataCount,
-                    maximumValue: chartConfig.maximumValue,
-                    height: chartConfig.height,
-                    animationDuration: chartConfig.animationDuration,
-                    showLegend: chartConfig.showLegend,
-                    showLabels: chartConfig.showLabels,
-                    autoColors: chartConfig.autoColors,
-                    datasets: chartConfig.datasets,
                     indices: new Array(chartConfig.datasets.length).fill(0),
                     chart,
                 });
@@ -4052,6 +5276,187 @@ const getRandomOffset = (): number => {
     return Math.random();
 };
 
+/**
+ * Returns the value of a reading in the unit of the dataset
+ */
+const convertValue = (
+    dataset: ChartConfig["datasets"][number],
+    value: number,
+): number => value * (dataset.scale ?? 1) + (dataset.offset ?? 0);
+
+/**
+ * Returns the decimal places of the values of a dataset, `undefined` to use the default of the
+ * unit
+ */
+const getDecimals = (
+    config: ChartConfig,
+    datasetIndex: number,
+): number | undefined =>
+    config.datasets[datasetIndex].decimals ?? config.decimals;
+
+/**
+ * Returns the last threshold with a value not above the value, `undefined` if there is none
+ */
+const getThreshold = (
+    config: ChartConfig,
+    value: number,
+): Threshold | undefined => {
+    let found: Threshold | undefined;
+
+    if (!config.thresholds || Number.isNaN(value)) {
+        return found;
+    }
+
+    for (const threshold of config.thresholds) {
+        if (threshold.value > value) {
+            break;
+        }
+
+        found = threshold;
+    }
+
+    return found;
+};
+
+/**
+ * Returns the difference between the current value and the average of the previous values, e.g.
+ * "▲ 1.5 °C"
+ */
+const formatTrend = (
+    difference: number,
+    unit: string,
+    decimals?: number,
+): string => {
+    if (Number.isNaN(difference)) {
+        return "";
+    }
+
+    let arrow = "=";
+
+    if (difference > 0) {
+        arrow = "▲";
+    } else if (difference < 0) {
+        arrow = "▼";
+    }
+
+    return `${arrow} ${formatValue(Math.abs(difference), unit, decimals)}`;
+};
+
+/**
+ * Adds a column with the number of the last values in each bucket, after counting the value in
+ * its bucket. Values outside of the range are counted in the first or last bucket.
+ */
+const addHeatmapValue = (heatmap: Heatmap, value: number) => {
+    let bucket = -1;
+
+    if (!Number.isNaN(value)) {
+        const range = heatmap.maximumValue - heatmap.minimumValue;
+        const ratio = range > 0 ? (value - heatmap.minimumValue) / range : 0;
+
+        bucket = Math.min(
+            Math.max(Math.floor(ratio * heatmap.bucketCount), 0),
+            heatmap.bucketCount - 1,
+        );
+    }
+
+    heatmap.buckets.push(bucket);
+
+    if (heatmap.buckets.length > heatmap.dataCount) {
+        heatmap.buckets.shift();
+    }
+
+    const column: number[] = new Array(heatmap.bucketCount).fill(0);
+
+    for (const x of heatmap.buckets) {
+        if (x !== -1) {
+            column[x] += 1;
+        }
+    }
+
+    heatmap.columns.push(column);
+
+    if (heatmap.columns.length > heatmap.dataCount) {
+        heatmap.columns.shift();
+    }
+};
+
+/**
+ * Draws the columns of a heatmap from the right, with the first bucket at the bottom. The more
+ * values a bucket has compared to the other buckets of the column, the less transparent it is.
+ */
+const drawHeatmap = (heatmap: Heatmap) => {
+    const canvas = heatmap.canvas;
+    const width = canvas.clientWidth;
+    const height = canvas.clientHeight;
+    const pixelRatio = window.devicePixelRatio;
+
+    // resizing the canvas also clears it
+    canvas.width = Math.round(width * pixelRatio);
+    canvas.height = Math.round(height * pixelRatio);
+
+    const ctx = canvas.getContext("2d");
+
+    if (!ctx || width === 0 || height === 0) {
+        return;
+    }
+
+    ctx.scale(pixelRatio, pixelRatio);
+
+    // the range of the values on the left
+    const maximumLabel = formatValue(
+        heatmap.maximumValue,
+        heatmap.unit,
+        heatmap.decimals,
+    );
+    const minimumLabel = formatValue(
+        heatmap.minimumValue,
+        heatmap.unit,
+        heatmap.decimals,
+    );
+
+    ctx.font = `0.75rem ${Chart.defaults.font.family}`;
+    ctx.fillStyle = chartTextColor;
+    ctx.textAlign = "right";
+
+    const labelWidth =
+        Math.max(
+            ctx.measureText(maximumLabel).width,
+            ctx.measureText(minimumLabel).width,
+        ) + 8;
+
+    ctx.textBaseline = "top";
+    ctx.fillText(maximumLabel, labelWidth - 8, 0);
+    ctx.textBaseline = "bottom";
+    ctx.fillText(minimumLabel, labelWidth - 8, height);
+
+    const cellWidth = (width - labelWidth) / heatmap.dataCount;
+    const cellHeight = height / heatmap.bucketCount;
+    const firstColumn = heatmap.dataCount - heatmap.columns.length;
+
+    for (let i = 0; i < heatmap.columns.length; i++) {
+        const column = heatmap.columns[i];
+        const maximumCount = Math.max(...column);
+
+        for (let j = 0; j < column.length; j++) {
+            if (column[j] === 0) {
+                continue;
+            }
+
+            ctx.fillStyle = heatmap.bucketColors[j];
+            ctx.globalAlpha = column[j] / maximumCount;
+            // draw slightly bigger cells so there are no gaps between them
+            ctx.fillRect(
+                labelWidth + (firstColumn + i) * cellWidth,
+                height - (j + 1) * cellHeight,
+                cellWidth + 0.5,
+                cellHeight + 0.5,
+            );
+        }
+    }
+
+    ctx.globalAlpha = 1;
+};
+
 const updateIndices = (sensors: SensorInfo) => {
     // eslint-disable-next-line @typescript-eslint/prefer-for-of
     for (let i = 0; i < chartGroupInfo.length; i++) {
@@ -4126,7 +5531,8 @@ const updateChartColors = () => {
             if (chart) {
                 if (
                     chartConfig.chartType === "bar" ||
-                    chartConfig.chartType === "line"
+                    chartConfig.chartType === "line" ||
+                    chartConfig.chartType === "scatter"
                 ) {
                     chart.options.scales!.x!.ticks!.color = chartTextColor;
                     chart.options.scales!.x!.border!.color = chartBorderColor;
@@ -4141,6 +5547,12 @@ const updateChartColors = () => {
                         chartTooltipTextColor;
                 }
 
+                if (chartConfig.chartType === "scatter") {
+                    chart.options.scales!.x!.grid!.color = chartBackgroundColor;
+                    chart.options.scales!.x!.title!.color = chartTextColor;
+                    chart.options.scales!.y!.title!.color = chartTextColor;
+                }
+
                 chart.update("none");
             }
         }
@@ -4148,7 +5560,12 @@ const updateChartColors = () => {
 
     for (const group of chartGroupInfo) {
         for (const chart of group.charts) {
-            if (chart.chartType === "bar" || chart.chartType === "line") {
+            if (
+                chart.chartType === "bar" ||
+                chart.chartType === "line" ||
+                chart.chartType === "sparkline" ||
+                chart.chartType === "scatter"
+            ) {
                 chart.chart.options.scales!.x!.ticks!.color = chartTextColor;
                 chart.chart.options.scales!.x!.border!.color = chartBorderColor;
                 chart.chart.options.scales!.y!.ticks!.color = chartTextColor;
@@ -4162,9 +5579,20 @@ const updateChartColors = () => {
                 chart.chart.options.plugins!.tooltip!.bodyColor =
                     chartTooltipTextColor;
 
+                if (chart.chartType === "scatter") {
+                    chart.chart.options.scales!.x!.grid!.color =
+                        chartBackgroundColor;
+                    chart.chart.options.scales!.x!.title!.color =
+                        chartTextColor;
+                    chart.chart.options.scales!.y!.title!.color =
+                        chartTextColor;
+                }
+
                 chart.chart.update("none");
             } else if (chart.chartType === "gauge") {
                 chart.chart.update("none");
+            } else if (chart.chartType === "heatmap") {
+                drawHeatmap(chart.heatmap);
             }
         }
     }
@@ -4186,7 +5614,9 @@ window
                 if (
                     chart.chartType === "bar" ||
                     chart.chartType === "line" ||
-                    chart.chartType === "gauge"
+                    chart.chartType === "sparkline" ||
+                    chart.chartType === "gauge" ||
+                    chart.chartType === "scatter"
                 ) {
                     chart.chart.options.animation = preferReducedMotion
                         ? false
@@ -4217,14 +5647,29 @@ interface GridBreakpoints {
 
 interface ChartConfig {
     title: string;
-    chartType: "bar" | "line" | "gauge" | "table";
-    dataCount: number; // number of data points in line chart
-    maximumValue: number; // max value for gauge chart
+    chartType:
+        | "bar"
+        | "line"
+        | "gauge"
+        | "table"
+        | "stat"
+        | "sparkline"
+        | "heatmap"
+        | "scatter";
+    dataCount: number; // number of data points in line, sparkline, heatmap and scatter charts
+    maximumValue: number; // max value for gauge and heatmap charts, and the y axis of scatter charts
     height: number;
     animationDuration: number;
     showLegend: boolean; // for bar and line charts
     showLabels: boolean; // for bar charts
     autoColors: boolean;
+    trendCount?: number; // number of previous values the trend of stat charts is calculated from
+    fill?: boolean; // fill the area under the line of sparkline charts
+    bucketCount?: number; // number of value ranges of heatmap charts
+    xMaximumValue?: number; // max value of the x axis of scatter charts
+    minimumValue?: number; // min value of the y axis, gauge and heatmap charts, 0 if not set
+    thresholds?: Threshold[]; // sorted by value
+    decimals?: number; // decimal places of the values, the default of the unit if not set
     datasets: {
         sensorId: number;
         sensorInstance: number;
@@ -4232,9 +5677,20 @@ interface ChartConfig {
         label: string;
         unit: string;
         color: string;
+        // the value is shown as `value * scale + offset`
+        scale?: number;
+        offset?: number;
+        decimals?: number; // overrides the decimals of the chart
     }[];
 }
 
+// applies from its value up to the value of the next threshold
+interface Threshold {
+    value: number;
+    color: string;
+    label?: string;
+}
+
 interface ChartGroupConfig {
     gridBreakpoints: GridBreakpoints;
     chartConfigs: ChartConfig[];
@@ -4267,16 +5723,22 @@ interface ChartConfigWithRefs extends ChartConfig {
         delete: HTMLButtonElement;
     };
     chart: Chart<
-        "bar" | "line" | "doughnut",
+        "bar" | "line" | "doughnut" | "scatter",
         Point[] | number[],
         unknown
     > | null;
 }
 
-type ChartInfo = ChartInfoBarLineChart | ChartInfoGaugeChart | ChartInfoTable;
+type ChartInfo =
+    | ChartInfoBarLineChart
+    | ChartInfoGaugeChart
+    | ChartInfoTable
+    | ChartInfoStat
+    | ChartInfoHeatmap
+    | ChartInfoScatterChart;
 
 interface ChartInfoBarLineChart extends ChartConfig {
-    chartType: "bar" | "line";
+    chartType: "bar" | "line" | "sparkline";
     indices: number[];
     chart: Chart<"bar" | "line", Point[], unknown>;
     yAxisMax: number; // maximum y value
@@ -4295,6 +5757,40 @@ interface ChartInfoTable extends ChartConfig {
     lastValues: number[];
 }
 
+interface ChartInfoStat extends ChartConfig {
+    chartType: "stat";
+    indices: number[];
+    value: HTMLParagraphElement;
+    trend: HTMLParagraphElement;
+    previousValues: number[]; // oldest first, up to `trendCount` values
+}
+
+interface ChartInfoHeatmap extends ChartConfig {
+    chartType: "heatmap";
+    indices: number[];
+    heatmap: Heatmap;
+    resizeObserver: ResizeObserver;
+}
+
+interface ChartInfoScatterChart extends ChartConfig {
+    chartType: "scatter";
+    indices: number[];
+    chart: Chart<"scatter", Point[], unknown>;
+}
+
+interface Heatmap {
+    canvas: HTMLCanvasElement;
+    bucketColors: string[];
+    unit: string;
+    decimals?: number;
+    minimumValue: number;
+    maximumValue: number;
+    bucketCount: number;
+    dataCount: number;
+    buckets: number[]; // bucket index of the last `dataCount` values, -1 if not found
+    columns: number[][]; // number of values in each bucket when each column was added
+}
+
 interface ChartGroupInfo {
     container: HTMLDivElement;
     gridBreakpoints: GridBreakpoints;
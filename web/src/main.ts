@@ -4,8 +4,9 @@ import {
     createDialogText,
 } from "./util.ts";
 
-const wsProtocol = window.location.protocol === "http:" ? "ws:" : "wss:";
-const wsUrl = `${wsProtocol}//${window.location.host}/data`;
+// URLs are relative to the page, so Jonitor can be served under the base path of a reverse proxy
+const wsUrl = new URL("data", window.location.href);
+wsUrl.protocol = window.location.protocol === "http:" ? "ws:" : "wss:";
 
 let lastPollTime = 0n;
 let sensors: SensorInfo;
@@ -75,7 +76,7 @@ ws.addEventListener("message", async (e) => {
 
         if (sensorsUpdatedCount !== lastSensorsUpdatedCount) {
             try {
-                const response = await fetch("/sensors");
+                const response = await fetch("sensors");
                 if (!response.ok) {
                     console.error(
                         `/sensors returned status ${response.status}`,
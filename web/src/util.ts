@@ -298,7 +298,11 @@ const addEmptySensorTexts = (container: HTMLElement, hasFooter: boolean) => {
     container.appendChild(textBottom);
 };
 
-const formatValue = (value: number, unit: string): string => {
+const formatValue = (
+    value: number,
+    unit: string,
+    decimals?: number,
+): string => {
     if (unit === "Yes/No") {
         return value === 0 ? "No" : "Yes";
     }
@@ -335,6 +339,11 @@ const formatValue = (value: number, unit: string): string => {
             digits = 0;
     }
 
+    // decimals set in the dashboard override the default of the unit
+    if (decimals !== undefined) {
+        digits = decimals;
+    }
+
     return `${value.toFixed(digits)} ${unit}`;
 };
 
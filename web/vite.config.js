@@ -1,11 +1,83 @@
+import { readdirSync, readFileSync, statSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+import { brotliCompressSync, constants, gzipSync } from "node:zlib";
 import { defineConfig } from "vite";
 
+// files smaller than this are not worth compressing
+const MINIMUM_COMPRESS_SIZE = 1024;
+
+const COMPRESSIBLE_EXTENSIONS = [
+    ".html",
+    ".js",
+    ".css",
+    ".svg",
+    ".json",
+    ".ico",
+    ".woff2",
+];
+
+/**
+ * Writes a `.gz` and `.br` file next to every compressible file in the output folder, which are
+ * embedded into jonitor.exe by the `embed-web` feature
+ */
+function precompress() {
+    let outDir = "";
+
+    const compressDir = (dir) => {
+        for (const name of readdirSync(dir)) {
+            const path = join(dir, name);
+
+            if (statSync(path).isDirectory()) {
+                compressDir(path);
+                continue;
+            }
+
+            if (!COMPRESSIBLE_EXTENSIONS.some((x) => name.endsWith(x))) {
+                continue;
+            }
+
+            const content = readFileSync(path);
+            if (content.length < MINIMUM_COMPRESS_SIZE) {
+                continue;
+            }
+
+            const gzip = gzipSync(content, { level: 9 });
+            const brotli = brotliCompressSync(content, {
+                params: { [constants.BROTLI_PARAM_QUALITY]: 11 },
+            });
+
+            // keep only the variants which are smaller, e.g. not for woff2 files
+            if (gzip.length < content.length) {
+                writeFileSync(`${path}.gz`, gzip);
+            }
+
+            if (brotli.length < content.length) {
+                writeFileSync(`${path}.br`, brotli);
+            }
+        }
+    };
+
+    return {
+        name: "precompress",
+        apply: "build",
+        configResolved(config) {
+            outDir = join(config.root, config.build.outDir);
+        },
+        closeBundle() {
+            compressDir(outDir);
+        },
+    };
+}
+
 export default defineConfig({
+    // relative URLs, so the same build works under any base_path
+    base: "./",
     define: {
         "import.meta.env.APP_VERSION": JSON.stringify(
             process.env.npm_package_version,
         ),
     },
+    plugins: [precompress()],
     server: {
         proxy: {
             "/data": "ws://127.0.0.1:10110",